@@ -0,0 +1,335 @@
+use std::fmt::{self, Display, Formatter};
+use std::thread;
+
+use crate::board::{TwoPlayerScore, TwoPlayers};
+use crate::game::{play_two_player_game, Player, TableEvent, TwoPlayerGame};
+use crate::CribbageCoreError;
+
+const SKUNK_LINE: u8 = 91;
+const DOUBLE_SKUNK_LINE: u8 = 61;
+const Z_95: f64 = 1.959_963_984_540_054;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ArenaConfig {
+    pub games: u32,
+    pub seed: u64,
+    pub alternate_dealer: bool,
+    pub threads: usize,
+}
+
+impl ArenaConfig {
+    pub fn new(games: u32, seed: u64) -> ArenaConfig {
+        ArenaConfig {
+            games,
+            seed,
+            ..ArenaConfig::default()
+        }
+    }
+}
+
+impl Default for ArenaConfig {
+    fn default() -> ArenaConfig {
+        ArenaConfig {
+            games: 1000,
+            seed: 0,
+            alternate_dealer: true,
+            threads: thread::available_parallelism().map_or(1, |threads| threads.get()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ArenaPlayerStats {
+    pub wins: u32,
+    pub skunks: u32,
+    pub double_skunks: u32,
+    pub hand_points: u64,
+    pub crib_points: u64,
+    pub pegging_points: u64,
+    pub deals_as_dealer: u64,
+}
+
+impl ArenaPlayerStats {
+    fn merge(&mut self, other: &ArenaPlayerStats) {
+        self.wins += other.wins;
+        self.skunks += other.skunks;
+        self.double_skunks += other.double_skunks;
+        self.hand_points += other.hand_points;
+        self.crib_points += other.crib_points;
+        self.pegging_points += other.pegging_points;
+        self.deals_as_dealer += other.deals_as_dealer;
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ArenaReport {
+    pub games: u32,
+    pub deals: u64,
+    pub players: TwoPlayerScore<ArenaPlayerStats>,
+}
+
+impl ArenaReport {
+    pub fn win_rate(&self, player: TwoPlayers) -> f64 {
+        ratio(u64::from(self.players[player].wins), u64::from(self.games))
+    }
+
+    // Wilson score interval at 95% confidence
+    pub fn win_rate_interval(&self, player: TwoPlayers) -> (f64, f64) {
+        if self.games == 0 {
+            return (0.0, 1.0);
+        }
+
+        let n = f64::from(self.games);
+        let p = self.win_rate(player);
+        let z2 = Z_95 * Z_95;
+        let center = (p + z2 / (2.0 * n)) / (1.0 + z2 / n);
+        let margin = Z_95 * (p * (1.0 - p) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+        ((center - margin).max(0.0), (center + margin).min(1.0))
+    }
+
+    pub fn skunk_rate(&self, player: TwoPlayers) -> f64 {
+        ratio(
+            u64::from(self.players[player].skunks),
+            u64::from(self.games),
+        )
+    }
+
+    pub fn average_hand_points(&self, player: TwoPlayers) -> f64 {
+        ratio(self.players[player].hand_points, self.deals)
+    }
+
+    pub fn average_crib_points(&self, player: TwoPlayers) -> f64 {
+        let stats = &self.players[player];
+        ratio(stats.crib_points, stats.deals_as_dealer)
+    }
+
+    pub fn average_pegging_points(&self, player: TwoPlayers) -> f64 {
+        ratio(self.players[player].pegging_points, self.deals)
+    }
+
+    pub fn average_game_length(&self) -> f64 {
+        ratio(self.deals, u64::from(self.games))
+    }
+
+    fn merge(&mut self, other: &ArenaReport) {
+        self.games += other.games;
+        self.deals += other.deals;
+        for &player in &[TwoPlayers::PlayerOne, TwoPlayers::PlayerTwo] {
+            self.players[player].merge(&other.players[player]);
+        }
+    }
+
+    fn record(&mut self, game: &TwoPlayerGame, winner: TwoPlayers) {
+        self.games += 1;
+        let mut dealer = TwoPlayers::PlayerOne;
+        for event in game.events() {
+            match *event {
+                TableEvent::DealStarted { dealer: next } => {
+                    dealer = next;
+                    self.deals += 1;
+                    self.players[dealer].deals_as_dealer += 1;
+                }
+                TableEvent::StarterCut { heels, .. } => {
+                    self.players[dealer].pegging_points += u64::from(heels);
+                }
+                TableEvent::Played { player, points, .. }
+                | TableEvent::SequenceEnded { player, points } => {
+                    self.players[player].pegging_points += u64::from(points);
+                }
+                TableEvent::Shown {
+                    player,
+                    is_crib,
+                    points,
+                    ..
+                } => {
+                    let stats = &mut self.players[player];
+                    if is_crib {
+                        stats.crib_points += u64::from(points);
+                    } else {
+                        stats.hand_points += u64::from(points);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let loser_score = game.score(winner.other());
+        let stats = &mut self.players[winner];
+        stats.wins += 1;
+        if loser_score < SKUNK_LINE {
+            stats.skunks += 1;
+        }
+
+        if loser_score < DOUBLE_SKUNK_LINE {
+            stats.double_skunks += 1;
+        }
+    }
+}
+
+impl Display for ArenaReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} games, {:.2} deals per game",
+            self.games,
+            self.average_game_length()
+        )?;
+        for (name, player) in &[
+            ("Player 1", TwoPlayers::PlayerOne),
+            ("Player 2", TwoPlayers::PlayerTwo),
+        ] {
+            let (low, high) = self.win_rate_interval(*player);
+            writeln!(
+                f,
+                "{}: {:.1}% wins (95% CI {:.1}%-{:.1}%), {:.1}% skunks, {:.2} hand, {:.2} crib, {:.2} pegging",
+                name,
+                100.0 * self.win_rate(*player),
+                100.0 * low,
+                100.0 * high,
+                100.0 * self.skunk_rate(*player),
+                self.average_hand_points(*player),
+                self.average_crib_points(*player),
+                self.average_pegging_points(*player),
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+// Plays `config.games` seeded games between fresh agents built by the two factories. Game `i`
+// uses seed `config.seed + i`, so a run is reproducible whatever the thread count
+pub fn run_arena<A, B>(
+    config: &ArenaConfig,
+    player_one: A,
+    player_two: B,
+) -> Result<ArenaReport, CribbageCoreError>
+where
+    A: Fn(u64) -> Box<dyn Player + Send> + Sync,
+    B: Fn(u64) -> Box<dyn Player + Send> + Sync,
+{
+    let threads = config.threads.clamp(1, config.games.max(1) as usize);
+    let results: Vec<Result<ArenaReport, CribbageCoreError>> = thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let player_one = &player_one;
+                let player_two = &player_two;
+                scope.spawn(move || {
+                    let mut report = ArenaReport::default();
+                    for index in (worker..config.games as usize).step_by(threads) {
+                        let seed = config.seed.wrapping_add(index as u64);
+                        let first_dealer = if config.alternate_dealer && index % 2 == 1 {
+                            TwoPlayers::PlayerTwo
+                        } else {
+                            TwoPlayers::PlayerOne
+                        };
+
+                        let mut game = TwoPlayerGame::with_seed(first_dealer, seed);
+                        let mut one = player_one(bot_seed(seed, TwoPlayers::PlayerOne));
+                        let mut two = player_two(bot_seed(seed, TwoPlayers::PlayerTwo));
+                        let winner = play_two_player_game(&mut game, one.as_mut(), two.as_mut())?;
+                        report.record(&game, winner);
+                    }
+
+                    Ok(report)
+                })
+            })
+            .collect();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().expect("arena worker panicked"))
+            .collect()
+    });
+
+    let mut report = ArenaReport::default();
+    for result in results {
+        report.merge(&result?);
+    }
+
+    Ok(report)
+}
+
+// Mixes a game's seed with the seat (the splitmix64 step), so that neither bot's random choices
+// follow the stream that shuffles the deck or each other's
+fn bot_seed(seed: u64, seat: TwoPlayers) -> u64 {
+    let seat = match seat {
+        TwoPlayers::PlayerOne => 1,
+        TwoPlayers::PlayerTwo => 2,
+    };
+
+    let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15_u64.wrapping_mul(seat));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+fn ratio(numerator: u64, denominator: u64) -> f64 {
+    if denominator == 0 {
+        0.0
+    } else {
+        numerator as f64 / denominator as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::arena::{bot_seed, run_arena, ArenaConfig};
+    use crate::board::TwoPlayers;
+    use crate::bots::{GreedyBot, RandomBot};
+    use crate::game::Player;
+
+    fn random(seed: u64) -> Box<dyn Player + Send> {
+        Box::new(RandomBot::new(seed))
+    }
+
+    fn greedy(_: u64) -> Box<dyn Player + Send> {
+        Box::new(GreedyBot::new())
+    }
+
+    #[test]
+    fn test_arena_report() {
+        let one = TwoPlayers::PlayerOne;
+        let two = TwoPlayers::PlayerTwo;
+        let config = ArenaConfig {
+            threads: 3,
+            ..ArenaConfig::new(40, 7)
+        };
+        let report = run_arena(&config, greedy, random).unwrap();
+        assert_eq!(report.games, 40);
+        assert_eq!(report.players[one].wins + report.players[two].wins, 40);
+        assert!(report.win_rate(one) > report.win_rate(two));
+        assert_eq!(
+            report.players[one].deals_as_dealer + report.players[two].deals_as_dealer,
+            report.deals
+        );
+
+        let (low, high) = report.win_rate_interval(one);
+        assert!(low < report.win_rate(one) && report.win_rate(one) <= high);
+        assert!(report.average_game_length() > 5.0 && report.average_game_length() < 15.0);
+        assert!(report.average_hand_points(one) > report.average_hand_points(two));
+        assert!(report.average_crib_points(one) > 0.0);
+        assert!(report.average_pegging_points(two) > 0.0);
+        assert!(report.skunk_rate(one) >= report.skunk_rate(two));
+        assert!(report.to_string().starts_with("40 games"));
+
+        let single = run_arena(
+            &ArenaConfig {
+                threads: 1,
+                ..config
+            },
+            greedy,
+            random,
+        )
+        .unwrap();
+        assert_eq!(single, report);
+    }
+    #[test]
+    fn test_bot_seeds_differ_from_deck_seed() {
+        for seed in [0, 1, 7, u64::MAX] {
+            let one = bot_seed(seed, TwoPlayers::PlayerOne);
+            let two = bot_seed(seed, TwoPlayers::PlayerTwo);
+            assert!(one != seed && two != seed && one != two, "seed {}", seed);
+        }
+    }
+}
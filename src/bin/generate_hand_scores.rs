@@ -0,0 +1,60 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::str::FromStr;
+
+use cribbage_core::{
+    generate_hand_score_directory, generate_hand_scores_for_cut_rank, hand_score_file_name,
+    HandScoreWriter, Rank,
+};
+
+const USAGE: &str = "Usage: generate_hand_scores [OUTPUT_DIRECTORY] [RANK...]";
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let directory = match args.next() {
+        Some(ref arg) if arg == "-h" || arg == "--help" => {
+            println!("{}", USAGE);
+            return;
+        }
+        Some(arg) => PathBuf::from(arg),
+        None => PathBuf::from("resources/cribbage_hand_scores"),
+    };
+
+    let mut ranks = Vec::new();
+    for arg in args {
+        match Rank::from_str(&arg) {
+            Ok(rank) => ranks.push(rank),
+            Err(_) => {
+                eprintln!("Invalid rank: {}\n{}", arg, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+
+    if let Err(error) = fs::create_dir_all(&directory) {
+        eprintln!("Unable to create {:?}: {}", directory, error);
+        process::exit(1);
+    }
+
+    if ranks.is_empty() {
+        println!("Writing: {:?}", directory);
+        if let Err(error) = generate_hand_score_directory(&directory) {
+            eprintln!("Unable to write {:?}: {}", directory, error);
+            process::exit(1);
+        }
+        return;
+    }
+
+    for rank in ranks {
+        let path = directory.join(hand_score_file_name(rank));
+        println!("Writing: {:?}", path);
+        let result = HandScoreWriter::create(&path)
+            .and_then(|mut writer| generate_hand_scores_for_cut_rank(rank, &mut writer));
+        if let Err(error) = result {
+            eprintln!("Unable to write {:?}: {}", path, error);
+            process::exit(1);
+        }
+    }
+}
@@ -1,17 +1,38 @@
 use std::ops::{Index, IndexMut};
 
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::CribbageCoreError;
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TwoPlayers {
     PlayerOne,
     PlayerTwo,
 }
 
+impl TwoPlayers {
+    pub fn other(self) -> TwoPlayers {
+        match self {
+            TwoPlayers::PlayerOne => TwoPlayers::PlayerTwo,
+            TwoPlayers::PlayerTwo => TwoPlayers::PlayerOne,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct TwoPlayerScore<T> {
     scores: [T; 2],
 }
 
+impl<T> TwoPlayerScore<T> {
+    pub fn new(scores: [T; 2]) -> TwoPlayerScore<T> {
+        TwoPlayerScore { scores }
+    }
+}
+
 impl<T> Index<TwoPlayers> for TwoPlayerScore<T> {
     type Output = T;
 
@@ -33,12 +54,15 @@ impl<T> IndexMut<TwoPlayers> for TwoPlayerScore<T> {
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum ThreePlayers {
     PlayerOne,
     PlayerTwo,
     PlayerThree,
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct ThreePlayerScore<T> {
     scores: [T; 3],
 }
@@ -66,6 +90,7 @@ impl<T> IndexMut<ThreePlayers> for ThreePlayerScore<T> {
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum FourPlayers {
     PlayerOne,
     PlayerTwo,
@@ -73,6 +98,8 @@ pub enum FourPlayers {
     PlayerFour,
 }
 
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(transparent))]
 pub struct FourPlayerScore<T> {
     scores: [T; 4],
 }
@@ -123,6 +150,7 @@ impl Score for u8 {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct Board<P, S>
 where
     S: Index<P>,
@@ -173,11 +201,92 @@ where
         self.scores[id]
     }
 
+    pub fn target(&self) -> S::Output {
+        self.target
+    }
+
     pub fn winner(&self) -> Option<P> {
         self.winner
     }
 }
 
+#[cfg(feature = "serde")]
+impl<P, S> Serialize for Board<P, S>
+where
+    P: Serialize,
+    S: Index<P> + Serialize,
+    S::Output: Serialize + Sized,
+{
+    fn serialize<Z: Serializer>(&self, serializer: Z) -> Result<Z::Ok, Z::Error> {
+        use serde::ser::SerializeStruct;
+
+        let mut board = serializer.serialize_struct("Board", 3)?;
+        board.serialize_field("scores", &self.scores)?;
+        board.serialize_field("target", &self.target)?;
+        board.serialize_field("winner", &self.winner)?;
+        board.end()
+    }
+}
+
+// A deserialized board must be one `add_points` could have produced: nobody past the target and
+// a winner exactly when someone reached it
+#[cfg(feature = "serde")]
+macro_rules! deserialize_board {
+    ($players:ident, $scores:ident, [$($player:ident),+]) => {
+        impl<'de, T> Deserialize<'de> for Board<$players, $scores<T>>
+        where
+            T: Score + Deserialize<'de>,
+        {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                #[derive(Deserialize)]
+                #[serde(rename = "Board")]
+                struct Fields<T> {
+                    scores: $scores<T>,
+                    target: T,
+                    winner: Option<$players>,
+                }
+
+                let fields = Fields::<T>::deserialize(deserializer)?;
+                let mut leader = None;
+                for &player in &[$($players::$player),+] {
+                    if fields.scores[player] > fields.target {
+                        return Err(de::Error::custom("score beyond the target"));
+                    }
+
+                    if fields.scores[player] == fields.target {
+                        leader = Some(player);
+                    }
+                }
+
+                if fields.winner != leader {
+                    return Err(de::Error::custom("winner does not match the scores"));
+                }
+
+                Ok(Board {
+                    scores: fields.scores,
+                    target: fields.target,
+                    winner: fields.winner,
+                })
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+deserialize_board!(TwoPlayers, TwoPlayerScore, [PlayerOne, PlayerTwo]);
+#[cfg(feature = "serde")]
+deserialize_board!(
+    ThreePlayers,
+    ThreePlayerScore,
+    [PlayerOne, PlayerTwo, PlayerThree]
+);
+#[cfg(feature = "serde")]
+deserialize_board!(
+    FourPlayers,
+    FourPlayerScore,
+    [PlayerOne, PlayerTwo, PlayerThree, PlayerFour]
+);
+
 pub fn custom_board<P, S>(scores: S, target: S::Output) -> Board<P, S>
 where
     S: Index<P>,
@@ -203,6 +312,7 @@ pub fn standard_four_player_board() -> Board<FourPlayers, FourPlayerScore<u8>> {
 }
 
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod tests {
     use crate::board::{
         standard_four_player_board, standard_three_player_board, standard_two_player_board,
@@ -254,6 +364,9 @@ mod tests {
             board.add_points(TwoPlayers::PlayerTwo, 1),
             Err(CribbageCoreError::WinnerExists)
         );
+        assert_eq!(board.target(), 121);
+        assert_eq!(TwoPlayers::PlayerOne.other(), TwoPlayers::PlayerTwo);
+        assert_eq!(TwoPlayers::PlayerTwo.other(), TwoPlayers::PlayerOne);
     }
 
     #[test]
@@ -421,4 +534,32 @@ mod tests {
             Err(CribbageCoreError::WinnerExists)
         );
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_board_serde() {
+        use crate::board::{Board, TwoPlayerScore};
+
+        let mut board = standard_two_player_board();
+        board.add_points(TwoPlayers::PlayerTwo, 17).unwrap();
+        let json = serde_json::to_string(&board).unwrap();
+        assert_eq!(json, r#"{"scores":[0,17],"target":121,"winner":null}"#);
+        let restored: Board<TwoPlayers, TwoPlayerScore<u8>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.score(TwoPlayers::PlayerTwo), 17);
+
+        let finished = r#"{"scores":[121,17],"target":121,"winner":"PlayerOne"}"#;
+        let restored: Board<TwoPlayers, TwoPlayerScore<u8>> =
+            serde_json::from_str(finished).unwrap();
+        assert_eq!(restored.winner(), Some(TwoPlayers::PlayerOne));
+
+        for json in &[
+            r#"{"scores":[122,17],"target":121,"winner":"PlayerOne"}"#,
+            r#"{"scores":[121,17],"target":121,"winner":null}"#,
+            r#"{"scores":[0,17],"target":121,"winner":"PlayerTwo"}"#,
+            r#"{"scores":[0,17,0],"target":121,"winner":null}"#,
+        ] {
+            let board = serde_json::from_str::<Board<TwoPlayers, TwoPlayerScore<u8>>>(json);
+            assert!(board.is_err(), "{}", json);
+        }
+    }
 }
@@ -0,0 +1,90 @@
+use crate::bots::pegging_points;
+use crate::card::Card;
+use crate::discard::analyze_discards;
+use crate::game::{Player, Situation};
+use crate::hand::{FourPlayerDeal, ThreePlayerDeal, TwoPlayerDeal};
+use crate::pegging::Pegger;
+
+#[derive(Default)]
+pub struct GreedyBot;
+
+impl GreedyBot {
+    pub fn new() -> GreedyBot {
+        GreedyBot
+    }
+
+    fn best_discard(cards: &[Card]) -> Vec<Card> {
+        match analyze_discards(cards, false, 0, 0) {
+            Ok(options) => options[0].discard.clone(),
+            Err(_) => cards[4..].to_vec(),
+        }
+    }
+}
+
+impl Player for GreedyBot {
+    fn choose_discard(&mut self, deal: &TwoPlayerDeal, _situation: &Situation<'_>) -> [Card; 2] {
+        let discard = GreedyBot::best_discard(deal.cards());
+        [discard[0], discard[1]]
+    }
+
+    fn choose_three_player_discard(
+        &mut self,
+        deal: &ThreePlayerDeal,
+        _situation: &Situation<'_>,
+    ) -> Card {
+        GreedyBot::best_discard(deal.cards())[0]
+    }
+
+    fn choose_four_player_discard(
+        &mut self,
+        deal: &FourPlayerDeal,
+        _situation: &Situation<'_>,
+    ) -> Card {
+        GreedyBot::best_discard(deal.cards())[0]
+    }
+
+    fn choose_peg(
+        &mut self,
+        pegger: &Pegger,
+        _hand: &[Card],
+        legal: &[Card],
+        _situation: &Situation<'_>,
+    ) -> Card {
+        let mut best = legal[0];
+        let mut best_points = pegging_points(pegger, best);
+        for &card in &legal[1..] {
+            let points = pegging_points(pegger, card);
+            if points > best_points {
+                best = card;
+                best_points = points;
+            }
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::GreedyBot;
+    use crate::card::Card;
+    use crate::game::{Player, Situation, TwoPlayerGame};
+    use crate::pegging::Pegger;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_greedy_bot_takes_fifteen() {
+        let game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 0);
+        let mut pegger = Pegger::new();
+        pegger.play_card(Card::from_str("TH").unwrap()).unwrap();
+        let legal = [Card::from_str("4C").unwrap(), Card::from_str("5C").unwrap()];
+        let card = GreedyBot::new().choose_peg(
+            &pegger,
+            &legal,
+            &legal,
+            &Situation::new(&game, TwoPlayers::PlayerTwo),
+        );
+        assert_eq!(card, legal[1]);
+    }
+}
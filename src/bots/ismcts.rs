@@ -0,0 +1,748 @@
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{custom_board, Board, TwoPlayerScore, TwoPlayers};
+use crate::bots::{pegging_points, StrongBot};
+use crate::card::{Card, Rank};
+use crate::card_set::CardSet;
+use crate::discard::analyze_discards;
+use crate::game::{Player, Situation, TableEvent};
+use crate::hand::{score_hand, FourPlayerDeal, ThreePlayerDeal, TwoPlayerDeal};
+use crate::pegging::{Pegger, PeggingRound};
+use crate::pegging_analysis::PeggingView;
+use crate::position::{RaceEstimator, WinEstimator};
+
+const EXPLORATION: f64 = 0.7;
+const DISCARD_CANDIDATES: usize = 4;
+const CRIB_SAMPLES: usize = 200;
+const ROLLOUT_CUTS: usize = 4;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct IsmctsConfig {
+    pub iterations: usize,
+    pub threads: usize,
+    pub seed: u64,
+}
+
+impl IsmctsConfig {
+    pub fn new(iterations: usize, threads: usize, seed: u64) -> IsmctsConfig {
+        IsmctsConfig {
+            iterations,
+            threads,
+            seed,
+        }
+    }
+}
+
+impl Default for IsmctsConfig {
+    fn default() -> IsmctsConfig {
+        IsmctsConfig::new(1000, 1, 0)
+    }
+}
+
+pub struct IsmctsBot {
+    config: IsmctsConfig,
+    rng: StdRng,
+    kept: Vec<Card>,
+    crib_part: Vec<Card>,
+    fallback: StrongBot,
+}
+
+impl IsmctsBot {
+    pub fn new(config: IsmctsConfig) -> IsmctsBot {
+        IsmctsBot {
+            config,
+            rng: StdRng::seed_from_u64(config.seed),
+            kept: Vec::new(),
+            crib_part: Vec::new(),
+            fallback: StrongBot::new(config.seed),
+        }
+    }
+
+    pub fn config(&self) -> IsmctsConfig {
+        self.config
+    }
+
+    fn search<F>(&mut self, determinize: F) -> Option<Action>
+    where
+        F: Fn(&mut StdRng) -> SimState + Sync,
+    {
+        let threads = match self.config.threads {
+            0 => thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        let iterations = self.config.iterations.max(1);
+        let per_thread = (iterations + threads - 1) / threads;
+        let seeds: Vec<u64> = (0..threads).map(|_| self.rng.gen()).collect();
+        let determinize = &determinize;
+
+        let results: Vec<Vec<(Action, u32, f64)>> = if threads == 1 {
+            vec![run_search(determinize, per_thread, seeds[0])]
+        } else {
+            thread::scope(|scope| {
+                let handles: Vec<_> = seeds
+                    .iter()
+                    .map(|&seed| scope.spawn(move || run_search(determinize, per_thread, seed)))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("search thread panicked"))
+                    .collect()
+            })
+        };
+
+        let mut totals: Vec<(Action, u32, f64)> = Vec::new();
+        for (action, visits, wins) in results.into_iter().flatten() {
+            match totals.iter_mut().find(|(a, _, _)| *a == action) {
+                Some((_, total_visits, total_wins)) => {
+                    *total_visits += visits;
+                    *total_wins += wins;
+                }
+                None => totals.push((action, visits, wins)),
+            }
+        }
+
+        // Values between moves are often a fraction of a percent apart, which leaves visit counts
+        // close to even, so pick on the mean instead
+        totals
+            .into_iter()
+            .filter(|&(_, visits, _)| visits > 0)
+            .max_by(|a, b| (a.2 / f64::from(a.1)).total_cmp(&(b.2 / f64::from(b.1))))
+            .map(|(action, _, _)| action)
+    }
+}
+
+impl Player for IsmctsBot {
+    fn choose_discard(&mut self, deal: &TwoPlayerDeal, situation: &Situation<'_>) -> [Card; 2] {
+        let cards = deal.cards().to_vec();
+
+        // Shortlist throws by expected points so the search spends its iterations on the ones
+        // where pegging and the board can actually change the decision
+        let candidates: Vec<[Card; 2]> =
+            analyze_discards(&cards, situation.is_dealer(), CRIB_SAMPLES, self.rng.gen())
+                .unwrap_or_default()
+                .into_iter()
+                .take(DISCARD_CANDIDATES)
+                .map(|option| [option.discard[0], option.discard[1]])
+                .collect();
+
+        let action = self.search(|rng| SimState::for_discard(&cards, &candidates, situation, rng));
+        let crib = match action {
+            Some(Action::Discard(crib)) if crib.iter().all(|card| cards.contains(card)) => crib,
+            _ => self.fallback.choose_discard(deal, situation),
+        };
+
+        self.kept = cards
+            .iter()
+            .copied()
+            .filter(|card| !crib.contains(card))
+            .collect();
+        self.crib_part = crib.to_vec();
+        crib
+    }
+
+    fn choose_three_player_discard(
+        &mut self,
+        deal: &ThreePlayerDeal,
+        situation: &Situation<'_>,
+    ) -> Card {
+        self.fallback.choose_three_player_discard(deal, situation)
+    }
+
+    fn choose_four_player_discard(
+        &mut self,
+        deal: &FourPlayerDeal,
+        situation: &Situation<'_>,
+    ) -> Card {
+        self.fallback.choose_four_player_discard(deal, situation)
+    }
+
+    fn choose_peg(
+        &mut self,
+        pegger: &Pegger,
+        hand: &[Card],
+        legal: &[Card],
+        situation: &Situation<'_>,
+    ) -> Card {
+        if legal.len() == 1 {
+            return legal[0];
+        }
+
+        // Only trust the remembered discard if it belongs to this deal
+        let known = CardSet::from_cards(hand).is_subset(CardSet::from_cards(&self.kept));
+        let mut view = PeggingView::from_situation(hand, pegger, situation);
+        if known {
+            view.observe_cards(CardSet::from_cards(&self.crib_part));
+        }
+
+        let kept = if known { self.kept.clone() } else { Vec::new() };
+        let crib_part = if known {
+            self.crib_part.clone()
+        } else {
+            Vec::new()
+        };
+        let action =
+            self.search(|rng| SimState::for_pegging(&view, &kept, &crib_part, situation, rng));
+        match action {
+            Some(Action::Play(card)) if legal.contains(&card) => card,
+            _ => self.fallback.choose_peg(pegger, hand, legal, situation),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Action {
+    Discard([Card; 2]),
+    Play(Card),
+}
+
+#[derive(Clone, Debug)]
+struct SimState {
+    board: Board<TwoPlayers, TwoPlayerScore<u8>>,
+    dealer: TwoPlayers,
+    deals: TwoPlayerScore<Option<Vec<Card>>>,
+    kept: TwoPlayerScore<Vec<Card>>,
+    crib: Vec<Card>,
+    deck: Vec<Card>,
+    starter: Option<Card>,
+    round: PeggingRound,
+    candidates: Vec<[Card; 2]>,
+    deal_number: u32,
+}
+
+impl SimState {
+    fn from_situation(situation: &Situation<'_>, deck: Vec<Card>) -> SimState {
+        let mut scores = TwoPlayerScore::default();
+        scores[situation.player] = situation.own_score;
+        scores[situation.player.other()] = situation.opponent_score;
+        SimState {
+            board: custom_board(scores, situation.target),
+            dealer: situation.dealer,
+            deals: TwoPlayerScore::default(),
+            kept: TwoPlayerScore::default(),
+            crib: Vec::new(),
+            deck,
+            starter: situation.starter,
+            round: PeggingRound::new(TwoPlayerScore::default(), situation.dealer.other()),
+            candidates: Vec::new(),
+            deal_number: 0,
+        }
+    }
+
+    fn for_discard<R: Rng + ?Sized>(
+        cards: &[Card],
+        candidates: &[[Card; 2]],
+        situation: &Situation<'_>,
+        rng: &mut R,
+    ) -> SimState {
+        let mut unseen = CardSet::from_cards(cards).complement().to_vec();
+        unseen.shuffle(rng);
+        let opponent_deal = unseen.split_off(unseen.len() - 6);
+
+        let player = situation.player;
+        let mut state = SimState::from_situation(situation, unseen);
+        state.deals[player] = Some(cards.to_vec());
+        state.deals[player.other()] = Some(opponent_deal);
+        state.candidates = candidates.to_vec();
+
+        // The opponent's throw is hidden whether it has happened yet or not, so it's part of the
+        // sampled world rather than a node in the tree
+        let crib = state.rollout_discard(player.other(), rng);
+        state.discard(player.other(), crib);
+        state
+    }
+
+    fn for_pegging<R: Rng + ?Sized>(
+        view: &PeggingView,
+        kept: &[Card],
+        crib_part: &[Card],
+        situation: &Situation<'_>,
+        rng: &mut R,
+    ) -> SimState {
+        let player = situation.player;
+        let opponent = player.other();
+        let round = view.sample_round(player, rng);
+
+        let mut played: TwoPlayerScore<Vec<Card>> = TwoPlayerScore::default();
+        for event in situation.deal_events {
+            if let TableEvent::Played { player, card, .. } = *event {
+                played[player].push(card);
+            }
+        }
+
+        let mut unseen = (view.seen() | CardSet::from_cards(round.hand(opponent)))
+            .complement()
+            .to_vec();
+        unseen.shuffle(rng);
+
+        let mut state = SimState::from_situation(situation, Vec::new());
+        state.kept[player] = if kept.is_empty() {
+            played[player]
+                .iter()
+                .chain(round.hand(player))
+                .copied()
+                .collect()
+        } else {
+            kept.to_vec()
+        };
+        state.kept[opponent] = played[opponent]
+            .iter()
+            .chain(round.hand(opponent))
+            .copied()
+            .collect();
+        state.crib = crib_part.to_vec();
+        while state.crib.len() < 4 {
+            match unseen.pop() {
+                Some(card) => state.crib.push(card),
+                None => break,
+            }
+        }
+
+        state.deck = unseen;
+        state.round = round;
+        state
+    }
+
+    fn is_over(&self) -> bool {
+        self.board.winner().is_some()
+    }
+
+    fn win_probability(&self, player: TwoPlayers) -> f64 {
+        match self.board.winner() {
+            Some(winner) if winner == player => 1.0,
+            Some(_) => 0.0,
+            None => RaceEstimator.win_probability(
+                self.board.score(player),
+                self.board.score(player.other()),
+                self.dealer == player,
+                self.board.target(),
+            ),
+        }
+    }
+
+    fn pone(&self) -> TwoPlayers {
+        self.dealer.other()
+    }
+
+    fn to_move(&self) -> Option<TwoPlayers> {
+        if self.is_over() {
+            return None;
+        }
+
+        let pone = self.pone();
+        for &player in &[pone, self.dealer] {
+            if self.deals[player].is_some() {
+                return Some(player);
+            }
+        }
+
+        Some(self.round.turn())
+    }
+
+    fn legal_actions(&self) -> Vec<Action> {
+        let player = match self.to_move() {
+            Some(player) => player,
+            None => return Vec::new(),
+        };
+
+        match &self.deals[player] {
+            Some(_) if !self.candidates.is_empty() => self
+                .candidates
+                .iter()
+                .copied()
+                .map(Action::Discard)
+                .collect(),
+            Some(cards) => {
+                let mut actions = Vec::with_capacity(15);
+                for first in 0..cards.len() {
+                    for second in first + 1..cards.len() {
+                        actions.push(Action::Discard([cards[first], cards[second]]));
+                    }
+                }
+
+                actions
+            }
+            None => self
+                .round
+                .legal_plays()
+                .into_iter()
+                .map(Action::Play)
+                .collect(),
+        }
+    }
+
+    fn apply<R: Rng + ?Sized>(&mut self, action: Action, rng: &mut R) {
+        let player = match self.to_move() {
+            Some(player) => player,
+            None => return,
+        };
+
+        match action {
+            Action::Discard(crib) => self.discard(player, crib),
+            Action::Play(card) => {
+                if let Ok(outcome) = self.round.play(card) {
+                    self.award(player, outcome.total_points());
+                }
+
+                if self.round.is_finished() {
+                    self.show();
+                    self.start_deal(rng);
+                }
+            }
+        }
+    }
+
+    fn discard(&mut self, player: TwoPlayers, crib: [Card; 2]) {
+        if let Some(cards) = self.deals[player].take() {
+            self.kept[player] = cards
+                .into_iter()
+                .filter(|card| !crib.contains(card))
+                .collect();
+            self.crib.extend_from_slice(&crib);
+            self.candidates.clear();
+        }
+
+        if self.deals[player.other()].is_none() {
+            self.cut_starter();
+        }
+    }
+
+    fn award(&mut self, player: TwoPlayers, points: u8) {
+        if points > 0 {
+            let _ = self.board.add_points(player, points);
+        }
+    }
+
+    fn cut_starter(&mut self) {
+        let starter = match self.deck.pop() {
+            Some(starter) => starter,
+            None => return,
+        };
+
+        self.starter = Some(starter);
+        if starter.rank() == Rank::Jack {
+            self.award(self.dealer, 2);
+        }
+
+        let hands = self.kept.clone();
+        self.round = PeggingRound::new(hands, self.pone());
+    }
+
+    fn show(&mut self) {
+        let starter = match self.starter {
+            Some(starter) => starter,
+            None => return,
+        };
+
+        let pone = self.pone();
+        let dealer = self.dealer;
+        let shows = [
+            (pone, &self.kept[pone], false),
+            (dealer, &self.kept[dealer], false),
+            (dealer, &self.crib, true),
+        ];
+        let scores: Vec<(TwoPlayers, u8)> = shows
+            .iter()
+            .filter(|(_, cards, _)| cards.len() == 4)
+            .map(|&(player, cards, is_crib)| {
+                let cards = [cards[0], cards[1], cards[2], cards[3]];
+                (player, score_hand(cards, starter, is_crib))
+            })
+            .collect();
+
+        for (player, points) in scores {
+            self.award(player, points);
+        }
+    }
+
+    fn start_deal<R: Rng + ?Sized>(&mut self, rng: &mut R) {
+        if self.is_over() {
+            return;
+        }
+
+        self.dealer = self.pone();
+        self.deal_number += 1;
+        self.deck = CardSet::full().to_vec();
+        self.deck.shuffle(rng);
+        let pone = self.pone();
+        let dealer = self.dealer;
+        self.deals[pone] = Some(self.deck.split_off(self.deck.len() - 6));
+        self.deals[dealer] = Some(self.deck.split_off(self.deck.len() - 6));
+        self.kept = TwoPlayerScore::default();
+        self.crib.clear();
+        self.starter = None;
+        self.round = PeggingRound::new(TwoPlayerScore::default(), pone);
+    }
+
+    // Keep whichever four score best against a couple of random cuts; cheap enough to run for
+    // every deal of a playout
+    fn rollout_discard<R: Rng + ?Sized>(&self, player: TwoPlayers, rng: &mut R) -> [Card; 2] {
+        let cards = match &self.deals[player] {
+            Some(cards) => cards,
+            None => return [self.kept[player][0], self.kept[player][1]],
+        };
+
+        let cuts: Vec<Card> = self
+            .deck
+            .choose_multiple(rng, ROLLOUT_CUTS)
+            .copied()
+            .collect();
+        let mut best: Option<([Card; 2], u32)> = None;
+        for first in 0..cards.len() {
+            for second in first + 1..cards.len() {
+                let keep: Vec<Card> = (0..cards.len())
+                    .filter(|&index| index != first && index != second)
+                    .map(|index| cards[index])
+                    .collect();
+                let keep = [keep[0], keep[1], keep[2], keep[3]];
+                let total = cuts
+                    .iter()
+                    .map(|&cut| u32::from(score_hand(keep, cut, false)))
+                    .sum::<u32>();
+                if best.map_or(true, |(_, best_total)| total > best_total) {
+                    best = Some(([cards[first], cards[second]], total));
+                }
+            }
+        }
+
+        best.map_or([cards[0], cards[1]], |(crib, _)| crib)
+    }
+
+    fn rollout_action<R: Rng + ?Sized>(&self, rng: &mut R) -> Option<Action> {
+        let player = self.to_move()?;
+        if self.deals[player].is_some() {
+            return Some(Action::Discard(self.rollout_discard(player, rng)));
+        }
+
+        let mut legal = self.round.legal_plays();
+        legal.shuffle(rng);
+        legal
+            .into_iter()
+            .max_by_key(|&card| pegging_points(self.round.pegger(), card))
+            .map(Action::Play)
+    }
+}
+
+struct Node {
+    action: Option<Action>,
+    player: Option<TwoPlayers>,
+    visits: u32,
+    available: u32,
+    wins: f64,
+    children: Vec<usize>,
+}
+
+impl Node {
+    fn new(action: Option<Action>, player: Option<TwoPlayers>) -> Node {
+        Node {
+            action,
+            player,
+            visits: 0,
+            available: 0,
+            wins: 0.0,
+            children: Vec::new(),
+        }
+    }
+
+    fn ucb(&self) -> f64 {
+        let visits = f64::from(self.visits.max(1));
+        self.wins / visits + EXPLORATION * (f64::from(self.available.max(1)).ln() / visits).sqrt()
+    }
+}
+
+// Single-observer ISMCTS: every iteration samples the hidden cards and deck order afresh, and
+// the tree only grows through the current deal; later deals are left to the win estimate. Each
+// sampled world is played out once for every root move with the same rollout seed, since the
+// moves at the root are usually separated by far less than the luck of the cards
+fn run_search<F>(determinize: &F, iterations: usize, seed: u64) -> Vec<(Action, u32, f64)>
+where
+    F: Fn(&mut StdRng) -> SimState,
+{
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut nodes = vec![Node::new(None, None)];
+
+    for _ in 0..iterations {
+        let world = determinize(&mut rng);
+        let player = match world.to_move() {
+            Some(player) => player,
+            None => break,
+        };
+
+        let legal = world.legal_actions();
+        let rollout_seed = rng.gen();
+        for &action in &legal {
+            let child = child_for(&mut nodes, 0, action, player);
+            nodes[child].available += 1;
+
+            let mut rng = StdRng::seed_from_u64(rollout_seed);
+            let mut state = world.clone();
+            state.apply(action, &mut rng);
+            iterate(&mut nodes, state, child, &mut rng);
+        }
+    }
+
+    nodes[0]
+        .children
+        .iter()
+        .filter_map(|&child| {
+            let node = &nodes[child];
+            node.action.map(|action| (action, node.visits, node.wins))
+        })
+        .collect()
+}
+
+fn child_for(nodes: &mut Vec<Node>, parent: usize, action: Action, player: TwoPlayers) -> usize {
+    if let Some(&child) = nodes[parent]
+        .children
+        .iter()
+        .find(|&&child| nodes[child].action == Some(action))
+    {
+        return child;
+    }
+
+    let child = nodes.len();
+    nodes.push(Node::new(Some(action), Some(player)));
+    nodes[parent].children.push(child);
+    child
+}
+
+fn iterate(nodes: &mut Vec<Node>, mut state: SimState, start: usize, rng: &mut StdRng) {
+    let mut path = vec![start];
+    let mut current = start;
+
+    while state.deal_number == 0 {
+        let player = match state.to_move() {
+            Some(player) => player,
+            None => break,
+        };
+
+        let legal = state.legal_actions();
+        let untried: Vec<Action> = legal
+            .iter()
+            .copied()
+            .filter(|&action| {
+                !nodes[current]
+                    .children
+                    .iter()
+                    .any(|&child| nodes[child].action == Some(action))
+            })
+            .collect();
+
+        let expanding = untried.choose(rng).copied();
+        if let Some(action) = expanding {
+            child_for(nodes, current, action, player);
+        }
+
+        let available: Vec<usize> = nodes[current]
+            .children
+            .iter()
+            .copied()
+            .filter(|&child| nodes[child].action.is_some_and(|a| legal.contains(&a)))
+            .collect();
+        for &child in &available {
+            nodes[child].available += 1;
+        }
+
+        let next = match expanding {
+            Some(action) => child_for(nodes, current, action, player),
+            None => match available
+                .into_iter()
+                .max_by(|&a, &b| nodes[a].ucb().total_cmp(&nodes[b].ucb()))
+            {
+                Some(next) => next,
+                None => break,
+            },
+        };
+
+        if let Some(action) = nodes[next].action {
+            state.apply(action, rng);
+        }
+
+        path.push(next);
+        current = next;
+        if expanding.is_some() {
+            break;
+        }
+    }
+
+    while state.deal_number == 0 {
+        match state.rollout_action(rng) {
+            Some(action) => state.apply(action, rng),
+            None => break,
+        }
+    }
+
+    let value = state.win_probability(TwoPlayers::PlayerOne);
+    for &index in &path {
+        let node = &mut nodes[index];
+        node.visits += 1;
+        node.wins += match node.player {
+            Some(TwoPlayers::PlayerOne) => value,
+            Some(TwoPlayers::PlayerTwo) => 1.0 - value,
+            None => 0.0,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::{IsmctsBot, IsmctsConfig, RandomBot};
+    use crate::card::Card;
+    use crate::card_set::CardSet;
+    use crate::game::{play_two_player_game, Player, Situation, TableEvent, TwoPlayerGame};
+    use crate::pegging::Pegger;
+    use std::str::FromStr;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split(' ').map(|c| Card::from_str(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_pegs_out_near_the_end() {
+        let events = [
+            TableEvent::DealStarted {
+                dealer: TwoPlayers::PlayerOne,
+            },
+            TableEvent::Played {
+                player: TwoPlayers::PlayerTwo,
+                card: cards("TS")[0],
+                count: 10,
+                points: 0,
+            },
+        ];
+        let situation = Situation {
+            player: TwoPlayers::PlayerOne,
+            dealer: TwoPlayers::PlayerOne,
+            own_score: 119,
+            opponent_score: 120,
+            target: 121,
+            starter: Some(cards("KD")[0]),
+            crib_part: CardSet::new(),
+            deal_events: &events,
+        };
+
+        let mut pegger = Pegger::new();
+        pegger.play_card(cards("TS")[0]).unwrap();
+        let hand = cards("5C 9H 2D AS");
+        let legal = hand.clone();
+        let mut bot = IsmctsBot::new(IsmctsConfig::new(200, 1, 3));
+        assert_eq!(
+            bot.choose_peg(&pegger, &hand, &legal, &situation),
+            cards("5C")[0]
+        );
+    }
+
+    #[test]
+    fn test_ismcts_plays_legal_games() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 4);
+        let mut ismcts = IsmctsBot::new(IsmctsConfig::new(30, 2, 9));
+        let mut random = RandomBot::new(9);
+        let winner = play_two_player_game(&mut game, &mut ismcts, &mut random).unwrap();
+        assert_eq!(game.score(winner), 121);
+        assert_eq!(ismcts.config().threads, 2);
+    }
+}
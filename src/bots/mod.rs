@@ -0,0 +1,145 @@
+mod greedy;
+mod ismcts;
+mod positional;
+mod random;
+mod strong;
+
+pub use self::greedy::GreedyBot;
+pub use self::ismcts::{IsmctsBot, IsmctsConfig};
+pub use self::positional::PositionalBot;
+pub use self::random::RandomBot;
+pub use self::strong::StrongBot;
+
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::game::{Player, Situation, TableEvent};
+use crate::pegging::Pegger;
+use crate::CribbageCoreError;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub fn bot(self, seed: u64) -> Box<dyn Player + Send> {
+        match self {
+            Difficulty::Easy => Box::new(RandomBot::new(seed)),
+            Difficulty::Medium => Box::new(GreedyBot::new()),
+            Difficulty::Hard => Box::new(StrongBot::new(seed)),
+            Difficulty::Expert => Box::new(IsmctsBot::new(IsmctsConfig {
+                seed,
+                ..IsmctsConfig::default()
+            })),
+        }
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = CribbageCoreError;
+
+    fn from_str(name: &str) -> Result<Difficulty, CribbageCoreError> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "easy" | "random" => Ok(Difficulty::Easy),
+            "medium" | "greedy" => Ok(Difficulty::Medium),
+            "hard" | "strong" => Ok(Difficulty::Hard),
+            "expert" | "ismcts" => Ok(Difficulty::Expert),
+            _ => Err(CribbageCoreError::UnknownBot),
+        }
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Medium => "medium",
+            Difficulty::Hard => "hard",
+            Difficulty::Expert => "expert",
+        };
+
+        write!(f, "{}", s)
+    }
+}
+
+pub fn bot_by_name(name: &str, seed: u64) -> Result<Box<dyn Player + Send>, CribbageCoreError> {
+    Ok(Difficulty::from_str(name)?.bot(seed))
+}
+
+fn pegging_points(pegger: &Pegger, card: Card) -> u8 {
+    pegger.clone().play_card(card).unwrap_or(0)
+}
+
+fn seen_cards(hand: &[Card], situation: &Situation<'_>) -> CardSet {
+    let mut seen = CardSet::from_cards(hand) | situation.crib_part;
+    seen.extend(situation.starter);
+    for event in situation.deal_events {
+        if let TableEvent::Played { card, .. } = *event {
+            seen.insert(card);
+        }
+    }
+
+    seen
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::{bot_by_name, seen_cards, Difficulty};
+    use crate::game::{play_two_player_game, GamePhase, Situation, TwoPlayerGame};
+    use crate::CribbageCoreError;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_difficulty_from_str() {
+        assert_eq!(Difficulty::from_str("easy"), Ok(Difficulty::Easy));
+        assert_eq!(Difficulty::from_str("Greedy"), Ok(Difficulty::Medium));
+        assert_eq!(Difficulty::from_str(" hard "), Ok(Difficulty::Hard));
+        assert_eq!(Difficulty::from_str("ISMCTS"), Ok(Difficulty::Expert));
+        assert_eq!(
+            Difficulty::from_str("grandmaster"),
+            Err(CribbageCoreError::UnknownBot)
+        );
+        assert_eq!(Difficulty::Expert.to_string(), "expert");
+        assert_eq!(Difficulty::Medium.to_string(), "medium");
+        assert!(bot_by_name("strong", 0).is_ok());
+    }
+
+    #[test]
+    fn test_bots_are_deterministic() {
+        for name in &["easy", "medium", "hard"] {
+            let play = || {
+                let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 21);
+                let mut one = bot_by_name(name, 1).unwrap();
+                let mut two = bot_by_name(name, 2).unwrap();
+                play_two_player_game(&mut game, one.as_mut(), two.as_mut()).unwrap();
+                game.events().to_vec()
+            };
+
+            assert_eq!(play(), play());
+        }
+    }
+
+    #[test]
+    fn test_seen_cards_include_own_discards() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 4);
+        let mut discards = Vec::new();
+        for player in [TwoPlayers::PlayerTwo, TwoPlayers::PlayerOne] {
+            let deal = game.deal(player).unwrap().cards().to_vec();
+            game.discard(player, [deal[0], deal[1]]).unwrap();
+            discards.push([deal[0], deal[1]]);
+        }
+        assert_eq!(game.phase(), GamePhase::Pegging);
+
+        let dealer = TwoPlayers::PlayerOne;
+        let seen = seen_cards(game.pegging_hand(dealer), &Situation::new(&game, dealer));
+        assert!(discards[1].iter().all(|&card| seen.contains(card)));
+        assert!(discards[0].iter().all(|&card| !seen.contains(card)));
+    }
+}
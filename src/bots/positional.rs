@@ -0,0 +1,146 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bots::StrongBot;
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::game::{Player, Situation};
+use crate::hand::{FourPlayerDeal, ThreePlayerDeal, TwoPlayerDeal};
+use crate::pegging::Pegger;
+use crate::pegging_analysis::{PeggingBudget, PeggingView};
+use crate::position::{
+    rank_discards_by_position, rank_pegging_by_position, DealCards, Position, RaceEstimator,
+    WinEstimator,
+};
+
+const DISCARD_SAMPLES: usize = 400;
+const PEGGING_SAMPLES: usize = 300;
+
+pub struct PositionalBot {
+    estimator: Box<dyn WinEstimator + Send>,
+    rng: StdRng,
+    kept: Option<[Card; 4]>,
+    crib_part: Option<[Card; 2]>,
+    fallback: StrongBot,
+}
+
+impl PositionalBot {
+    pub fn new(seed: u64) -> PositionalBot {
+        PositionalBot::with_estimator(Box::new(RaceEstimator), seed)
+    }
+
+    pub fn with_estimator(estimator: Box<dyn WinEstimator + Send>, seed: u64) -> PositionalBot {
+        PositionalBot {
+            estimator,
+            rng: StdRng::seed_from_u64(seed),
+            kept: None,
+            crib_part: None,
+            fallback: StrongBot::new(seed),
+        }
+    }
+}
+
+impl Player for PositionalBot {
+    fn choose_discard(&mut self, deal: &TwoPlayerDeal, situation: &Situation<'_>) -> [Card; 2] {
+        let cards = deal.cards();
+        let crib = match rank_discards_by_position(
+            cards,
+            Position::from_situation(situation),
+            &self.estimator,
+            DISCARD_SAMPLES,
+            self.rng.gen(),
+        ) {
+            Ok(ranked) => [ranked[0].option.discard[0], ranked[0].option.discard[1]],
+            Err(_) => self.fallback.choose_discard(deal, situation),
+        };
+
+        let kept: Vec<Card> = cards
+            .iter()
+            .copied()
+            .filter(|card| !crib.contains(card))
+            .collect();
+        self.kept = Some([kept[0], kept[1], kept[2], kept[3]]);
+        self.crib_part = Some(crib);
+        crib
+    }
+
+    fn choose_three_player_discard(
+        &mut self,
+        deal: &ThreePlayerDeal,
+        situation: &Situation<'_>,
+    ) -> Card {
+        self.fallback.choose_three_player_discard(deal, situation)
+    }
+
+    fn choose_four_player_discard(
+        &mut self,
+        deal: &FourPlayerDeal,
+        situation: &Situation<'_>,
+    ) -> Card {
+        self.fallback.choose_four_player_discard(deal, situation)
+    }
+
+    fn choose_peg(
+        &mut self,
+        pegger: &Pegger,
+        hand: &[Card],
+        legal: &[Card],
+        situation: &Situation<'_>,
+    ) -> Card {
+        if legal.len() == 1 {
+            return legal[0];
+        }
+
+        let deal = match (self.kept, self.crib_part, situation.starter) {
+            (Some(kept), Some(crib_part), Some(starter))
+                if hand.iter().all(|card| kept.contains(card)) =>
+            {
+                DealCards {
+                    kept,
+                    crib_part,
+                    starter,
+                }
+            }
+            _ => return self.fallback.choose_peg(pegger, hand, legal, situation),
+        };
+
+        let mut view = PeggingView::from_situation(hand, pegger, situation);
+        view.observe_cards(CardSet::from_cards(&deal.crib_part));
+        let ranked = rank_pegging_by_position(
+            &view,
+            deal,
+            Position::from_situation(situation),
+            &self.estimator,
+            PeggingBudget::Samples(PEGGING_SAMPLES),
+            self.rng.gen(),
+        );
+        match ranked.first() {
+            Some(peg) if legal.contains(&peg.card) => peg.card,
+            _ => self.fallback.choose_peg(pegger, hand, legal, situation),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::{PositionalBot, RandomBot};
+    use crate::game::{play_two_player_game, TwoPlayerGame};
+
+    #[test]
+    fn test_positional_bot_beats_random() {
+        let mut wins = 0;
+        for seed in 0..4 {
+            let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, seed);
+            let mut positional = PositionalBot::new(seed);
+            let mut random = RandomBot::new(seed);
+            if play_two_player_game(&mut game, &mut positional, &mut random).unwrap()
+                == TwoPlayers::PlayerOne
+            {
+                wins += 1;
+            }
+        }
+
+        assert!(wins >= 3);
+    }
+}
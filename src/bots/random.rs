@@ -0,0 +1,64 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::card::Card;
+use crate::game::{Player, Situation};
+use crate::hand::{FourPlayerDeal, ThreePlayerDeal, TwoPlayerDeal};
+use crate::pegging::Pegger;
+
+pub struct RandomBot {
+    rng: StdRng,
+}
+
+impl RandomBot {
+    pub fn new(seed: u64) -> RandomBot {
+        RandomBot {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    // Deals and legal plays are never empty
+    fn choose(&mut self, cards: &[Card]) -> Card {
+        *cards
+            .choose(&mut self.rng)
+            .expect("there is always a card to choose")
+    }
+}
+
+impl Player for RandomBot {
+    fn choose_discard(&mut self, deal: &TwoPlayerDeal, _situation: &Situation<'_>) -> [Card; 2] {
+        let chosen: Vec<Card> = deal
+            .cards()
+            .choose_multiple(&mut self.rng, 2)
+            .copied()
+            .collect();
+        [chosen[0], chosen[1]]
+    }
+
+    fn choose_three_player_discard(
+        &mut self,
+        deal: &ThreePlayerDeal,
+        _situation: &Situation<'_>,
+    ) -> Card {
+        self.choose(deal.cards())
+    }
+
+    fn choose_four_player_discard(
+        &mut self,
+        deal: &FourPlayerDeal,
+        _situation: &Situation<'_>,
+    ) -> Card {
+        self.choose(deal.cards())
+    }
+
+    fn choose_peg(
+        &mut self,
+        _pegger: &Pegger,
+        _hand: &[Card],
+        legal: &[Card],
+        _situation: &Situation<'_>,
+    ) -> Card {
+        self.choose(legal)
+    }
+}
@@ -0,0 +1,127 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::bots::{pegging_points, seen_cards};
+use crate::card::Card;
+use crate::discard::analyze_discards;
+use crate::game::{Player, Situation};
+use crate::hand::{FourPlayerDeal, ThreePlayerDeal, TwoPlayerDeal};
+use crate::pegging::Pegger;
+
+const CRIB_SAMPLES: usize = 400;
+
+pub struct StrongBot {
+    rng: StdRng,
+}
+
+impl StrongBot {
+    pub fn new(seed: u64) -> StrongBot {
+        StrongBot {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn best_discard(&mut self, cards: &[Card], is_dealer: bool) -> Vec<Card> {
+        match analyze_discards(cards, is_dealer, CRIB_SAMPLES, self.rng.gen()) {
+            Ok(options) => options[0].discard.clone(),
+            Err(_) => cards[4..].to_vec(),
+        }
+    }
+
+    // Points for playing the card, less the opponent's expected best reply drawn from the cards
+    // still unseen, with small nudges to keep the count away from the 5 and 21 traps
+    fn evaluate_peg(pegger: &Pegger, card: Card, unseen: &[Card]) -> f64 {
+        let mut after = pegger.clone();
+        let points = match after.play_card(card) {
+            Ok(points) => f64::from(points),
+            Err(_) => return f64::MIN,
+        };
+
+        let mut reply_total = 0u32;
+        for &reply in unseen {
+            if after.can_play(reply) {
+                reply_total += u32::from(pegging_points(&after, reply));
+            }
+        }
+
+        let expected_reply = match unseen.len() {
+            0 => 0.0,
+            n => f64::from(reply_total) / n as f64,
+        };
+
+        let trap = match after.count() {
+            5 | 21 => 0.5,
+            _ => 0.0,
+        };
+
+        points - expected_reply - trap
+    }
+}
+
+impl Player for StrongBot {
+    fn choose_discard(&mut self, deal: &TwoPlayerDeal, situation: &Situation<'_>) -> [Card; 2] {
+        let discard = self.best_discard(deal.cards(), situation.is_dealer());
+        [discard[0], discard[1]]
+    }
+
+    fn choose_three_player_discard(
+        &mut self,
+        deal: &ThreePlayerDeal,
+        situation: &Situation<'_>,
+    ) -> Card {
+        self.best_discard(deal.cards(), situation.is_dealer())[0]
+    }
+
+    fn choose_four_player_discard(
+        &mut self,
+        deal: &FourPlayerDeal,
+        situation: &Situation<'_>,
+    ) -> Card {
+        self.best_discard(deal.cards(), situation.is_dealer())[0]
+    }
+
+    fn choose_peg(
+        &mut self,
+        pegger: &Pegger,
+        hand: &[Card],
+        legal: &[Card],
+        situation: &Situation<'_>,
+    ) -> Card {
+        let unseen = seen_cards(hand, situation).complement().to_vec();
+        let mut best = legal[0];
+        let mut best_value = f64::MIN;
+        for &card in legal {
+            let value = StrongBot::evaluate_peg(pegger, card, &unseen);
+            if value > best_value {
+                best = card;
+                best_value = value;
+            }
+        }
+
+        best
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::{RandomBot, StrongBot};
+    use crate::game::{play_two_player_game, TwoPlayerGame};
+
+    #[test]
+    fn test_strong_bot_beats_random_bot() {
+        let mut strong_wins = 0;
+        for seed in 0..10 {
+            let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, seed);
+            let mut strong = StrongBot::new(seed);
+            let mut random = RandomBot::new(seed);
+            if play_two_player_game(&mut game, &mut strong, &mut random).unwrap()
+                == TwoPlayers::PlayerOne
+            {
+                strong_wins += 1;
+            }
+        }
+
+        assert!(strong_wins >= 7, "strong bot won {} of 10", strong_wins);
+    }
+}
@@ -1,8 +1,29 @@
 use std::fmt::{self, Debug, Display, Formatter};
 use std::str::FromStr;
 
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::CribbageCoreError;
 
+pub const RANKS: [Rank; 13] = [
+    Rank::Ace,
+    Rank::Two,
+    Rank::Three,
+    Rank::Four,
+    Rank::Five,
+    Rank::Six,
+    Rank::Seven,
+    Rank::Eight,
+    Rank::Nine,
+    Rank::Ten,
+    Rank::Jack,
+    Rank::Queen,
+    Rank::King,
+];
+
+pub(crate) const SUITS: [Suit; 4] = [Suit::Spades, Suit::Diamonds, Suit::Clubs, Suit::Hearts];
+
 #[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum Rank {
     Ace,
@@ -53,6 +74,27 @@ impl Rank {
             Rank::Ten | Rank::Jack | Rank::Queen | Rank::King => 10,
         }
     }
+
+    fn from_char(c: char) -> Option<Rank> {
+        let rank = match c.to_ascii_uppercase() {
+            'A' => Rank::Ace,
+            '2' => Rank::Two,
+            '3' => Rank::Three,
+            '4' => Rank::Four,
+            '5' => Rank::Five,
+            '6' => Rank::Six,
+            '7' => Rank::Seven,
+            '8' => Rank::Eight,
+            '9' => Rank::Nine,
+            'T' => Rank::Ten,
+            'J' => Rank::Jack,
+            'Q' => Rank::Queen,
+            'K' => Rank::King,
+            _ => return None,
+        };
+
+        Some(rank)
+    }
 }
 
 impl Display for Rank {
@@ -85,6 +127,29 @@ pub enum Suit {
     Hearts,
 }
 
+impl Suit {
+    fn index(self) -> u8 {
+        match self {
+            Suit::Spades => 0,
+            Suit::Diamonds => 1,
+            Suit::Clubs => 2,
+            Suit::Hearts => 3,
+        }
+    }
+
+    fn from_char(c: char) -> Option<Suit> {
+        let suit = match c.to_ascii_uppercase() {
+            'H' => Suit::Hearts,
+            'C' => Suit::Clubs,
+            'D' => Suit::Diamonds,
+            'S' => Suit::Spades,
+            _ => return None,
+        };
+
+        Some(suit)
+    }
+}
+
 impl Display for Suit {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         let s = match self {
@@ -116,6 +181,21 @@ impl Card {
     pub fn suit(self) -> Suit {
         self.suit
     }
+
+    pub fn index(self) -> u8 {
+        self.suit.index() * 13 + self.rank.ordinal() - 1
+    }
+
+    pub fn from_index(index: u8) -> Result<Card, CribbageCoreError> {
+        if index >= 52 {
+            return Err(CribbageCoreError::InvalidCard);
+        }
+
+        Ok(Card {
+            rank: RANKS[(index % 13) as usize],
+            suit: SUITS[(index / 13) as usize],
+        })
+    }
 }
 
 impl FromStr for Card {
@@ -127,38 +207,27 @@ impl FromStr for Card {
             return Err(CribbageCoreError::InvalidCardString);
         }
 
-        let rank = match chars[0].to_ascii_uppercase() {
-            'A' => Rank::Ace,
-            '2' => Rank::Two,
-            '3' => Rank::Three,
-            '4' => Rank::Four,
-            '5' => Rank::Five,
-            '6' => Rank::Six,
-            '7' => Rank::Seven,
-            '8' => Rank::Eight,
-            '9' => Rank::Nine,
-            'T' => Rank::Ten,
-            'J' => Rank::Jack,
-            'Q' => Rank::Queen,
-            'K' => Rank::King,
-            _ => return Err(CribbageCoreError::InvalidCardString),
-        };
+        let rank = Rank::from_char(chars[0]).ok_or(CribbageCoreError::InvalidCardString)?;
+        let suit = Suit::from_char(chars[1]).ok_or(CribbageCoreError::InvalidCardString)?;
+        Ok(Card { rank, suit })
+    }
+}
 
-        let suit = match chars[1].to_ascii_uppercase() {
-            'H' => Suit::Hearts,
-            'C' => Suit::Clubs,
-            'D' => Suit::Diamonds,
-            'S' => Suit::Spades,
-            _ => return Err(CribbageCoreError::InvalidCardString),
-        };
+impl FromStr for Rank {
+    type Err = CribbageCoreError;
 
-        Ok(Card { rank, suit })
+    fn from_str(rank_string: &str) -> Result<Rank, CribbageCoreError> {
+        let mut chars = rank_string.trim().chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Rank::from_char(c).ok_or(CribbageCoreError::InvalidCardString),
+            _ => Err(CribbageCoreError::InvalidCardString),
+        }
     }
 }
 
 impl Debug for Card {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self)
     }
 }
 
@@ -168,7 +237,52 @@ impl Display for Card {
     }
 }
 
+// Ranks, suits and cards are serialized as their notation, e.g. "5", "H" and "5H"
+#[cfg(feature = "serde")]
+macro_rules! serde_as_notation {
+    ($type:ty, $parse:expr, $expecting:expr) => {
+        impl Serialize for $type {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.collect_str(self)
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $type {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<$type, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                $parse(s.as_str())
+                    .ok_or_else(|| de::Error::invalid_value(de::Unexpected::Str(&s), &$expecting))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "serde")]
+fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+#[cfg(feature = "serde")]
+serde_as_notation!(
+    Rank,
+    |s| single_char(s).and_then(Rank::from_char),
+    "a rank such as \"T\""
+);
+#[cfg(feature = "serde")]
+serde_as_notation!(
+    Suit,
+    |s| single_char(s).and_then(Suit::from_char),
+    "a suit such as \"H\""
+);
+#[cfg(feature = "serde")]
+serde_as_notation!(Card, |s| Card::from_str(s).ok(), "a card such as \"5H\"");
+
 #[cfg(test)]
+#[allow(clippy::to_string_in_format_args)]
 mod test {
     use crate::card::{Card, Rank, Suit};
     use crate::CribbageCoreError;
@@ -277,4 +391,60 @@ mod test {
             Err(CribbageCoreError::InvalidCardString)
         );
     }
+
+    #[test]
+    fn test_rank_from_str() {
+        for rank in &RANKS {
+            assert_eq!(Rank::from_str(&rank.to_string()), Ok(*rank));
+        }
+
+        assert_eq!(Rank::from_str("t"), Ok(Rank::Ten));
+        assert_eq!(
+            Rank::from_str(""),
+            Err(CribbageCoreError::InvalidCardString)
+        );
+        assert_eq!(
+            Rank::from_str("10"),
+            Err(CribbageCoreError::InvalidCardString)
+        );
+        assert_eq!(
+            Rank::from_str("X"),
+            Err(CribbageCoreError::InvalidCardString)
+        );
+    }
+
+    #[test]
+    fn test_card_index() {
+        let mut seen = [false; 52];
+        for rank in &RANKS {
+            for suit in &SUITS {
+                let card = Card::new(*rank, *suit);
+                let index = card.index();
+                assert!(index < 52);
+                assert!(!seen[index as usize]);
+                seen[index as usize] = true;
+                assert_eq!(Card::from_index(index), Ok(card));
+            }
+        }
+
+        assert_eq!(Card::new(Rank::Ace, Suit::Spades).index(), 0);
+        assert_eq!(Card::new(Rank::King, Suit::Hearts).index(), 51);
+        assert_eq!(Card::from_index(52), Err(CribbageCoreError::InvalidCard));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_card_serde() {
+        let card = Card::new(Rank::Five, Suit::Hearts);
+        assert_eq!(serde_json::to_string(&card).unwrap(), "\"5H\"");
+        assert_eq!(serde_json::from_str::<Card>("\"5h\"").unwrap(), card);
+        assert_eq!(serde_json::to_string(&Rank::Ten).unwrap(), "\"T\"");
+        assert_eq!(
+            serde_json::from_str::<Suit>("\"D\"").unwrap(),
+            Suit::Diamonds
+        );
+        assert!(serde_json::from_str::<Card>("\"1H\"").is_err());
+        assert!(serde_json::from_str::<Card>("5").is_err());
+        assert!(serde_json::from_str::<Rank>("\"TT\"").is_err());
+    }
 }
@@ -0,0 +1,266 @@
+use std::fmt::{self, Debug, Formatter};
+use std::iter::FromIterator;
+use std::ops::{BitAnd, BitOr, Not, Sub};
+
+use crate::card::Card;
+
+const ALL_CARDS: u64 = (1 << 52) - 1;
+
+#[derive(Clone, Copy, Default, Eq, Hash, PartialEq)]
+pub struct CardSet {
+    bits: u64,
+}
+
+impl CardSet {
+    pub fn new() -> CardSet {
+        CardSet::default()
+    }
+
+    pub fn full() -> CardSet {
+        CardSet { bits: ALL_CARDS }
+    }
+
+    pub fn from_cards(cards: &[Card]) -> CardSet {
+        cards.iter().copied().collect()
+    }
+
+    pub fn from_bits(bits: u64) -> CardSet {
+        CardSet {
+            bits: bits & ALL_CARDS,
+        }
+    }
+
+    pub fn bits(self) -> u64 {
+        self.bits
+    }
+
+    pub fn len(self) -> usize {
+        self.bits.count_ones() as usize
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.bits == 0
+    }
+
+    pub fn contains(self, card: Card) -> bool {
+        self.bits & CardSet::bit(card) != 0
+    }
+
+    pub fn insert(&mut self, card: Card) -> bool {
+        let inserted = !self.contains(card);
+        self.bits |= CardSet::bit(card);
+        inserted
+    }
+
+    pub fn remove(&mut self, card: Card) -> bool {
+        let removed = self.contains(card);
+        self.bits &= !CardSet::bit(card);
+        removed
+    }
+
+    pub fn union(self, other: CardSet) -> CardSet {
+        CardSet {
+            bits: self.bits | other.bits,
+        }
+    }
+
+    pub fn intersection(self, other: CardSet) -> CardSet {
+        CardSet {
+            bits: self.bits & other.bits,
+        }
+    }
+
+    pub fn difference(self, other: CardSet) -> CardSet {
+        CardSet {
+            bits: self.bits & !other.bits,
+        }
+    }
+
+    pub fn complement(self) -> CardSet {
+        CardSet {
+            bits: !self.bits & ALL_CARDS,
+        }
+    }
+
+    pub fn is_subset(self, other: CardSet) -> bool {
+        self.bits & !other.bits == 0
+    }
+
+    pub fn iter(self) -> CardSetIter {
+        CardSetIter { bits: self.bits }
+    }
+
+    pub fn to_vec(self) -> Vec<Card> {
+        self.iter().collect()
+    }
+
+    fn bit(card: Card) -> u64 {
+        1 << card.index()
+    }
+}
+
+impl Debug for CardSet {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl From<&[Card]> for CardSet {
+    fn from(cards: &[Card]) -> CardSet {
+        CardSet::from_cards(cards)
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> CardSet {
+        let mut set = CardSet::new();
+        for card in iter {
+            set.insert(card);
+        }
+
+        set
+    }
+}
+
+impl Extend<Card> for CardSet {
+    fn extend<I: IntoIterator<Item = Card>>(&mut self, iter: I) {
+        for card in iter {
+            self.insert(card);
+        }
+    }
+}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = CardSetIter;
+
+    fn into_iter(self) -> CardSetIter {
+        self.iter()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, other: CardSet) -> CardSet {
+        self.union(other)
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, other: CardSet) -> CardSet {
+        self.intersection(other)
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+
+    fn sub(self, other: CardSet) -> CardSet {
+        self.difference(other)
+    }
+}
+
+impl Not for CardSet {
+    type Output = CardSet;
+
+    fn not(self) -> CardSet {
+        self.complement()
+    }
+}
+
+pub struct CardSetIter {
+    bits: u64,
+}
+
+impl Iterator for CardSetIter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.bits == 0 {
+            return None;
+        }
+
+        let index = self.bits.trailing_zeros() as u8;
+        self.bits &= self.bits - 1;
+        Card::from_index(index).ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.bits.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for CardSetIter {}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::{Card, Rank, Suit};
+    use crate::card_set::CardSet;
+    use crate::deck::Deck;
+
+    #[test]
+    fn test_insert_remove_contains() {
+        let ace = Card::new(Rank::Ace, Suit::Spades);
+        let king = Card::new(Rank::King, Suit::Hearts);
+
+        let mut set = CardSet::new();
+        assert!(set.is_empty());
+        assert!(!set.contains(ace));
+
+        assert!(set.insert(ace));
+        assert!(!set.insert(ace));
+        assert!(set.insert(king));
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(ace));
+        assert!(set.contains(king));
+
+        assert!(set.remove(ace));
+        assert!(!set.remove(ace));
+        assert_eq!(set.len(), 1);
+        assert!(!set.contains(ace));
+        assert!(set.contains(king));
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let five_hearts = Card::new(Rank::Five, Suit::Hearts);
+        let five_clubs = Card::new(Rank::Five, Suit::Clubs);
+        let jack_spades = Card::new(Rank::Jack, Suit::Spades);
+
+        let a = CardSet::from_cards(&[five_hearts, five_clubs]);
+        let b = CardSet::from_cards(&[five_clubs, jack_spades]);
+
+        assert_eq!(
+            a | b,
+            CardSet::from_cards(&[five_hearts, five_clubs, jack_spades])
+        );
+        assert_eq!(a & b, CardSet::from_cards(&[five_clubs]));
+        assert_eq!(a - b, CardSet::from_cards(&[five_hearts]));
+        assert_eq!((!a).len(), 50);
+        assert!(!(!a).contains(five_hearts));
+        assert!((a & b).is_subset(a));
+        assert!(!a.is_subset(b));
+    }
+
+    #[test]
+    fn test_full_and_iteration() {
+        let full = CardSet::full();
+        assert_eq!(full.len(), 52);
+        assert_eq!(full.complement(), CardSet::new());
+
+        let cards = full.to_vec();
+        assert_eq!(cards.len(), 52);
+        for (index, card) in cards.iter().enumerate() {
+            assert_eq!(card.index() as usize, index);
+        }
+
+        let mut deck = Deck::new();
+        let drawn = deck.draw_n(52).unwrap();
+        assert_eq!(CardSet::from_cards(&drawn), full);
+        assert_eq!(CardSet::from_bits(u64::MAX), full);
+    }
+}
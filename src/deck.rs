@@ -1,9 +1,11 @@
 use rand::prelude::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 
 use crate::card::{Card, Rank, Suit};
+use crate::card_set::CardSet;
 use crate::CribbageCoreError;
 
+#[derive(Clone, Debug)]
 pub struct Deck {
     cards_drawn: usize,
     cards: Vec<Card>,
@@ -14,6 +16,30 @@ impl Deck {
         Deck::default()
     }
 
+    // A deck in the given order, with the first `cards_drawn` cards already dealt
+    pub fn from_order(cards: &[Card], cards_drawn: usize) -> Result<Deck, CribbageCoreError> {
+        if cards.len() != 52 || CardSet::from_cards(cards).len() != 52 {
+            return Err(CribbageCoreError::InvalidCard);
+        }
+
+        if cards_drawn > cards.len() {
+            return Err(CribbageCoreError::NotEnoughCards);
+        }
+
+        Ok(Deck {
+            cards_drawn,
+            cards: cards.to_vec(),
+        })
+    }
+
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
+    pub fn cards_drawn(&self) -> usize {
+        self.cards_drawn
+    }
+
     pub fn draw(&mut self) -> Result<Card, CribbageCoreError> {
         let card = match self.cards.get(self.cards_drawn) {
             Some(card) => {
@@ -37,9 +63,17 @@ impl Deck {
         Ok(self.cards[start..end].to_vec())
     }
 
+    pub fn card_set(&self) -> CardSet {
+        CardSet::from_cards(&self.cards[self.cards_drawn..])
+    }
+
     pub fn shuffle(&mut self) {
+        self.shuffle_with_rng(&mut thread_rng());
+    }
+
+    pub fn shuffle_with_rng<R: Rng + ?Sized>(&mut self, rng: &mut R) {
         self.cards_drawn = 0;
-        self.cards.shuffle(&mut thread_rng());
+        self.cards.shuffle(rng);
     }
 }
 
@@ -110,8 +144,11 @@ impl Default for Deck {
 #[cfg(test)]
 mod tests {
     use crate::card::{Card, Rank, Suit};
+    use crate::card_set::CardSet;
     use crate::deck::Deck;
     use crate::CribbageCoreError;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
 
     #[test]
     fn test_new() {
@@ -171,6 +208,35 @@ mod tests {
         assert_eq!(deck.cards_drawn, 52);
     }
 
+    #[test]
+    pub fn test_shuffle_with_rng() {
+        let mut deck = Deck::new();
+        let mut other = Deck::new();
+        deck.shuffle_with_rng(&mut StdRng::seed_from_u64(7));
+        other.shuffle_with_rng(&mut StdRng::seed_from_u64(7));
+        assert_eq!(deck.cards, other.cards);
+        assert_ne!(deck.cards, Deck::new().cards);
+
+        other.shuffle_with_rng(&mut StdRng::seed_from_u64(8));
+        assert_ne!(deck.cards, other.cards);
+    }
+
+    #[test]
+    pub fn test_card_set() {
+        let mut deck = Deck::new();
+        assert_eq!(deck.card_set(), CardSet::full());
+
+        let drawn = deck.draw_n(6).unwrap();
+        let remaining = deck.card_set();
+        assert_eq!(remaining.len(), 46);
+        for card in drawn {
+            assert!(!remaining.contains(card));
+        }
+
+        deck.draw_n(46).unwrap();
+        assert!(deck.card_set().is_empty());
+    }
+
     #[test]
     pub fn test_shuffle() {
         let mut deck = Deck::new();
@@ -189,4 +255,31 @@ mod tests {
         deck.shuffle();
         assert_eq!(deck.cards_drawn, 0);
     }
+
+    #[test]
+    fn test_from_order() {
+        let mut deck = Deck::new();
+        deck.shuffle_with_rng(&mut StdRng::seed_from_u64(3));
+        deck.draw_n(13).unwrap();
+
+        let mut restored = Deck::from_order(deck.cards(), deck.cards_drawn()).unwrap();
+        assert_eq!(restored.cards_drawn(), 13);
+        assert_eq!(restored.card_set(), deck.card_set());
+        assert_eq!(restored.draw(), deck.draw());
+
+        let mut cards = deck.cards().to_vec();
+        assert_eq!(
+            Deck::from_order(&cards, 53).unwrap_err(),
+            CribbageCoreError::NotEnoughCards
+        );
+        cards[0] = cards[1];
+        assert_eq!(
+            Deck::from_order(&cards, 0).unwrap_err(),
+            CribbageCoreError::InvalidCard
+        );
+        assert_eq!(
+            Deck::from_order(&cards[1..], 0).unwrap_err(),
+            CribbageCoreError::InvalidCard
+        );
+    }
 }
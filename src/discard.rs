@@ -0,0 +1,188 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::hand::score_hand;
+use crate::CribbageCoreError;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiscardOption {
+    pub keep: [Card; 4],
+    pub discard: Vec<Card>,
+    pub hand_ev: f64,
+    pub crib_ev: f64,
+    pub best_cut: (Card, u8),
+    pub worst_cut: (Card, u8),
+}
+
+impl DiscardOption {
+    pub fn net(&self, is_dealer: bool) -> f64 {
+        if is_dealer {
+            self.hand_ev + self.crib_ev
+        } else {
+            self.hand_ev - self.crib_ev
+        }
+    }
+}
+
+pub fn analyze_discards(
+    cards: &[Card],
+    is_dealer: bool,
+    crib_samples: usize,
+    seed: u64,
+) -> Result<Vec<DiscardOption>, CribbageCoreError> {
+    let held = CardSet::from_cards(cards);
+    if held.len() != cards.len() {
+        return Err(CribbageCoreError::InvalidCard);
+    }
+
+    if cards.len() != 5 && cards.len() != 6 {
+        return Err(CribbageCoreError::NotEnoughCards);
+    }
+
+    let unseen = held.complement().to_vec();
+    let discard_count = cards.len() - 4;
+    let crib_samples = sample_crib_completions(&unseen, 4 - discard_count, crib_samples, seed);
+
+    let mut options = Vec::new();
+    for keep_mask in 0u32..(1 << cards.len()) {
+        if keep_mask.count_ones() != 4 {
+            continue;
+        }
+
+        let mut keep = Vec::with_capacity(4);
+        let mut discard = Vec::with_capacity(discard_count);
+        for (index, &card) in cards.iter().enumerate() {
+            if keep_mask & (1 << index) != 0 {
+                keep.push(card);
+            } else {
+                discard.push(card);
+            }
+        }
+
+        let keep = [keep[0], keep[1], keep[2], keep[3]];
+        options.push(evaluate(keep, discard, &unseen, &crib_samples));
+    }
+
+    options.sort_by(|a, b| b.net(is_dealer).total_cmp(&a.net(is_dealer)));
+    Ok(options)
+}
+
+fn evaluate(
+    keep: [Card; 4],
+    discard: Vec<Card>,
+    unseen: &[Card],
+    crib_samples: &[(Vec<Card>, Card)],
+) -> DiscardOption {
+    let mut total = 0u32;
+    let mut best_cut = (unseen[0], 0);
+    let mut worst_cut = (unseen[0], u8::MAX);
+    for &cut in unseen {
+        let points = score_hand(keep, cut, false);
+        total += u32::from(points);
+        if points > best_cut.1 {
+            best_cut = (cut, points);
+        }
+
+        if points < worst_cut.1 {
+            worst_cut = (cut, points);
+        }
+    }
+
+    let mut crib_total = 0u32;
+    for (others, cut) in crib_samples {
+        let mut crib = [keep[0]; 4];
+        for (slot, &card) in crib.iter_mut().zip(discard.iter().chain(others.iter())) {
+            *slot = card;
+        }
+
+        crib_total += u32::from(score_hand(crib, *cut, true));
+    }
+
+    let crib_ev = match crib_samples.len() {
+        0 => 0.0,
+        n => f64::from(crib_total) / n as f64,
+    };
+
+    DiscardOption {
+        keep,
+        discard,
+        hand_ev: f64::from(total) / unseen.len() as f64,
+        crib_ev,
+        best_cut,
+        worst_cut,
+    }
+}
+
+fn sample_crib_completions(
+    unseen: &[Card],
+    others: usize,
+    samples: usize,
+    seed: u64,
+) -> Vec<(Vec<Card>, Card)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pool = unseen.to_vec();
+    (0..samples)
+        .map(|_| {
+            let (chosen, _) = pool.partial_shuffle(&mut rng, others + 1);
+            (chosen[..others].to_vec(), chosen[others])
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::discard::analyze_discards;
+    use crate::CribbageCoreError;
+    use std::str::FromStr;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split(' ').map(|c| Card::from_str(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_analyze_two_player_discards() {
+        let hand = cards("5H 5C 5S JD KC 2D");
+        let options = analyze_discards(&hand, false, 200, 1).unwrap();
+        assert_eq!(options.len(), 15);
+
+        let best = &options[0];
+        let mut keep = best.keep.to_vec();
+        keep.sort();
+        assert_eq!(keep, cards("5S 5C 5H JD"));
+        assert!(best.hand_ev > 14.0);
+        assert!(best.best_cut.1 >= 28);
+        assert!(best.worst_cut.1 <= best.best_cut.1);
+        assert_eq!(best.discard.len(), 2);
+
+        for pair in options.windows(2) {
+            assert!(pair[0].net(false) >= pair[1].net(false));
+        }
+
+        let again = analyze_discards(&hand, false, 200, 1).unwrap();
+        assert_eq!(again, options);
+    }
+
+    #[test]
+    fn test_analyze_five_card_discards() {
+        let options = analyze_discards(&cards("AH 2H 3H 4H KS"), false, 50, 2).unwrap();
+        assert_eq!(options.len(), 5);
+        assert_eq!(options.iter().map(|o| o.discard.len()).max(), Some(1));
+        assert_eq!(options[0].discard, cards("KS"));
+    }
+
+    #[test]
+    fn test_analyze_invalid_input() {
+        assert_eq!(
+            analyze_discards(&cards("AH 2H 3H 4H"), false, 0, 0),
+            Err(CribbageCoreError::NotEnoughCards)
+        );
+        assert_eq!(
+            analyze_discards(&cards("AH AH 3H 4H 5H"), false, 0, 0),
+            Err(CribbageCoreError::InvalidCard)
+        );
+    }
+}
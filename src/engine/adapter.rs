@@ -0,0 +1,190 @@
+use std::io::{self, BufRead, Write};
+
+use crate::board::{TwoPlayerScore, TwoPlayers};
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::game::{Player, Situation, TableEvent};
+use crate::hand::TwoPlayerDeal;
+use crate::pegging::Pegger;
+use crate::text::{format_cards, parse_cards, parse_event, parse_seat};
+
+// What an engine can rebuild of the table from the referee's messages
+struct Table {
+    seat: TwoPlayers,
+    target: u8,
+    dealer: TwoPlayers,
+    scores: TwoPlayerScore<u8>,
+    starter: Option<Card>,
+    crib_part: CardSet,
+    deal_events: Vec<TableEvent>,
+}
+
+impl Table {
+    fn new(seat: TwoPlayers, target: u8) -> Table {
+        Table {
+            seat,
+            target,
+            dealer: TwoPlayers::PlayerOne,
+            scores: TwoPlayerScore::default(),
+            starter: None,
+            crib_part: CardSet::new(),
+            deal_events: Vec::new(),
+        }
+    }
+
+    fn apply(&mut self, event: TableEvent) {
+        match event {
+            TableEvent::DealStarted { dealer } => {
+                self.dealer = dealer;
+                self.starter = None;
+                self.crib_part = CardSet::new();
+                self.deal_events.clear();
+            }
+            TableEvent::StarterCut { starter, heels } => {
+                self.starter = Some(starter);
+                self.award(self.dealer, heels);
+            }
+            TableEvent::Played { player, points, .. }
+            | TableEvent::SequenceEnded { player, points }
+            | TableEvent::Shown { player, points, .. } => self.award(player, points),
+            _ => {}
+        }
+
+        self.deal_events.push(event);
+    }
+
+    fn award(&mut self, player: TwoPlayers, points: u8) {
+        self.scores[player] = self.scores[player].saturating_add(points).min(self.target);
+    }
+
+    fn situation(&self) -> Situation<'_> {
+        Situation {
+            player: self.seat,
+            dealer: self.dealer,
+            own_score: self.scores[self.seat],
+            opponent_score: self.scores[self.seat.other()],
+            target: self.target,
+            starter: self.starter,
+            crib_part: self.crib_part,
+            deal_events: &self.deal_events,
+        }
+    }
+}
+
+// Runs `player` as an engine until the referee quits or closes its input
+pub fn run_engine<P, R, W>(player: &mut P, name: &str, input: R, mut output: W) -> io::Result<()>
+where
+    P: Player + ?Sized,
+    R: BufRead,
+    W: Write,
+{
+    let mut table = Table::new(TwoPlayers::PlayerOne, 121);
+    for line in input.lines() {
+        let line = line?;
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.as_slice() {
+            [] => continue,
+            ["cribbage"] => {
+                writeln!(output, "id name {}", name)?;
+                writeln!(output, "cribbageok")?;
+            }
+            ["newgame", seat, target] => match (parse_seat(seat), target.parse()) {
+                (Some(seat), Ok(target)) => table = Table::new(seat, target),
+                _ => writeln!(output, "info error invalid newgame")?,
+            },
+            ["event", event @ ..] => match parse_event(event) {
+                Some(event) => {
+                    table.apply(event);
+                    player.observe(&event, &table.situation());
+                }
+                None => writeln!(output, "info error invalid event")?,
+            },
+            ["discard", cards @ ..] => match parse_cards(cards) {
+                Some(cards) if cards.len() == 6 => {
+                    let deal = TwoPlayerDeal::new([
+                        cards[0], cards[1], cards[2], cards[3], cards[4], cards[5],
+                    ]);
+                    let crib = player.choose_discard(&deal, &table.situation());
+                    table.crib_part = CardSet::from_cards(&crib);
+                    writeln!(output, "crib {}", format_cards(&crib))?;
+                }
+                _ => writeln!(output, "error invalid discard")?,
+            },
+            ["peg", "sequence", rest @ ..] => match parse_peg(rest) {
+                Some((pegger, hand, legal)) => {
+                    let card = player.choose_peg(&pegger, &hand, &legal, &table.situation());
+                    writeln!(output, "play {}", card)?;
+                }
+                None => writeln!(output, "error invalid peg")?,
+            },
+            ["quit"] => break,
+            _ => writeln!(output, "info error unknown command")?,
+        }
+
+        output.flush()?;
+    }
+
+    output.flush()
+}
+
+fn parse_peg(words: &[&str]) -> Option<(Pegger, Vec<Card>, Vec<Card>)> {
+    let hand_at = words.iter().position(|&word| word == "hand")?;
+    let legal_at = words.iter().position(|&word| word == "legal")?;
+    if legal_at < hand_at {
+        return None;
+    }
+
+    let mut pegger = Pegger::new();
+    for card in parse_cards(&words[..hand_at])? {
+        pegger.play_card(card).ok()?;
+    }
+
+    let hand = parse_cards(&words[hand_at + 1..legal_at])?;
+    let legal = parse_cards(&words[legal_at + 1..])?;
+    if legal.is_empty() {
+        return None;
+    }
+
+    Some((pegger, hand, legal))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bots::GreedyBot;
+    use crate::engine::run_engine;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_run_engine() {
+        let input = "cribbage\n\
+                     newgame 2 121\n\
+                     event deal 1\n\
+                     discard 5H 5C JD 2S 9C KH\n\
+                     event starter 4D 0\n\
+                     event play 1 TS 10 0\n\
+                     peg sequence TS hand 5H 5C JD 2S legal 5H 5C JD 2S\n\
+                     bogus\n\
+                     discard 5H\n\
+                     quit\n\
+                     peg sequence hand 5H legal 5H\n";
+        let mut output = Vec::new();
+        run_engine(
+            &mut GreedyBot::new(),
+            "greedy",
+            Cursor::new(input),
+            &mut output,
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], "id name greedy");
+        assert_eq!(lines[1], "cribbageok");
+        assert!(lines[2].starts_with("crib "));
+        assert!(!lines[2].contains("5H") && !lines[2].contains("5C"));
+        assert_eq!(lines[3], "play 5H");
+        assert_eq!(lines[4], "info error unknown command");
+        assert_eq!(lines[5], "error invalid discard");
+        assert_eq!(lines.len(), 6);
+    }
+}
@@ -0,0 +1,26 @@
+// A line-based protocol, in the spirit of UCI, for bots running as separate programs. The
+// referee owns the game and talks to each engine over its stdin and stdout:
+//
+//   referee                              engine
+//   cribbage                             id name <text> (optional), then cribbageok
+//   newgame <seat> <target>
+//   event <event>
+//   discard <six cards>                  crib <card> <card>, or error <text>
+//   peg sequence <cards> hand <cards> legal <cards>
+//                                        play <card>, or error <text>
+//   quit
+//
+// Seats are 1 and 2, cards use their two-character notation (e.g. "TD"), and engines may send
+// `info <text>` lines at any time. An engine reports a line it cannot use with
+// `info error <text>`, except that a malformed `discard` or `peg` is answered with
+// `error <text>` in place of the reply the referee is waiting for. Events mirror `TableEvent`:
+//
+//   deal <dealer>, discard <seat>, starter <card> <heels>, play <seat> <card> <count> <points>,
+//   go <seat> <count>, last <seat> <points>, show <seat> <hand|crib> <four cards> <points>,
+//   win <seat>
+
+mod adapter;
+mod referee;
+
+pub use self::adapter::run_engine;
+pub use self::referee::{EngineError, EnginePlayer};
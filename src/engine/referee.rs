@@ -0,0 +1,422 @@
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::board::TwoPlayers;
+use crate::card::Card;
+use crate::game::{Player, Situation, TableEvent};
+use crate::hand::TwoPlayerDeal;
+use crate::pegging::Pegger;
+use crate::text::{format_cards, format_event, parse_cards, seat};
+
+const QUIT_GRACE: Duration = Duration::from_millis(200);
+
+#[derive(Debug)]
+pub enum EngineError {
+    Io(io::Error),
+    Timeout,
+    Disconnected,
+    UnexpectedReply(String),
+}
+
+impl Display for EngineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineError::Io(error) => write!(f, "{}", error),
+            EngineError::Timeout => write!(f, "engine timed out"),
+            EngineError::Disconnected => write!(f, "engine disconnected"),
+            EngineError::UnexpectedReply(reply) => write!(f, "unexpected reply: {}", reply),
+        }
+    }
+}
+
+impl error::Error for EngineError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            EngineError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for EngineError {
+    fn from(error: io::Error) -> EngineError {
+        EngineError::Io(error)
+    }
+}
+
+// A `Player` whose decisions come from an engine speaking the text protocol. Once an engine times
+// out or disconnects it is no longer consulted; it and any illegal replies are recorded as faults
+// and answered with a fallback move so the game can still finish
+pub struct EnginePlayer {
+    name: String,
+    writer: Box<dyn Write + Send>,
+    replies: Receiver<String>,
+    timeout: Duration,
+    child: Option<Child>,
+    seat: Option<TwoPlayers>,
+    failed: bool,
+    faults: Vec<EngineError>,
+}
+
+impl EnginePlayer {
+    pub fn new<R, W>(reader: R, writer: W, timeout: Duration) -> Result<EnginePlayer, EngineError>
+    where
+        R: Read + Send + 'static,
+        W: Write + Send + 'static,
+    {
+        EnginePlayer::start(reader, Box::new(writer), timeout, None)
+    }
+
+    pub fn spawn(command: &mut Command, timeout: Duration) -> Result<EnginePlayer, EngineError> {
+        let mut child = command
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let (stdin, stdout) = match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => (stdin, stdout),
+            _ => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(EngineError::Disconnected);
+            }
+        };
+
+        EnginePlayer::start(stdout, Box::new(stdin), timeout, Some(child))
+    }
+
+    fn start<R: Read + Send + 'static>(
+        reader: R,
+        writer: Box<dyn Write + Send>,
+        timeout: Duration,
+        child: Option<Child>,
+    ) -> Result<EnginePlayer, EngineError> {
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                let line = match line {
+                    Ok(line) => line,
+                    Err(_) => break,
+                };
+
+                let line = line.trim();
+                if line.is_empty() || line == "info" || line.starts_with("info ") {
+                    continue;
+                }
+
+                if sender.send(line.to_string()).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = EnginePlayer {
+            name: String::new(),
+            writer,
+            replies,
+            timeout,
+            child,
+            seat: None,
+            failed: false,
+            faults: Vec::new(),
+        };
+
+        engine.send("cribbage")?;
+        let deadline = Instant::now() + timeout;
+        loop {
+            let wait = deadline.saturating_duration_since(Instant::now());
+            let reply = engine.receive(wait)?;
+            if reply == "cribbageok" {
+                return Ok(engine);
+            }
+
+            if let Some(name) = reply.strip_prefix("id name ") {
+                engine.name = name.to_string();
+            }
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn faults(&self) -> &[EngineError] {
+        &self.faults
+    }
+
+    fn send(&mut self, line: &str) -> Result<(), EngineError> {
+        writeln!(self.writer, "{}", line)?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    fn receive(&mut self, timeout: Duration) -> Result<String, EngineError> {
+        self.replies
+            .recv_timeout(timeout)
+            .map_err(|error| match error {
+                RecvTimeoutError::Timeout => EngineError::Timeout,
+                RecvTimeoutError::Disconnected => EngineError::Disconnected,
+            })
+    }
+
+    fn notify(&mut self, line: &str) {
+        if self.failed {
+            return;
+        }
+
+        if let Err(error) = self.send(line) {
+            self.fail(error);
+        }
+    }
+
+    // Sends a request and returns the cards of a reply starting with `keyword`
+    fn request(&mut self, line: &str, keyword: &str) -> Option<Vec<Card>> {
+        if self.failed {
+            return None;
+        }
+
+        let timeout = self.timeout;
+        let reply = match self.send(line).and_then(|_| self.receive(timeout)) {
+            Ok(reply) => reply,
+            Err(error) => {
+                self.fail(error);
+                return None;
+            }
+        };
+
+        let words: Vec<&str> = reply.split_whitespace().collect();
+        let cards = match words.split_first() {
+            Some((first, rest)) if *first == keyword => parse_cards(rest),
+            _ => None,
+        };
+
+        if cards.is_none() {
+            self.faults.push(EngineError::UnexpectedReply(reply));
+        }
+
+        cards
+    }
+
+    fn fail(&mut self, error: EngineError) {
+        self.failed = true;
+        self.faults.push(error);
+    }
+
+    fn join(&mut self, situation: &Situation<'_>) {
+        if self.seat.is_none() {
+            self.seat = Some(situation.player);
+            let line = format!("newgame {} {}", seat(situation.player), situation.target);
+            self.notify(&line);
+        }
+    }
+}
+
+impl Player for EnginePlayer {
+    fn choose_discard(&mut self, deal: &TwoPlayerDeal, situation: &Situation<'_>) -> [Card; 2] {
+        self.join(situation);
+        let cards = deal.cards();
+        let line = format!("discard {}", format_cards(cards));
+        match self.request(&line, "crib").as_deref() {
+            Some(&[a, b]) if a != b && cards.contains(&a) && cards.contains(&b) => return [a, b],
+            Some(_) => {
+                self.faults.push(EngineError::UnexpectedReply(format!(
+                    "illegal discard for {}",
+                    line
+                )));
+            }
+            None => {}
+        }
+
+        [cards[4], cards[5]]
+    }
+
+    fn choose_peg(
+        &mut self,
+        pegger: &Pegger,
+        hand: &[Card],
+        legal: &[Card],
+        situation: &Situation<'_>,
+    ) -> Card {
+        self.join(situation);
+        let line = format!(
+            "peg sequence {} hand {} legal {}",
+            format_cards(pegger.played_cards()),
+            format_cards(hand),
+            format_cards(legal)
+        );
+        match self.request(&line, "play").as_deref() {
+            Some(&[card]) if legal.contains(&card) => return card,
+            Some(_) => {
+                self.faults.push(EngineError::UnexpectedReply(format!(
+                    "illegal play for {}",
+                    line
+                )));
+            }
+            None => {}
+        }
+
+        legal[0]
+    }
+
+    fn observe(&mut self, event: &TableEvent, situation: &Situation<'_>) {
+        self.join(situation);
+        let line = format!("event {}", format_event(event));
+        self.notify(&line);
+        if let TableEvent::GameWon { .. } = event {
+            self.seat = None;
+        }
+    }
+}
+
+impl Drop for EnginePlayer {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        if let Some(mut child) = self.child.take() {
+            let deadline = Instant::now() + QUIT_GRACE;
+            while Instant::now() < deadline {
+                match child.try_wait() {
+                    Ok(Some(_)) => return,
+                    Ok(None) => thread::sleep(Duration::from_millis(5)),
+                    Err(_) => break,
+                }
+            }
+
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::{RandomBot, StrongBot};
+    use crate::engine::{run_engine, EngineError, EnginePlayer};
+    use crate::game::{play_two_player_game, TwoPlayerGame};
+    use std::io::{self, BufReader, ErrorKind, Read, Write};
+    use std::sync::mpsc::{self, Receiver, Sender};
+    use std::thread;
+    use std::time::Duration;
+
+    // An in-memory pipe whose reader sees the end of input once the writer has been dropped
+    struct PipeReader {
+        chunks: Receiver<Vec<u8>>,
+        chunk: Vec<u8>,
+        position: usize,
+    }
+
+    struct PipeWriter(Sender<Vec<u8>>);
+
+    fn pipe() -> (PipeReader, PipeWriter) {
+        let (sender, chunks) = mpsc::channel();
+        let reader = PipeReader {
+            chunks,
+            chunk: Vec::new(),
+            position: 0,
+        };
+        (reader, PipeWriter(sender))
+    }
+
+    impl Read for PipeReader {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.position == self.chunk.len() {
+                match self.chunks.recv() {
+                    Ok(chunk) => {
+                        self.chunk = chunk;
+                        self.position = 0;
+                    }
+                    Err(_) => return Ok(0),
+                }
+            }
+
+            let len = (self.chunk.len() - self.position).min(buf.len());
+            buf[..len].copy_from_slice(&self.chunk[self.position..self.position + len]);
+            self.position += len;
+            Ok(len)
+        }
+    }
+
+    impl Write for PipeWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0
+                .send(buf.to_vec())
+                .map_err(|_| io::Error::new(ErrorKind::BrokenPipe, "pipe closed"))?;
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn in_process_engine(seed: u64) -> EnginePlayer {
+        let (from_referee, to_engine) = pipe();
+        let (from_engine, to_referee) = pipe();
+        thread::spawn(move || {
+            let mut bot = StrongBot::new(seed);
+            run_engine(&mut bot, "strong", BufReader::new(from_referee), to_referee).unwrap();
+        });
+
+        EnginePlayer::new(from_engine, to_engine, Duration::from_secs(10)).unwrap()
+    }
+
+    #[test]
+    fn test_engine_matches_built_in_agent() {
+        let mut direct = TwoPlayerGame::with_seed(TwoPlayers::PlayerTwo, 8);
+        play_two_player_game(&mut direct, &mut StrongBot::new(4), &mut RandomBot::new(5)).unwrap();
+
+        let mut engine = in_process_engine(4);
+        assert_eq!(engine.name(), "strong");
+        let mut refereed = TwoPlayerGame::with_seed(TwoPlayers::PlayerTwo, 8);
+        play_two_player_game(&mut refereed, &mut engine, &mut RandomBot::new(5)).unwrap();
+        assert_eq!(refereed.events(), direct.events());
+
+        // The same engine process can play several games in a row
+        for seed in 0..2 {
+            let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, seed);
+            play_two_player_game(&mut game, &mut RandomBot::new(seed), &mut engine).unwrap();
+        }
+
+        assert!(engine.faults().is_empty());
+    }
+
+    #[test]
+    fn test_silent_engine_times_out() {
+        let (_from_referee, to_engine) = pipe();
+        let (from_engine, _to_referee) = pipe();
+        match EnginePlayer::new(from_engine, to_engine, Duration::from_millis(50)) {
+            Err(EngineError::Timeout) => {}
+            other => panic!(
+                "unexpected {:?}",
+                other.map(|engine| engine.name().to_string())
+            ),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_spawned_engine_faults() {
+        let script = "read line; echo 'info starting'; echo cribbageok; \
+                      while read line; do case $line in \
+                      discard*) echo 'crib 2S 2S';; peg*) echo 'play ZZ'; exit;; esac; done";
+        let mut engine = EnginePlayer::spawn(
+            std::process::Command::new("sh").arg("-c").arg(script),
+            Duration::from_secs(5),
+        )
+        .unwrap();
+
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 2);
+        let winner = play_two_player_game(&mut game, &mut engine, &mut RandomBot::new(2)).unwrap();
+        assert_eq!(game.winner(), Some(winner));
+        match engine.faults() {
+            [EngineError::UnexpectedReply(_), EngineError::UnexpectedReply(_), rest @ ..] => {
+                assert!(!rest.is_empty());
+            }
+            faults => panic!("unexpected {:?}", faults),
+        }
+    }
+}
@@ -0,0 +1,491 @@
+use std::io::{self, BufRead, Read, Stdout, Write};
+use std::str::FromStr;
+
+use crate::board::TwoPlayers;
+use crate::card::Card;
+use crate::game::{Player, Situation, TableEvent};
+use crate::hand::{FourPlayerDeal, Hand, ThreePlayerDeal, TwoPlayerDeal};
+use crate::pegging::Pegger;
+use crate::text::format_cards;
+
+// Reads stdin a line at a time, holding the lock only while reading, so that several console
+// players can share it
+#[derive(Debug, Default)]
+pub struct StdinLines {
+    line: String,
+    consumed: usize,
+}
+
+impl Read for StdinLines {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for StdinLines {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.consumed == self.line.len() {
+            self.line.clear();
+            self.consumed = 0;
+            io::stdin().read_line(&mut self.line)?;
+        }
+
+        Ok(&self.line.as_bytes()[self.consumed..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.consumed = (self.consumed + amt).min(self.line.len());
+    }
+}
+
+pub struct ConsolePlayer<R, W> {
+    name: String,
+    opponent_name: String,
+    narrating: bool,
+    input: R,
+    output: W,
+    starter: Option<Card>,
+    quit: bool,
+}
+
+impl ConsolePlayer<StdinLines, Stdout> {
+    pub fn stdio(name: &str) -> Self {
+        ConsolePlayer::new(name, StdinLines::default(), io::stdout())
+    }
+}
+
+impl<R: BufRead, W: Write> ConsolePlayer<R, W> {
+    pub fn new(name: &str, input: R, output: W) -> ConsolePlayer<R, W> {
+        ConsolePlayer {
+            name: name.to_string(),
+            opponent_name: "Opponent".to_string(),
+            narrating: true,
+            input,
+            output,
+            starter: None,
+            quit: false,
+        }
+    }
+
+    pub fn set_opponent_name(&mut self, name: &str) {
+        self.opponent_name = name.to_string();
+    }
+
+    // When two console players share one screen only one of them should narrate the table, or
+    // every event is printed twice
+    pub fn set_narrating(&mut self, narrating: bool) {
+        self.narrating = narrating;
+    }
+
+    pub fn into_output(self) -> W {
+        self.output
+    }
+
+    fn print_cards(&mut self, title: &str, cards: &[Card]) {
+        let _ = writeln!(self.output, "{} {}:", self.name, title);
+        for (index, card) in cards.iter().enumerate() {
+            let _ = writeln!(self.output, "  {}: {}", index, card);
+        }
+    }
+
+    fn print_board(&mut self, situation: &Situation<'_>) {
+        let _ = writeln!(
+            self.output,
+            "Board: {} {}, {} {} (playing to {})",
+            self.name,
+            situation.own_score,
+            self.opponent_name,
+            situation.opponent_score,
+            situation.target
+        );
+    }
+
+    // None once the input has ended or the player has typed quit
+    fn read_line(&mut self, prompt: &str) -> Option<String> {
+        if self.quit {
+            return None;
+        }
+
+        let _ = write!(self.output, "{}", prompt);
+        let _ = self.output.flush();
+
+        let mut input = String::new();
+        let input = match self.input.read_line(&mut input) {
+            Ok(0) | Err(_) => None,
+            Ok(_) => Some(input.trim().to_string()),
+        };
+
+        match input {
+            Some(input) if !input.eq_ignore_ascii_case("quit") && input != "q" => Some(input),
+            _ => {
+                let _ = writeln!(self.output, "\n{} left the game.", self.name);
+                self.quit = true;
+                None
+            }
+        }
+    }
+
+    fn read_cards(&mut self, prompt: &str, cards: &[Card], count: usize) -> Option<Vec<Card>> {
+        loop {
+            let input = self.read_line(prompt)?;
+            match choose_cards(&input, cards, count) {
+                Ok(chosen) => return Some(chosen),
+                Err(message) => {
+                    let _ = writeln!(self.output, "{}", message);
+                }
+            }
+        }
+    }
+
+    fn choose_one(&mut self, title: &str, cards: &[Card]) -> Card {
+        self.print_cards("Cards", cards);
+        self.read_cards(title, cards, 1)
+            .map_or(cards[0], |chosen| chosen[0])
+    }
+
+    fn player_name(&self, player: TwoPlayers, situation: &Situation<'_>) -> String {
+        if player == situation.player {
+            self.name.clone()
+        } else {
+            self.opponent_name.clone()
+        }
+    }
+}
+
+// Each word is either an index into `cards` or a card such as 5H; the choice must name `count`
+// different cards
+fn choose_cards(input: &str, cards: &[Card], count: usize) -> Result<Vec<Card>, String> {
+    let mut chosen = Vec::new();
+    for word in input.split_whitespace() {
+        let card = match word.parse::<usize>() {
+            Ok(index) => *cards.get(index).ok_or_else(|| {
+                format!("{} is not an index from 0 to {}.", index, cards.len() - 1)
+            })?,
+            Err(_) => match Card::from_str(&word.to_uppercase()) {
+                Ok(card) if cards.contains(&card) => card,
+                Ok(card) => return Err(format!("{} is not one of the cards shown.", card)),
+                Err(_) => return Err(format!("{} is not a card or an index.", word)),
+            },
+        };
+
+        if chosen.contains(&card) {
+            return Err(format!("{} was chosen twice.", card));
+        }
+
+        chosen.push(card);
+    }
+
+    if chosen.len() != count {
+        let cards = if count == 1 { "card" } else { "cards" };
+        return Err(format!("Please choose {} {}.", count, cards));
+    }
+
+    Ok(chosen)
+}
+
+impl<R: BufRead, W: Write> Player for ConsolePlayer<R, W> {
+    fn choose_discard(&mut self, deal: &TwoPlayerDeal, situation: &Situation<'_>) -> [Card; 2] {
+        self.print_cards("Cards", deal.cards());
+        let whose = if situation.is_dealer() {
+            "your"
+        } else {
+            "the opponent's"
+        };
+        let prompt = format!("Choose 2 cards for {} crib (by index or like 5H): ", whose);
+        let crib = self
+            .read_cards(&prompt, deal.cards(), 2)
+            .unwrap_or_else(|| deal.cards()[..2].to_vec());
+        [crib[0], crib[1]]
+    }
+
+    fn choose_three_player_discard(
+        &mut self,
+        deal: &ThreePlayerDeal,
+        _situation: &Situation<'_>,
+    ) -> Card {
+        self.choose_one(
+            "Choose 1 card for the crib (by index or like 5H): ",
+            deal.cards(),
+        )
+    }
+
+    fn choose_four_player_discard(
+        &mut self,
+        deal: &FourPlayerDeal,
+        _situation: &Situation<'_>,
+    ) -> Card {
+        self.choose_one(
+            "Choose 1 card for the crib (by index or like 5H): ",
+            deal.cards(),
+        )
+    }
+
+    fn choose_peg(
+        &mut self,
+        pegger: &Pegger,
+        hand: &[Card],
+        legal: &[Card],
+        _situation: &Situation<'_>,
+    ) -> Card {
+        let _ = writeln!(self.output, "Count: {}", pegger.count());
+        if hand.len() > legal.len() {
+            let _ = writeln!(self.output, "{} Hand: {}", self.name, format_cards(hand));
+        }
+
+        self.print_cards("Playable Cards", legal);
+        self.read_cards("Choose a card to play (by index or like 5H): ", legal, 1)
+            .map_or(legal[0], |chosen| chosen[0])
+    }
+
+    fn observe(&mut self, event: &TableEvent, situation: &Situation<'_>) {
+        if !self.narrating {
+            return;
+        }
+
+        let _ = match *event {
+            TableEvent::DealStarted { dealer } => {
+                let _ = writeln!(self.output);
+                self.print_board(situation);
+                writeln!(
+                    self.output,
+                    "{} deals.",
+                    self.player_name(dealer, situation)
+                )
+            }
+            TableEvent::StarterCut { starter, heels } => {
+                self.starter = Some(starter);
+                let _ = match heels {
+                    0 => writeln!(self.output, "Cut card: {}", starter),
+                    _ => writeln!(
+                        self.output,
+                        "Cut card: {} ({} for his heels)",
+                        starter, heels
+                    ),
+                };
+                self.print_board(situation);
+                Ok(())
+            }
+            TableEvent::Played {
+                player,
+                card,
+                count,
+                points,
+            } => {
+                let _ = write!(
+                    self.output,
+                    "{} played {} (count: {})",
+                    self.player_name(player, situation),
+                    card,
+                    count
+                );
+                match points {
+                    0 => writeln!(self.output),
+                    _ => writeln!(self.output, " for {}", points),
+                }
+            }
+            TableEvent::Go { player, .. } => {
+                writeln!(self.output, "{}: Go", self.player_name(player, situation))
+            }
+            TableEvent::SequenceEnded { player, points } => {
+                if points > 0 {
+                    let _ = writeln!(
+                        self.output,
+                        "{} scored {} for the go",
+                        self.player_name(player, situation),
+                        points
+                    );
+                }
+
+                let played = situation
+                    .deal_events
+                    .iter()
+                    .filter(|event| matches!(event, TableEvent::Played { .. }))
+                    .count();
+                if played == 8 {
+                    self.print_board(situation);
+                }
+
+                Ok(())
+            }
+            TableEvent::Shown {
+                player,
+                cards,
+                is_crib,
+                points,
+            } => {
+                let _ = write!(
+                    self.output,
+                    "{} {}: {}",
+                    self.player_name(player, situation),
+                    if is_crib { "crib" } else { "hand" },
+                    format_cards(&cards)
+                );
+                if let Some(starter) = self.starter {
+                    let breakdown = Hand::new(cards, starter, is_crib).breakdown();
+                    let parts: Vec<String> = [
+                        ("fifteens", breakdown.fifteens),
+                        ("pairs", breakdown.pairs),
+                        ("runs", breakdown.runs),
+                        ("flush", breakdown.flush),
+                        ("nobs", breakdown.nobs),
+                    ]
+                    .iter()
+                    .filter(|(_, points)| *points > 0)
+                    .map(|(name, points)| format!("{} {}", name, points))
+                    .collect();
+                    let _ = write!(self.output, " + {} = {}", starter, breakdown.total());
+                    if !parts.is_empty() {
+                        let _ = write!(self.output, " ({})", parts.join(", "));
+                    }
+
+                    if points != breakdown.total() {
+                        let _ = write!(self.output, ", claimed {}", points);
+                    }
+                } else {
+                    let _ = write!(self.output, " for {}", points);
+                }
+
+                writeln!(self.output)
+            }
+            TableEvent::GameWon { winner } => writeln!(
+                self.output,
+                "{} won {}-{}",
+                self.player_name(winner, situation),
+                situation.own_score.max(situation.opponent_score),
+                situation.own_score.min(situation.opponent_score)
+            ),
+            TableEvent::Discarded { .. } => Ok(()),
+        };
+    }
+
+    fn has_quit(&self) -> bool {
+        self.quit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::RandomBot;
+    use crate::card::Card;
+    use crate::game::{
+        play_two_player_game, ConsolePlayer, Player, Situation, TableEvent, TwoPlayerGame,
+    };
+    use crate::pegging::Pegger;
+    use crate::CribbageCoreError;
+    use std::io::Cursor;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_choose_discard_reprompts() {
+        let game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 9);
+        let deal = game.deal(TwoPlayers::PlayerOne).unwrap();
+        let situation = Situation::new(&game, TwoPlayers::PlayerOne);
+        let input = format!(
+            "0 0\n7 1\nx\n3\n{} 4\n",
+            deal.cards()[1].to_string().to_lowercase()
+        );
+        let mut player = ConsolePlayer::new("Player 1", Cursor::new(input), Vec::new());
+
+        let mut crib = player.choose_discard(deal, &situation);
+        crib.sort();
+        let mut expected = [deal.cards()[1], deal.cards()[4]];
+        expected.sort();
+        assert_eq!(crib, expected);
+        assert!(!player.has_quit());
+
+        let output = String::from_utf8(player.into_output()).unwrap();
+        assert!(output.contains(&format!("{} was chosen twice.", deal.cards()[0])));
+        assert!(output.contains("7 is not an index from 0 to 5."));
+        assert!(output.contains("x is not a card or an index."));
+        assert!(output.contains("Please choose 2 cards."));
+    }
+
+    #[test]
+    fn test_choose_peg() {
+        let game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 9);
+        let situation = Situation::new(&game, TwoPlayers::PlayerTwo);
+        let legal = [Card::from_str("5H").unwrap(), Card::from_str("KS").unwrap()];
+        let mut player = ConsolePlayer::new("Player 2", Cursor::new("2\n1\n"), Vec::new());
+        let card = player.choose_peg(&Pegger::new(), &legal, &legal, &situation);
+        assert_eq!(card, legal[1]);
+
+        let input = Cursor::new("QS\n5h\n");
+        let mut player = ConsolePlayer::new("Player 2", input, Vec::new());
+        let card = player.choose_peg(&Pegger::new(), &legal, &legal, &situation);
+        assert_eq!(card, legal[0]);
+        let output = String::from_utf8(player.into_output()).unwrap();
+        assert!(output.contains("QS is not one of the cards shown."));
+    }
+
+    #[test]
+    fn test_console_player_quits() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 9);
+        let mut console = ConsolePlayer::new("Player 1", Cursor::new("x\n"), Vec::new());
+        let result = play_two_player_game(&mut game, &mut console, &mut RandomBot::new(1));
+        assert_eq!(result, Err(CribbageCoreError::PlayerQuit));
+        assert!(console.has_quit());
+        assert!(game.crib_part(TwoPlayers::PlayerOne).is_none());
+
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 9);
+        let mut console = ConsolePlayer::new("Player 2", Cursor::new("quit\n"), Vec::new());
+        let result = play_two_player_game(&mut game, &mut RandomBot::new(1), &mut console);
+        assert_eq!(result, Err(CribbageCoreError::PlayerQuit));
+        let output = String::from_utf8(console.into_output()).unwrap();
+        assert!(output.ends_with("Player 2 left the game.\n"));
+    }
+
+    #[test]
+    fn test_hot_seat_narration() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 9);
+        let mut player_one = ConsolePlayer::new("Player 1", Cursor::new("quit\n"), Vec::new());
+        player_one.set_opponent_name("Player 2");
+        let mut player_two = ConsolePlayer::new("Player 2", Cursor::new(""), Vec::new());
+        player_two.set_narrating(false);
+        let result = play_two_player_game(&mut game, &mut player_one, &mut player_two);
+        assert_eq!(result, Err(CribbageCoreError::PlayerQuit));
+
+        let output = String::from_utf8(player_one.into_output()).unwrap();
+        assert!(
+            output.contains("Board: Player 1 0, Player 2 0 (playing to 121)\nPlayer 1 deals.\n")
+        );
+        let output = String::from_utf8(player_two.into_output()).unwrap();
+        assert!(!output.contains("Board:"));
+        assert!(!output.contains("deals."));
+    }
+
+    #[test]
+    fn test_show_breakdown() {
+        let game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 9);
+        let situation = Situation::new(&game, TwoPlayers::PlayerOne);
+        let card = |s| Card::from_str(s).unwrap();
+        let mut player = ConsolePlayer::new("Player 1", Cursor::new(""), Vec::new());
+        player.observe(
+            &TableEvent::StarterCut {
+                starter: card("5D"),
+                heels: 0,
+            },
+            &situation,
+        );
+        player.observe(
+            &TableEvent::Shown {
+                player: TwoPlayers::PlayerTwo,
+                cards: [card("5H"), card("5C"), card("5S"), card("JD")],
+                is_crib: false,
+                points: 29,
+            },
+            &situation,
+        );
+
+        let output = String::from_utf8(player.into_output()).unwrap();
+        assert!(
+            output.starts_with("Cut card: 5D\nBoard: Player 1 0, Opponent 0 (playing to 121)\n")
+        );
+        assert!(output
+            .ends_with("Opponent hand: 5H 5C 5S JD + 5D = 29 (fifteens 16, pairs 12, nobs 1)\n"));
+    }
+}
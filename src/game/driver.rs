@@ -0,0 +1,250 @@
+use crate::board::TwoPlayers;
+use crate::card::Card;
+use crate::game::{GamePhase, Player, Situation, TwoPlayerGame};
+use crate::hand::Hand;
+use crate::CribbageCoreError;
+
+// Something the driver can play a game through, either the game itself or a wrapper around it
+pub(crate) trait GameTable {
+    fn game(&self) -> &TwoPlayerGame;
+
+    fn discard(&mut self, player: TwoPlayers, crib: [Card; 2]) -> Result<(), CribbageCoreError>;
+
+    fn play(&mut self, player: TwoPlayers, card: Card) -> Result<u8, CribbageCoreError>;
+
+    fn show_with_claims<F>(&mut self, claim: F) -> Result<(), CribbageCoreError>
+    where
+        F: FnMut(TwoPlayers, &Hand, u8) -> u8;
+}
+
+impl GameTable for TwoPlayerGame {
+    fn game(&self) -> &TwoPlayerGame {
+        self
+    }
+
+    fn discard(&mut self, player: TwoPlayers, crib: [Card; 2]) -> Result<(), CribbageCoreError> {
+        TwoPlayerGame::discard(self, player, crib)
+    }
+
+    fn play(&mut self, player: TwoPlayers, card: Card) -> Result<u8, CribbageCoreError> {
+        TwoPlayerGame::play(self, player, card)
+    }
+
+    fn show_with_claims<F>(&mut self, claim: F) -> Result<(), CribbageCoreError>
+    where
+        F: FnMut(TwoPlayers, &Hand, u8) -> u8,
+    {
+        TwoPlayerGame::show_with_claims(self, claim)
+    }
+}
+
+pub fn play_two_player_game(
+    game: &mut TwoPlayerGame,
+    player_one: &mut dyn Player,
+    player_two: &mut dyn Player,
+) -> Result<TwoPlayers, CribbageCoreError> {
+    drive(game, player_one, player_two)
+}
+
+pub(crate) fn drive<T: GameTable>(
+    table: &mut T,
+    player_one: &mut dyn Player,
+    player_two: &mut dyn Player,
+) -> Result<TwoPlayers, CribbageCoreError> {
+    let mut players: [&mut dyn Player; 2] = [player_one, player_two];
+    let game = table.game();
+    let mut observed = game.events().len();
+    for (index, player) in players.iter_mut().enumerate() {
+        let id = player_id(index);
+        for event in game.events() {
+            player.observe(event, &Situation::new(game, id));
+        }
+    }
+
+    loop {
+        let game = table.game();
+        match game.phase() {
+            GamePhase::Discard => {
+                for id in &[game.pone(), game.dealer()] {
+                    let game = table.game();
+                    let deal = match game.deal(*id) {
+                        Some(deal) => deal,
+                        None => continue,
+                    };
+                    let situation = Situation::new(game, *id);
+                    let mut crib = players[index(*id)].choose_discard(deal, &situation);
+                    check_quit(&players, *id)?;
+                    let cards = deal.cards();
+                    if crib[0] == crib[1] || !crib.iter().all(|card| cards.contains(card)) {
+                        let player = &mut players[index(*id)];
+                        player.choice_rejected(CribbageCoreError::InvalidCard, &situation);
+                        crib = [cards[0], cards[1]];
+                    }
+                    table.discard(*id, crib)?;
+                }
+            }
+            GamePhase::Pegging => {
+                let id = game.turn().ok_or(CribbageCoreError::WrongPhase)?;
+                let legal = game.legal_plays(id);
+                let situation = Situation::new(game, id);
+                let mut card = players[index(id)].choose_peg(
+                    game.pegger(),
+                    game.pegging_hand(id),
+                    &legal,
+                    &situation,
+                );
+                check_quit(&players, id)?;
+                if !legal.contains(&card) {
+                    players[index(id)].choice_rejected(CribbageCoreError::InvalidCard, &situation);
+                    card = legal[0];
+                }
+                table.play(id, card)?;
+            }
+            GamePhase::Show => {
+                let snapshot = game.clone();
+                table.show_with_claims(|id, hand, score| {
+                    players[index(id)].claim_score(hand, score, &Situation::new(&snapshot, id))
+                })?;
+                for &id in &[TwoPlayers::PlayerOne, TwoPlayers::PlayerTwo] {
+                    check_quit(&players, id)?;
+                }
+            }
+            GamePhase::Finished => {}
+        }
+
+        let game = table.game();
+        let events = &game.events()[observed..];
+        for (index, player) in players.iter_mut().enumerate() {
+            let id = player_id(index);
+            for event in events {
+                player.observe(event, &Situation::new(game, id));
+            }
+        }
+        observed = game.events().len();
+
+        if let Some(winner) = game.winner() {
+            return Ok(winner);
+        }
+    }
+}
+
+fn check_quit(players: &[&mut dyn Player; 2], id: TwoPlayers) -> Result<(), CribbageCoreError> {
+    if players[index(id)].has_quit() {
+        return Err(CribbageCoreError::PlayerQuit);
+    }
+
+    Ok(())
+}
+
+fn index(id: TwoPlayers) -> usize {
+    match id {
+        TwoPlayers::PlayerOne => 0,
+        TwoPlayers::PlayerTwo => 1,
+    }
+}
+
+fn player_id(index: usize) -> TwoPlayers {
+    match index {
+        0 => TwoPlayers::PlayerOne,
+        _ => TwoPlayers::PlayerTwo,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::card::Card;
+    use crate::game::{play_two_player_game, Player, Situation, TableEvent, TwoPlayerGame};
+    use crate::hand::{Hand, TwoPlayerDeal};
+    use crate::pegging::Pegger;
+    use crate::CribbageCoreError;
+
+    #[derive(Default)]
+    struct FirstCardPlayer {
+        events: usize,
+        claims: usize,
+    }
+
+    impl Player for FirstCardPlayer {
+        fn choose_discard(&mut self, deal: &TwoPlayerDeal, _: &Situation<'_>) -> [Card; 2] {
+            [deal.cards()[0], deal.cards()[1]]
+        }
+
+        fn choose_peg(
+            &mut self,
+            _: &Pegger,
+            _: &[Card],
+            legal: &[Card],
+            _: &Situation<'_>,
+        ) -> Card {
+            legal[0]
+        }
+
+        fn claim_score(&mut self, _: &Hand, score: u8, _: &Situation<'_>) -> u8 {
+            self.claims += 1;
+            score
+        }
+
+        fn observe(&mut self, _: &TableEvent, _: &Situation<'_>) {
+            self.events += 1;
+        }
+    }
+
+    #[test]
+    fn test_play_two_player_game() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 11);
+        let mut one = FirstCardPlayer::default();
+        let mut two = FirstCardPlayer::default();
+
+        let winner = play_two_player_game(&mut game, &mut one, &mut two).unwrap();
+        assert_eq!(game.winner(), Some(winner));
+        assert_eq!(game.score(winner), 121);
+        assert!(game.score(winner.other()) < 121);
+        assert_eq!(one.events, game.events().len());
+        assert_eq!(two.events, game.events().len());
+        assert!(one.claims > 0);
+        assert!(two.claims > 0);
+        assert_eq!(game.events().last(), Some(&TableEvent::GameWon { winner }));
+
+        let mut replay = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 11);
+        let replay_winner = play_two_player_game(
+            &mut replay,
+            &mut FirstCardPlayer::default(),
+            &mut FirstCardPlayer::default(),
+        )
+        .unwrap();
+        assert_eq!(replay_winner, winner);
+        assert_eq!(replay.events(), game.events());
+    }
+
+    // Discards one card twice and plays the first card held, legal or not
+    #[derive(Default)]
+    struct CheatingPlayer {
+        rejected: usize,
+    }
+
+    impl Player for CheatingPlayer {
+        fn choose_discard(&mut self, deal: &TwoPlayerDeal, _: &Situation<'_>) -> [Card; 2] {
+            [deal.cards()[0], deal.cards()[0]]
+        }
+
+        fn choose_peg(&mut self, _: &Pegger, hand: &[Card], _: &[Card], _: &Situation<'_>) -> Card {
+            hand[0]
+        }
+
+        fn choice_rejected(&mut self, error: CribbageCoreError, _: &Situation<'_>) {
+            assert_eq!(error, CribbageCoreError::InvalidCard);
+            self.rejected += 1;
+        }
+    }
+
+    #[test]
+    fn test_illegal_choices_are_replaced() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 11);
+        let mut cheat = CheatingPlayer::default();
+        let winner =
+            play_two_player_game(&mut game, &mut cheat, &mut FirstCardPlayer::default()).unwrap();
+        assert_eq!(game.winner(), Some(winner));
+        assert!(cheat.rejected > 0);
+    }
+}
@@ -0,0 +1,630 @@
+mod console_player;
+mod driver;
+mod player;
+mod record;
+mod snapshot;
+mod view;
+
+pub use self::console_player::{ConsolePlayer, StdinLines};
+pub use self::driver::play_two_player_game;
+pub use self::player::{Player, Situation};
+pub use self::record::{
+    play_recorded_game, replay, GameLogEvent, GameRecorder, ReplayError, Replayer,
+};
+pub use self::snapshot::GameSnapshotError;
+pub use self::view::{PlayerView, SpectatorView};
+
+use rand::rngs::StdRng;
+use rand::{thread_rng, Rng, SeedableRng};
+
+use crate::board::{standard_two_player_board, Board, TwoPlayerScore, TwoPlayers};
+use crate::card::{Card, Rank};
+use crate::card_set::CardSet;
+use crate::deck::Deck;
+use crate::hand::{
+    deal_two_player_hand, CribCards, Hand, KeptCards, TwoPlayerCribPart, TwoPlayerDeal,
+};
+use crate::pegging::{Pegger, PeggingRound};
+use crate::CribbageCoreError;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GamePhase {
+    Discard,
+    Pegging,
+    Show,
+    Finished,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TableEvent {
+    DealStarted {
+        dealer: TwoPlayers,
+    },
+    Discarded {
+        player: TwoPlayers,
+    },
+    StarterCut {
+        starter: Card,
+        heels: u8,
+    },
+    Played {
+        player: TwoPlayers,
+        card: Card,
+        count: u8,
+        points: u8,
+    },
+    Go {
+        player: TwoPlayers,
+        count: u8,
+    },
+    SequenceEnded {
+        player: TwoPlayers,
+        points: u8,
+    },
+    Shown {
+        player: TwoPlayers,
+        cards: [Card; 4],
+        is_crib: bool,
+        points: u8,
+    },
+    GameWon {
+        winner: TwoPlayers,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct TwoPlayerGame {
+    seed: u64,
+    rng: StdRng,
+    deck: Deck,
+    board: Board<TwoPlayers, TwoPlayerScore<u8>>,
+    dealer: TwoPlayers,
+    phase: GamePhase,
+    deals: TwoPlayerScore<Option<TwoPlayerDeal>>,
+    kept: TwoPlayerScore<Option<KeptCards>>,
+    crib_parts: TwoPlayerScore<Option<TwoPlayerCribPart>>,
+    crib: Option<CribCards>,
+    starter: Option<Card>,
+    round: PeggingRound,
+    events: Vec<TableEvent>,
+    deal_start: usize,
+}
+
+impl TwoPlayerGame {
+    pub fn new(first_dealer: TwoPlayers) -> TwoPlayerGame {
+        TwoPlayerGame::with_seed(first_dealer, thread_rng().gen())
+    }
+
+    pub fn with_seed(first_dealer: TwoPlayers, seed: u64) -> TwoPlayerGame {
+        let mut game = TwoPlayerGame {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+            deck: Deck::new(),
+            board: standard_two_player_board(),
+            dealer: first_dealer,
+            phase: GamePhase::Discard,
+            deals: TwoPlayerScore::default(),
+            kept: TwoPlayerScore::default(),
+            crib_parts: TwoPlayerScore::default(),
+            crib: None,
+            starter: None,
+            round: PeggingRound::new(TwoPlayerScore::default(), first_dealer.other()),
+            events: Vec::new(),
+            deal_start: 0,
+        };
+
+        game.start_deal();
+        game
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn phase(&self) -> GamePhase {
+        self.phase
+    }
+
+    pub fn dealer(&self) -> TwoPlayers {
+        self.dealer
+    }
+
+    pub fn pone(&self) -> TwoPlayers {
+        self.dealer.other()
+    }
+
+    pub fn board(&self) -> &Board<TwoPlayers, TwoPlayerScore<u8>> {
+        &self.board
+    }
+
+    pub fn score(&self, player: TwoPlayers) -> u8 {
+        self.board.score(player)
+    }
+
+    pub fn winner(&self) -> Option<TwoPlayers> {
+        self.board.winner()
+    }
+
+    pub fn deck(&self) -> &Deck {
+        &self.deck
+    }
+
+    pub fn deal(&self, player: TwoPlayers) -> Option<&TwoPlayerDeal> {
+        self.deals[player].as_ref()
+    }
+
+    pub fn kept_cards(&self, player: TwoPlayers) -> Option<&KeptCards> {
+        self.kept[player].as_ref()
+    }
+
+    pub fn crib_part(&self, player: TwoPlayers) -> Option<&TwoPlayerCribPart> {
+        self.crib_parts[player].as_ref()
+    }
+
+    pub fn crib(&self) -> Option<&CribCards> {
+        self.crib.as_ref()
+    }
+
+    pub fn starter(&self) -> Option<Card> {
+        self.starter
+    }
+
+    pub fn pegger(&self) -> &Pegger {
+        self.round.pegger()
+    }
+
+    pub fn pegging_hand(&self, player: TwoPlayers) -> &[Card] {
+        self.round.hand(player)
+    }
+
+    pub fn pegging_round(&self) -> &PeggingRound {
+        &self.round
+    }
+
+    pub fn turn(&self) -> Option<TwoPlayers> {
+        match self.phase {
+            GamePhase::Pegging => Some(self.round.turn()),
+            _ => None,
+        }
+    }
+
+    pub fn events(&self) -> &[TableEvent] {
+        &self.events
+    }
+
+    pub fn deal_events(&self) -> &[TableEvent] {
+        &self.events[self.deal_start..]
+    }
+
+    pub fn legal_plays(&self, player: TwoPlayers) -> Vec<Card> {
+        if self.turn() != Some(player) {
+            return Vec::new();
+        }
+
+        self.round.legal_plays()
+    }
+
+    pub fn discard(
+        &mut self,
+        player: TwoPlayers,
+        crib: [Card; 2],
+    ) -> Result<(), CribbageCoreError> {
+        if self.phase != GamePhase::Discard {
+            return Err(CribbageCoreError::WrongPhase);
+        }
+
+        let deal = match &self.deals[player] {
+            Some(deal) => deal,
+            None => return Err(CribbageCoreError::OutOfTurn),
+        };
+
+        let crib_cards = CardSet::from_cards(&crib);
+        if crib_cards.len() != 2 || !crib_cards.is_subset(deal.card_set()) {
+            return Err(CribbageCoreError::InvalidCard);
+        }
+
+        let keep: Vec<Card> = deal
+            .cards()
+            .iter()
+            .copied()
+            .filter(|&card| !crib_cards.contains(card))
+            .collect();
+        let deal = self.deals[player]
+            .take()
+            .ok_or(CribbageCoreError::OutOfTurn)?;
+        let (kept, crib_part) = deal.split([keep[0], keep[1], keep[2], keep[3]], crib);
+        self.kept[player] = Some(kept);
+        self.crib_parts[player] = Some(crib_part);
+        self.events.push(TableEvent::Discarded { player });
+
+        if self.deals[player.other()].is_none() {
+            self.cut_starter()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn play(&mut self, player: TwoPlayers, card: Card) -> Result<u8, CribbageCoreError> {
+        if self.phase != GamePhase::Pegging {
+            return Err(CribbageCoreError::WrongPhase);
+        }
+
+        if self.round.turn() != player {
+            return Err(CribbageCoreError::OutOfTurn);
+        }
+
+        let outcome = self.round.play(card)?;
+        self.events.push(TableEvent::Played {
+            player,
+            card,
+            count: outcome.count,
+            points: outcome.points,
+        });
+
+        if self.award(player, outcome.points) {
+            return Ok(outcome.points);
+        }
+
+        if let Some(go) = outcome.go {
+            self.events.push(TableEvent::Go {
+                player: go,
+                count: outcome.count,
+            });
+        }
+
+        if outcome.sequence_ended {
+            self.events
+                .push(TableEvent::SequenceEnded { player, points: 1 });
+            if self.award(player, 1) {
+                return Ok(outcome.points);
+            }
+        }
+
+        if self.round.is_finished() {
+            self.phase = GamePhase::Show;
+        }
+
+        Ok(outcome.points)
+    }
+
+    pub fn show(&mut self) -> Result<(), CribbageCoreError> {
+        self.show_with_claims(|_, _, points| points)
+    }
+
+    pub fn show_with_claims<F>(&mut self, mut claim: F) -> Result<(), CribbageCoreError>
+    where
+        F: FnMut(TwoPlayers, &Hand, u8) -> u8,
+    {
+        if self.phase != GamePhase::Show {
+            return Err(CribbageCoreError::WrongPhase);
+        }
+
+        let starter = self.starter.ok_or(CribbageCoreError::WrongPhase)?;
+        let pone = self.pone();
+        let dealer = self.dealer;
+        let hands = vec![
+            (pone, self.kept[pone].take()),
+            (dealer, self.kept[dealer].take()),
+        ];
+        let mut shows: Vec<(TwoPlayers, Hand)> = Vec::with_capacity(3);
+        for (player, kept) in hands {
+            if let Some(kept) = kept {
+                shows.push((player, kept.add_cut_card(starter)));
+            }
+        }
+
+        if let Some(crib) = self.crib.take() {
+            shows.push((dealer, crib.add_cut_card(starter)));
+        }
+
+        for (player, mut hand) in shows {
+            let score = hand.score();
+            let points = claim(player, &hand, score).min(score);
+            self.events.push(TableEvent::Shown {
+                player,
+                cards: [
+                    hand.cards()[0],
+                    hand.cards()[1],
+                    hand.cards()[2],
+                    hand.cards()[3],
+                ],
+                is_crib: hand.is_crib(),
+                points,
+            });
+
+            if self.award(player, points) {
+                return Ok(());
+            }
+        }
+
+        self.dealer = self.dealer.other();
+        self.start_deal();
+        Ok(())
+    }
+
+    fn start_deal(&mut self) {
+        self.deck.shuffle_with_rng(&mut self.rng);
+        self.deal_start = self.events.len();
+        self.events.push(TableEvent::DealStarted {
+            dealer: self.dealer,
+        });
+
+        let pone = self.pone();
+        let dealer = self.dealer;
+        self.deals[pone] = deal_two_player_hand(&mut self.deck).ok();
+        self.deals[dealer] = deal_two_player_hand(&mut self.deck).ok();
+        self.kept = TwoPlayerScore::default();
+        self.crib_parts = TwoPlayerScore::default();
+        self.crib = None;
+        self.starter = None;
+        self.round = PeggingRound::new(TwoPlayerScore::default(), pone);
+        self.phase = GamePhase::Discard;
+    }
+
+    fn cut_starter(&mut self) -> Result<(), CribbageCoreError> {
+        let pone_part = self.crib_parts[self.pone()]
+            .clone()
+            .ok_or(CribbageCoreError::WrongPhase)?;
+        let dealer_part = self.crib_parts[self.dealer]
+            .clone()
+            .ok_or(CribbageCoreError::WrongPhase)?;
+        self.crib = Some(dealer_part.combine(pone_part));
+
+        let starter = self.deck.draw()?;
+        self.starter = Some(starter);
+        let heels = if starter.rank() == Rank::Jack { 2 } else { 0 };
+        self.events.push(TableEvent::StarterCut { starter, heels });
+        let hands = TwoPlayerScore::new([
+            self.pegging_cards(TwoPlayers::PlayerOne),
+            self.pegging_cards(TwoPlayers::PlayerTwo),
+        ]);
+        self.round = PeggingRound::new(hands, self.pone());
+        self.phase = GamePhase::Pegging;
+        self.award(self.dealer, heels);
+        Ok(())
+    }
+
+    fn pegging_cards(&self, player: TwoPlayers) -> Vec<Card> {
+        self.kept[player]
+            .as_ref()
+            .map(|kept| kept.cards().to_vec())
+            .unwrap_or_default()
+    }
+
+    fn award(&mut self, player: TwoPlayers, points: u8) -> bool {
+        if points > 0 && self.board.add_points(player, points).is_ok() {
+            if let Some(winner) = self.board.winner() {
+                self.phase = GamePhase::Finished;
+                self.events.push(TableEvent::GameWon { winner });
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{TwoPlayerScore, TwoPlayers};
+    use crate::card::Card;
+    use crate::game::{GamePhase, TableEvent, TwoPlayerGame};
+    use crate::pegging::PeggingRound;
+    use crate::CribbageCoreError;
+
+    fn discard_first_two(game: &mut TwoPlayerGame, player: TwoPlayers) {
+        let cards = game.deal(player).unwrap().cards().to_vec();
+        game.discard(player, [cards[0], cards[1]]).unwrap();
+    }
+
+    #[test]
+    fn test_seeded_games_are_deterministic() {
+        let game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 42);
+        let other = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 42);
+        assert_eq!(game.seed(), 42);
+        assert_eq!(
+            game.deal(TwoPlayers::PlayerOne).unwrap().cards(),
+            other.deal(TwoPlayers::PlayerOne).unwrap().cards()
+        );
+        assert_eq!(
+            game.deal(TwoPlayers::PlayerTwo).unwrap().cards(),
+            other.deal(TwoPlayers::PlayerTwo).unwrap().cards()
+        );
+    }
+
+    #[test]
+    fn test_discard() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 1);
+        assert_eq!(game.phase(), GamePhase::Discard);
+        assert_eq!(game.dealer(), TwoPlayers::PlayerOne);
+        assert_eq!(game.pone(), TwoPlayers::PlayerTwo);
+
+        let cards = game.deal(TwoPlayers::PlayerOne).unwrap().cards().to_vec();
+        let other_cards = game.deal(TwoPlayers::PlayerTwo).unwrap().cards().to_vec();
+        assert_eq!(
+            game.discard(TwoPlayers::PlayerOne, [cards[0], cards[0]]),
+            Err(CribbageCoreError::InvalidCard)
+        );
+        assert_eq!(
+            game.discard(TwoPlayers::PlayerOne, [cards[0], other_cards[0]]),
+            Err(CribbageCoreError::InvalidCard)
+        );
+        assert_eq!(
+            game.play(TwoPlayers::PlayerTwo, other_cards[0]),
+            Err(CribbageCoreError::WrongPhase)
+        );
+
+        game.discard(TwoPlayers::PlayerOne, [cards[0], cards[1]])
+            .unwrap();
+        assert_eq!(
+            game.discard(TwoPlayers::PlayerOne, [cards[2], cards[3]]),
+            Err(CribbageCoreError::OutOfTurn)
+        );
+        assert_eq!(
+            game.kept_cards(TwoPlayers::PlayerOne).unwrap().cards(),
+            &cards[2..]
+        );
+        assert!(game.starter().is_none());
+
+        discard_first_two(&mut game, TwoPlayers::PlayerTwo);
+        assert!(game.starter().is_some());
+        assert_eq!(game.crib().unwrap().cards().len(), 4);
+        if game.phase() != GamePhase::Finished {
+            assert_eq!(game.phase(), GamePhase::Pegging);
+            assert_eq!(game.turn(), Some(TwoPlayers::PlayerTwo));
+        }
+    }
+
+    #[test]
+    fn test_full_deal() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerTwo, 3);
+        discard_first_two(&mut game, TwoPlayers::PlayerOne);
+        discard_first_two(&mut game, TwoPlayers::PlayerTwo);
+
+        while game.phase() == GamePhase::Pegging {
+            let player = game.turn().unwrap();
+            if let Some(&card) = game.pegging_hand(player.other()).first() {
+                assert_eq!(
+                    game.play(player.other(), card),
+                    Err(CribbageCoreError::OutOfTurn)
+                );
+            }
+
+            let card = game.legal_plays(player)[0];
+            game.play(player, card).unwrap();
+        }
+
+        assert_eq!(game.phase(), GamePhase::Show);
+        let plays = game
+            .deal_events()
+            .iter()
+            .filter(|event| matches!(event, TableEvent::Played { .. }))
+            .count();
+        assert_eq!(plays, 8);
+
+        let pegged: u8 = game
+            .deal_events()
+            .iter()
+            .map(|event| match *event {
+                TableEvent::Played { points, .. } => points,
+                TableEvent::SequenceEnded { points, .. } => points,
+                TableEvent::StarterCut { heels, .. } => heels,
+                _ => 0,
+            })
+            .sum();
+        assert_eq!(
+            pegged,
+            game.score(TwoPlayers::PlayerOne) + game.score(TwoPlayers::PlayerTwo)
+        );
+
+        game.show().unwrap();
+        let shown: Vec<(TwoPlayers, bool)> = game
+            .events()
+            .iter()
+            .filter_map(|event| match *event {
+                TableEvent::Shown {
+                    player, is_crib, ..
+                } => Some((player, is_crib)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            shown,
+            vec![
+                (TwoPlayers::PlayerOne, false),
+                (TwoPlayers::PlayerTwo, false),
+                (TwoPlayers::PlayerTwo, true)
+            ]
+        );
+
+        assert_eq!(game.phase(), GamePhase::Discard);
+        assert_eq!(game.dealer(), TwoPlayers::PlayerOne);
+        assert_eq!(
+            game.deal_events(),
+            &[TableEvent::DealStarted {
+                dealer: TwoPlayers::PlayerOne
+            }]
+        );
+    }
+
+    #[test]
+    fn test_play_rejects_cards_not_in_hand() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 5);
+        discard_first_two(&mut game, TwoPlayers::PlayerOne);
+        discard_first_two(&mut game, TwoPlayers::PlayerTwo);
+        if game.phase() == GamePhase::Finished {
+            return;
+        }
+
+        let dealer_card: Card = game.pegging_hand(TwoPlayers::PlayerOne)[0];
+        assert_eq!(
+            game.play(TwoPlayers::PlayerTwo, dealer_card),
+            Err(CribbageCoreError::InvalidCard)
+        );
+        assert_eq!(game.pegging_hand(TwoPlayers::PlayerTwo).len(), 4);
+    }
+
+    #[test]
+    fn test_go_and_thirty_one() {
+        use std::str::FromStr;
+
+        let cards =
+            |s: &str| -> Vec<Card> { s.split(' ').map(|c| Card::from_str(c).unwrap()).collect() };
+
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 0);
+        let pone = TwoPlayers::PlayerTwo;
+        let dealer = TwoPlayers::PlayerOne;
+        game.phase = GamePhase::Pegging;
+        let mut hands = TwoPlayerScore::default();
+        hands[pone] = cards("KH QH 2C AC");
+        hands[dealer] = cards("KS QS 9D 3D");
+        game.round = PeggingRound::new(hands, pone);
+
+        assert_eq!(game.play(pone, cards("KH")[0]), Ok(0));
+        assert_eq!(game.play(dealer, cards("KS")[0]), Ok(2));
+        assert_eq!(game.play(pone, cards("QH")[0]), Ok(0));
+        assert_eq!(game.turn(), Some(pone));
+        assert_eq!(game.legal_plays(pone), cards("AC"));
+        assert_eq!(game.play(pone, cards("AC")[0]), Ok(1));
+        assert_eq!(game.pegger().count(), 0);
+        assert_eq!(game.turn(), Some(dealer));
+
+        assert_eq!(game.play(dealer, cards("QS")[0]), Ok(0));
+        assert_eq!(game.play(pone, cards("2C")[0]), Ok(0));
+        assert_eq!(game.play(dealer, cards("9D")[0]), Ok(0));
+        assert_eq!(game.turn(), Some(dealer));
+        assert_eq!(game.play(dealer, cards("3D")[0]), Ok(0));
+        assert_eq!(game.phase(), GamePhase::Show);
+
+        assert_eq!(game.score(pone), 2);
+        assert_eq!(game.score(dealer), 3);
+        let events: Vec<TableEvent> = game
+            .deal_events()
+            .iter()
+            .copied()
+            .filter(|event| {
+                matches!(
+                    event,
+                    TableEvent::Go { .. } | TableEvent::SequenceEnded { .. }
+                )
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                TableEvent::Go {
+                    player: dealer,
+                    count: 30
+                },
+                TableEvent::SequenceEnded {
+                    player: pone,
+                    points: 1
+                },
+                TableEvent::SequenceEnded {
+                    player: dealer,
+                    points: 1
+                },
+            ]
+        );
+    }
+}
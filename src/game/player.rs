@@ -0,0 +1,86 @@
+use crate::board::TwoPlayers;
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::game::{TableEvent, TwoPlayerGame};
+use crate::hand::{FourPlayerDeal, Hand, ThreePlayerDeal, TwoPlayerDeal};
+use crate::pegging::Pegger;
+use crate::CribbageCoreError;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Situation<'a> {
+    pub player: TwoPlayers,
+    pub dealer: TwoPlayers,
+    pub own_score: u8,
+    pub opponent_score: u8,
+    pub target: u8,
+    pub starter: Option<Card>,
+    // The player's own discards this deal, empty until they have discarded
+    pub crib_part: CardSet,
+    pub deal_events: &'a [TableEvent],
+}
+
+impl<'a> Situation<'a> {
+    pub fn new(game: &'a TwoPlayerGame, player: TwoPlayers) -> Situation<'a> {
+        Situation {
+            player,
+            dealer: game.dealer(),
+            own_score: game.score(player),
+            opponent_score: game.score(player.other()),
+            target: game.board().target(),
+            starter: game.starter(),
+            crib_part: game
+                .crib_part(player)
+                .map_or_else(CardSet::new, |part| part.card_set()),
+            deal_events: game.deal_events(),
+        }
+    }
+
+    pub fn is_dealer(&self) -> bool {
+        self.player == self.dealer
+    }
+}
+
+pub trait Player {
+    fn choose_discard(&mut self, deal: &TwoPlayerDeal, situation: &Situation<'_>) -> [Card; 2];
+
+    // Only two-player games are driven so far; players that can't judge a five-card deal may
+    // keep these defaults, which throw the first card
+    fn choose_three_player_discard(
+        &mut self,
+        deal: &ThreePlayerDeal,
+        _situation: &Situation<'_>,
+    ) -> Card {
+        deal.cards()[0]
+    }
+
+    fn choose_four_player_discard(
+        &mut self,
+        deal: &FourPlayerDeal,
+        _situation: &Situation<'_>,
+    ) -> Card {
+        deal.cards()[0]
+    }
+
+    fn choose_peg(
+        &mut self,
+        pegger: &Pegger,
+        hand: &[Card],
+        legal: &[Card],
+        situation: &Situation<'_>,
+    ) -> Card;
+
+    fn claim_score(&mut self, _hand: &Hand, score: u8, _situation: &Situation<'_>) -> u8 {
+        score
+    }
+
+    fn observe(&mut self, _event: &TableEvent, _situation: &Situation<'_>) {}
+
+    // Called when a discard or play breaks the rules; the driver then makes the first legal
+    // choice for the player instead of ending the game
+    fn choice_rejected(&mut self, _error: CribbageCoreError, _situation: &Situation<'_>) {}
+
+    // Checked after every choice; a player that has left the table ends the game
+    fn has_quit(&self) -> bool {
+        false
+    }
+}
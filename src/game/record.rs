@@ -0,0 +1,599 @@
+use std::error;
+use std::fmt::{self, Display, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::board::{TwoPlayerScore, TwoPlayers};
+use crate::card::Card;
+use crate::game::driver::{drive, GameTable};
+use crate::game::{GamePhase, Player, TableEvent, TwoPlayerGame};
+use crate::hand::Hand;
+use crate::CribbageCoreError;
+
+// Everything that happened in a game, including the hidden cards `TableEvent`s leave out, so the
+// game can be rebuilt and audited. `Scored` follows every event that moved a peg
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum GameLogEvent {
+    Started {
+        seed: u64,
+        first_dealer: TwoPlayers,
+        target: u8,
+    },
+    DealStarted {
+        dealer: TwoPlayers,
+    },
+    Dealt {
+        player: TwoPlayers,
+        cards: [Card; 6],
+    },
+    Discarded {
+        player: TwoPlayers,
+        cards: [Card; 2],
+    },
+    StarterCut {
+        starter: Card,
+        heels: u8,
+    },
+    Played {
+        player: TwoPlayers,
+        card: Card,
+        count: u8,
+        points: u8,
+    },
+    Go {
+        player: TwoPlayers,
+        count: u8,
+    },
+    SequenceEnded {
+        player: TwoPlayers,
+        points: u8,
+    },
+    Shown {
+        player: TwoPlayers,
+        cards: [Card; 4],
+        is_crib: bool,
+        score: u8,
+        points: u8,
+    },
+    Scored {
+        player: TwoPlayers,
+        points: u8,
+        total: u8,
+    },
+    GameWon {
+        winner: TwoPlayers,
+    },
+}
+
+// Turns the game's new `TableEvent`s into log events, filling in what only the game knows
+#[derive(Clone, Debug)]
+struct Translator {
+    observed: usize,
+    totals: TwoPlayerScore<u8>,
+}
+
+impl Translator {
+    fn new() -> Translator {
+        Translator {
+            observed: 0,
+            totals: TwoPlayerScore::default(),
+        }
+    }
+
+    fn translate(&mut self, game: &TwoPlayerGame, crib: Option<[Card; 2]>) -> Vec<GameLogEvent> {
+        let mut log = Vec::new();
+        for event in &game.events()[self.observed..] {
+            let scored = match *event {
+                TableEvent::DealStarted { dealer } => {
+                    log.push(GameLogEvent::DealStarted { dealer });
+                    for &player in &[dealer.other(), dealer] {
+                        if let Some(deal) = game.deal(player) {
+                            let mut cards = [deal.cards()[0]; 6];
+                            cards.copy_from_slice(deal.cards());
+                            log.push(GameLogEvent::Dealt { player, cards });
+                        }
+                    }
+
+                    None
+                }
+                TableEvent::Discarded { player } => {
+                    if let Some(cards) = crib {
+                        log.push(GameLogEvent::Discarded { player, cards });
+                    }
+
+                    None
+                }
+                TableEvent::StarterCut { starter, heels } => {
+                    log.push(GameLogEvent::StarterCut { starter, heels });
+                    Some((game.dealer(), heels))
+                }
+                TableEvent::Played {
+                    player,
+                    card,
+                    count,
+                    points,
+                } => {
+                    log.push(GameLogEvent::Played {
+                        player,
+                        card,
+                        count,
+                        points,
+                    });
+                    Some((player, points))
+                }
+                TableEvent::Go { player, count } => {
+                    log.push(GameLogEvent::Go { player, count });
+                    None
+                }
+                TableEvent::SequenceEnded { player, points } => {
+                    log.push(GameLogEvent::SequenceEnded { player, points });
+                    Some((player, points))
+                }
+                TableEvent::Shown {
+                    player,
+                    cards,
+                    is_crib,
+                    points,
+                } => {
+                    let score = last_starter(game)
+                        .map(|starter| Hand::new(cards, starter, is_crib).score())
+                        .unwrap_or(points);
+                    log.push(GameLogEvent::Shown {
+                        player,
+                        cards,
+                        is_crib,
+                        score,
+                        points,
+                    });
+                    Some((player, points))
+                }
+                TableEvent::GameWon { winner } => {
+                    log.push(GameLogEvent::GameWon { winner });
+                    None
+                }
+            };
+
+            if let Some((player, points)) = scored.filter(|&(_, points)| points > 0) {
+                let target = game.board().target();
+                self.totals[player] = self.totals[player].saturating_add(points).min(target);
+                log.push(GameLogEvent::Scored {
+                    player,
+                    points,
+                    total: self.totals[player],
+                });
+            }
+        }
+
+        self.observed = game.events().len();
+        log
+    }
+}
+
+// By the time a show is translated the next deal may have started, so the starter comes from the
+// events
+fn last_starter(game: &TwoPlayerGame) -> Option<Card> {
+    game.events().iter().rev().find_map(|event| match *event {
+        TableEvent::StarterCut { starter, .. } => Some(starter),
+        _ => None,
+    })
+}
+
+// A game that logs every action taken through it
+#[derive(Clone, Debug)]
+pub struct GameRecorder {
+    game: TwoPlayerGame,
+    translator: Translator,
+    log: Vec<GameLogEvent>,
+}
+
+impl GameRecorder {
+    pub fn with_seed(first_dealer: TwoPlayers, seed: u64) -> GameRecorder {
+        let game = TwoPlayerGame::with_seed(first_dealer, seed);
+        let mut log = vec![GameLogEvent::Started {
+            seed,
+            first_dealer,
+            target: game.board().target(),
+        }];
+        let mut translator = Translator::new();
+        log.extend(translator.translate(&game, None));
+        GameRecorder {
+            game,
+            translator,
+            log,
+        }
+    }
+
+    pub fn game(&self) -> &TwoPlayerGame {
+        &self.game
+    }
+
+    pub fn log(&self) -> &[GameLogEvent] {
+        &self.log
+    }
+
+    pub fn into_log(self) -> Vec<GameLogEvent> {
+        self.log
+    }
+
+    pub fn discard(
+        &mut self,
+        player: TwoPlayers,
+        crib: [Card; 2],
+    ) -> Result<(), CribbageCoreError> {
+        self.game.discard(player, crib)?;
+        self.record(Some(crib));
+        Ok(())
+    }
+
+    pub fn play(&mut self, player: TwoPlayers, card: Card) -> Result<u8, CribbageCoreError> {
+        let points = self.game.play(player, card)?;
+        self.record(None);
+        Ok(points)
+    }
+
+    pub fn show(&mut self) -> Result<(), CribbageCoreError> {
+        self.show_with_claims(|_, _, points| points)
+    }
+
+    pub fn show_with_claims<F>(&mut self, claim: F) -> Result<(), CribbageCoreError>
+    where
+        F: FnMut(TwoPlayers, &Hand, u8) -> u8,
+    {
+        self.game.show_with_claims(claim)?;
+        self.record(None);
+        Ok(())
+    }
+
+    fn record(&mut self, crib: Option<[Card; 2]>) {
+        let events = self.translator.translate(&self.game, crib);
+        self.log.extend(events);
+    }
+}
+
+impl GameTable for GameRecorder {
+    fn game(&self) -> &TwoPlayerGame {
+        &self.game
+    }
+
+    fn discard(&mut self, player: TwoPlayers, crib: [Card; 2]) -> Result<(), CribbageCoreError> {
+        GameRecorder::discard(self, player, crib)
+    }
+
+    fn play(&mut self, player: TwoPlayers, card: Card) -> Result<u8, CribbageCoreError> {
+        GameRecorder::play(self, player, card)
+    }
+
+    fn show_with_claims<F>(&mut self, claim: F) -> Result<(), CribbageCoreError>
+    where
+        F: FnMut(TwoPlayers, &Hand, u8) -> u8,
+    {
+        GameRecorder::show_with_claims(self, claim)
+    }
+}
+
+pub fn play_recorded_game(
+    recorder: &mut GameRecorder,
+    player_one: &mut dyn Player,
+    player_two: &mut dyn Player,
+) -> Result<TwoPlayers, CribbageCoreError> {
+    drive(recorder, player_one, player_two)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ReplayError {
+    // The log doesn't begin with `Started`
+    NotStarted,
+    // The game refused the action at this index
+    Rejected {
+        index: usize,
+        error: CribbageCoreError,
+    },
+    // The event at this index isn't what the game produced
+    Mismatch {
+        index: usize,
+    },
+    // The points recorded at this index aren't what the cards score
+    WrongScore {
+        index: usize,
+    },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::NotStarted => write!(f, "the log does not start a game"),
+            ReplayError::Rejected { index, error } => {
+                write!(f, "event {} was rejected: {}", index, error)
+            }
+            ReplayError::Mismatch { index } => write!(f, "event {} does not match the game", index),
+            ReplayError::WrongScore { index } => write!(f, "event {} has the wrong score", index),
+        }
+    }
+}
+
+impl error::Error for ReplayError {}
+
+// Rebuilds a game from its log one action at a time, checking each recorded event against the
+// game and each recorded score against `Pegger::play_card` and `Hand::score`
+#[derive(Clone, Debug)]
+pub struct Replayer<'a> {
+    log: &'a [GameLogEvent],
+    next: usize,
+    game: TwoPlayerGame,
+    translator: Translator,
+}
+
+impl<'a> Replayer<'a> {
+    pub fn new(log: &'a [GameLogEvent]) -> Result<Replayer<'a>, ReplayError> {
+        let (seed, first_dealer, target) = match log.first() {
+            Some(&GameLogEvent::Started {
+                seed,
+                first_dealer,
+                target,
+            }) => (seed, first_dealer, target),
+            _ => return Err(ReplayError::NotStarted),
+        };
+
+        let game = TwoPlayerGame::with_seed(first_dealer, seed);
+        if game.board().target() != target {
+            return Err(ReplayError::Mismatch { index: 0 });
+        }
+
+        let mut replayer = Replayer {
+            log,
+            next: 1,
+            game,
+            translator: Translator::new(),
+        };
+        replayer.check(None)?;
+        Ok(replayer)
+    }
+
+    pub fn game(&self) -> &TwoPlayerGame {
+        &self.game
+    }
+
+    // The index of the next event to replay
+    pub fn position(&self) -> usize {
+        self.next
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next >= self.log.len()
+    }
+
+    // Replays the next action and everything it caused
+    pub fn step(&mut self) -> Result<(), ReplayError> {
+        let index = self.next;
+        let rejected = |error| ReplayError::Rejected { index, error };
+        match self.log.get(index) {
+            Some(&GameLogEvent::Discarded { player, cards }) => {
+                self.game.discard(player, cards).map_err(rejected)?;
+                self.check(Some(cards))
+            }
+            Some(&GameLogEvent::Played {
+                player,
+                card,
+                points,
+                ..
+            }) => {
+                let mut pegger = self.game.pegger().clone();
+                if pegger.play_card(card).ok() != Some(points) {
+                    return Err(ReplayError::WrongScore { index });
+                }
+
+                self.game.play(player, card).map_err(rejected)?;
+                self.check(None)
+            }
+            Some(GameLogEvent::Shown { .. }) => {
+                let claims = self.show_claims()?;
+                self.game
+                    .show_with_claims(|player, hand, _| {
+                        claims
+                            .iter()
+                            .find(|&&(shown_by, is_crib, _)| {
+                                shown_by == player && is_crib == hand.is_crib()
+                            })
+                            .map_or(0, |&(_, _, points)| points)
+                    })
+                    .map_err(rejected)?;
+                self.check(None)
+            }
+            _ => Err(ReplayError::Mismatch { index }),
+        }
+    }
+
+    pub fn replay_to_end(mut self) -> Result<TwoPlayerGame, ReplayError> {
+        while !self.is_finished() {
+            self.step()?;
+        }
+
+        Ok(self.game)
+    }
+
+    // The points claimed in the show starting at the next event, after checking each hand's score
+    fn show_claims(&self) -> Result<Vec<(TwoPlayers, bool, u8)>, ReplayError> {
+        let starter = self
+            .game
+            .starter()
+            .ok_or(ReplayError::Mismatch { index: self.next })?;
+        let mut claims = Vec::new();
+        for (index, event) in self.log.iter().enumerate().skip(self.next) {
+            match *event {
+                GameLogEvent::Shown {
+                    player,
+                    cards,
+                    is_crib,
+                    score,
+                    points,
+                } => {
+                    if Hand::new(cards, starter, is_crib).score() != score || points > score {
+                        return Err(ReplayError::WrongScore { index });
+                    }
+
+                    claims.push((player, is_crib, points));
+                }
+                GameLogEvent::Scored { .. } => {}
+                _ => break,
+            }
+        }
+
+        Ok(claims)
+    }
+
+    // Compares what the game just did with the log
+    fn check(&mut self, crib: Option<[Card; 2]>) -> Result<(), ReplayError> {
+        let expected = self.translator.translate(&self.game, crib);
+        for (offset, event) in expected.iter().enumerate() {
+            let index = self.next + offset;
+            if self.log.get(index) != Some(event) {
+                return Err(match (self.log.get(index), event) {
+                    (Some(GameLogEvent::Scored { .. }), GameLogEvent::Scored { .. })
+                    | (Some(GameLogEvent::Shown { .. }), GameLogEvent::Shown { .. }) => {
+                        ReplayError::WrongScore { index }
+                    }
+                    _ => ReplayError::Mismatch { index },
+                });
+            }
+        }
+
+        self.next += expected.len();
+        if self.game.phase() == GamePhase::Finished && !self.is_finished() {
+            return Err(ReplayError::Mismatch { index: self.next });
+        }
+
+        Ok(())
+    }
+}
+
+pub fn replay(log: &[GameLogEvent]) -> Result<TwoPlayerGame, ReplayError> {
+    Replayer::new(log)?.replay_to_end()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::{GreedyBot, RandomBot};
+    use crate::game::{
+        play_recorded_game, play_two_player_game, replay, GameLogEvent, GameRecorder, ReplayError,
+        Replayer, TwoPlayerGame,
+    };
+
+    fn recorded_game(seed: u64) -> Vec<GameLogEvent> {
+        let mut recorder = GameRecorder::with_seed(TwoPlayers::PlayerOne, seed);
+        play_recorded_game(
+            &mut recorder,
+            &mut GreedyBot::new(),
+            &mut RandomBot::new(seed),
+        )
+        .unwrap();
+        recorder.into_log()
+    }
+
+    #[test]
+    fn test_replay_matches_game() {
+        let log = recorded_game(7);
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 7);
+        let winner =
+            play_two_player_game(&mut game, &mut GreedyBot::new(), &mut RandomBot::new(7)).unwrap();
+
+        assert_eq!(log.last(), Some(&GameLogEvent::GameWon { winner }));
+        let total = log.iter().rev().find_map(|event| match *event {
+            GameLogEvent::Scored { player, total, .. } => Some((player, total)),
+            _ => None,
+        });
+        assert_eq!(total, Some((winner, 121)));
+
+        let replayed = replay(&log).unwrap();
+        assert_eq!(replayed.events(), game.events());
+
+        let mut replayer = Replayer::new(&log).unwrap();
+        let mut steps = 0;
+        while !replayer.is_finished() {
+            let position = replayer.position();
+            replayer.step().unwrap();
+            assert!(replayer.position() > position);
+            steps += 1;
+        }
+
+        assert!(steps > 20);
+        assert_eq!(replayer.game().winner(), Some(winner));
+    }
+
+    #[test]
+    fn test_replay_checks_log() {
+        let log = recorded_game(3);
+        assert_eq!(replay(&log[1..]).unwrap_err(), ReplayError::NotStarted);
+
+        let tamper = |find: &dyn Fn(&GameLogEvent) -> bool, change: &dyn Fn(&mut GameLogEvent)| {
+            let mut tampered = log.clone();
+            let index = tampered.iter().position(find).unwrap();
+            change(&mut tampered[index]);
+            (index, replay(&tampered).unwrap_err())
+        };
+
+        let (index, error) = tamper(
+            &|event| matches!(event, GameLogEvent::Played { .. }),
+            &|event| {
+                if let GameLogEvent::Played { points, .. } = event {
+                    *points += 2;
+                }
+            },
+        );
+        assert_eq!(error, ReplayError::WrongScore { index });
+
+        let (index, error) = tamper(
+            &|event| matches!(event, GameLogEvent::Shown { .. }),
+            &|event| {
+                if let GameLogEvent::Shown { score, .. } = event {
+                    *score += 1;
+                }
+            },
+        );
+        assert_eq!(error, ReplayError::WrongScore { index });
+
+        let (index, error) = tamper(
+            &|event| matches!(event, GameLogEvent::Dealt { .. }),
+            &|event| {
+                if let GameLogEvent::Dealt { cards, .. } = event {
+                    cards.swap(0, 1);
+                }
+            },
+        );
+        assert_eq!(error, ReplayError::Mismatch { index });
+
+        let (index, error) = tamper(
+            &|event| matches!(event, GameLogEvent::Discarded { .. }),
+            &|event| {
+                if let GameLogEvent::Discarded { player, .. } = event {
+                    *player = player.other();
+                }
+            },
+        );
+        assert!(matches!(error, ReplayError::Rejected { index: i, .. } if i == index));
+
+        // Claiming fewer points than a hand is worth is allowed
+        let mut recorder = GameRecorder::with_seed(TwoPlayers::PlayerTwo, 5);
+        while recorder.game().winner().is_none() {
+            let game = recorder.game();
+            if let Some(player) = game.turn() {
+                let card = game.legal_plays(player)[0];
+                recorder.play(player, card).unwrap();
+            } else if let Some(deal) = game.deal(game.pone()) {
+                let crib = [deal.cards()[0], deal.cards()[1]];
+                recorder.discard(game.pone(), crib).unwrap();
+            } else if let Some(deal) = game.deal(game.dealer()) {
+                let crib = [deal.cards()[0], deal.cards()[1]];
+                recorder.discard(game.dealer(), crib).unwrap();
+            } else {
+                recorder
+                    .show_with_claims(|_, _, points| points.saturating_sub(1))
+                    .unwrap();
+            }
+        }
+
+        assert!(replay(recorder.log()).is_ok());
+    }
+}
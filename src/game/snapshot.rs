@@ -0,0 +1,503 @@
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::slice;
+use std::str::FromStr;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::board::{custom_board, TwoPlayerScore, TwoPlayers};
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::deck::Deck;
+use crate::game::{GamePhase, TableEvent, TwoPlayerGame};
+use crate::hand::{CribCards, KeptCards, TwoPlayerCribPart, TwoPlayerDeal};
+use crate::pegging::{Pegger, PeggingRound};
+use crate::text::{format_cards, format_event, parse_cards, parse_event, parse_seat, seat};
+
+const HEADER: &str = "cribbage-snapshot";
+const VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum GameSnapshotError {
+    Io(io::Error),
+    Parse { line: usize },
+}
+
+impl Display for GameSnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            GameSnapshotError::Io(error) => write!(f, "{}", error),
+            GameSnapshotError::Parse { line } => write!(f, "invalid snapshot at line {}", line),
+        }
+    }
+}
+
+impl error::Error for GameSnapshotError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            GameSnapshotError::Io(error) => Some(error),
+            GameSnapshotError::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for GameSnapshotError {
+    fn from(error: io::Error) -> GameSnapshotError {
+        GameSnapshotError::Io(error)
+    }
+}
+
+// Snapshots are text, one field per line in a fixed order, followed by the game's events:
+//
+//   cribbage-snapshot 1
+//   seed 42
+//   dealer 2
+//   phase pegging
+//   board 121 17 9
+//   deck <52 cards>
+//   drawn 13
+//   deal 1 -                     (the six dealt cards, until the player discards)
+//   deal 2 -
+//   kept 1 5H 5C JD QS           (until the show)
+//   kept 2 AS 2S 3S 4S
+//   crib_part 1 TD 9H
+//   crib_part 2 2C 7S
+//   crib 7S 2C TD 9H             (from the cut until the show)
+//   starter 5S
+//   turn 1
+//   said_go 0 0
+//   pegging 1 5C JD QS           (cards not yet pegged)
+//   pegging 2 AS 2S 3S 4S
+//   pegger 5 5H
+//   event deal 2                 (one line per event, as in the engine protocol)
+//
+// The random number generator isn't stored: it is reseeded and advanced by one shuffle per deal
+impl TwoPlayerGame {
+    pub fn write_snapshot<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{} {}", HEADER, VERSION)?;
+        writeln!(writer, "seed {}", self.seed)?;
+        writeln!(writer, "dealer {}", seat(self.dealer))?;
+        writeln!(writer, "phase {}", phase_name(self.phase))?;
+        writeln!(
+            writer,
+            "board {} {} {}",
+            self.board.target(),
+            self.score(TwoPlayers::PlayerOne),
+            self.score(TwoPlayers::PlayerTwo)
+        )?;
+        writeln!(writer, "deck {}", format_cards(self.deck.cards()))?;
+        writeln!(writer, "drawn {}", self.deck.cards_drawn())?;
+        for &player in &PLAYERS {
+            let deal = self.deals[player].as_ref().map(TwoPlayerDeal::cards);
+            writeln!(writer, "deal {} {}", seat(player), optional_cards(deal))?;
+        }
+
+        for &player in &PLAYERS {
+            let kept = self.kept[player].as_ref().map(KeptCards::cards);
+            writeln!(writer, "kept {} {}", seat(player), optional_cards(kept))?;
+        }
+
+        for &player in &PLAYERS {
+            let part = self.crib_parts[player]
+                .as_ref()
+                .map(TwoPlayerCribPart::cards);
+            writeln!(
+                writer,
+                "crib_part {} {}",
+                seat(player),
+                optional_cards(part)
+            )?;
+        }
+
+        let crib = self.crib.as_ref().map(CribCards::cards);
+        writeln!(writer, "crib {}", optional_cards(crib))?;
+        let starter = self.starter.as_ref().map(slice::from_ref);
+        writeln!(writer, "starter {}", optional_cards(starter))?;
+        writeln!(writer, "turn {}", seat(self.round.turn()))?;
+        writeln!(
+            writer,
+            "said_go {} {}",
+            u8::from(self.round.said_go(TwoPlayers::PlayerOne)),
+            u8::from(self.round.said_go(TwoPlayers::PlayerTwo))
+        )?;
+        for &player in &PLAYERS {
+            let line = format!(
+                "pegging {} {}",
+                seat(player),
+                format_cards(self.round.hand(player))
+            );
+            writeln!(writer, "{}", line.trim_end())?;
+        }
+
+        let pegger = self.round.pegger();
+        let line = format!(
+            "pegger {} {}",
+            pegger.count(),
+            format_cards(pegger.played_cards())
+        );
+        writeln!(writer, "{}", line.trim_end())?;
+        for event in &self.events {
+            writeln!(writer, "event {}", format_event(event))?;
+        }
+
+        writer.flush()
+    }
+
+    pub fn save_snapshot<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_snapshot(File::create(path)?)
+    }
+
+    pub fn from_snapshot<R: BufRead>(reader: R) -> Result<TwoPlayerGame, GameSnapshotError> {
+        let lines = reader.lines().collect::<io::Result<Vec<String>>>()?;
+        let mut lines = Lines {
+            lines: &lines,
+            next: 0,
+            dealt: CardSet::new(),
+        };
+
+        match *lines.next(HEADER)?.as_slice() {
+            [version] if version.parse() == Ok(VERSION) => {}
+            _ => return Err(lines.error()),
+        }
+
+        let seed = lines.value("seed")?;
+        let dealer = lines.seat("dealer")?;
+        let phase = match *lines.next("phase")?.as_slice() {
+            ["discard"] => GamePhase::Discard,
+            ["pegging"] => GamePhase::Pegging,
+            ["show"] => GamePhase::Show,
+            ["finished"] => GamePhase::Finished,
+            _ => return Err(lines.error()),
+        };
+
+        let (target, scores) = match *lines.next("board")?.as_slice() {
+            [target, one, two] => match (target.parse(), one.parse(), two.parse()) {
+                (Ok(target), Ok(one), Ok(two)) => (target, [one, two]),
+                _ => return Err(lines.error()),
+            },
+            _ => return Err(lines.error()),
+        };
+        let mut board = custom_board(TwoPlayerScore::new([0, 0]), target);
+        for (&player, &points) in PLAYERS.iter().zip(&scores) {
+            if points > target || board.add_points(player, points).is_err() {
+                return Err(lines.error());
+            }
+        }
+
+        let order = lines.next("deck")?;
+        let order = parse_cards(&order).ok_or_else(|| lines.error())?;
+        let deck = Deck::from_order(&order, lines.value("drawn")?).map_err(|_| lines.error())?;
+        lines.dealt = CardSet::from_cards(&order[..deck.cards_drawn()]);
+
+        let mut deals = TwoPlayerScore::<Option<TwoPlayerDeal>>::default();
+        for &player in &PLAYERS {
+            deals[player] = lines.cards("deal", Some(player))?.map(TwoPlayerDeal::new);
+        }
+
+        let mut kept = TwoPlayerScore::<Option<KeptCards>>::default();
+        for &player in &PLAYERS {
+            kept[player] = lines.cards("kept", Some(player))?.map(KeptCards::new);
+        }
+
+        let mut crib_parts = TwoPlayerScore::<Option<TwoPlayerCribPart>>::default();
+        for &player in &PLAYERS {
+            crib_parts[player] = lines
+                .cards("crib_part", Some(player))?
+                .map(TwoPlayerCribPart::new);
+        }
+
+        let crib = lines.cards("crib", None)?.map(CribCards::new);
+        let starter = lines.cards::<1>("starter", None)?.map(|[card]| card);
+        let turn = lines.seat("turn")?;
+        let said_go = match *lines.next("said_go")?.as_slice() {
+            [one, two] => match (flag(one), flag(two)) {
+                (Some(one), Some(two)) => TwoPlayerScore::new([one, two]),
+                _ => return Err(lines.error()),
+            },
+            _ => return Err(lines.error()),
+        };
+
+        let mut hands = TwoPlayerScore::<Vec<Card>>::default();
+        for &player in &PLAYERS {
+            hands[player] = lines.card_list("pegging", Some(player))?;
+        }
+
+        let (count, played) = match lines.next("pegger")?.split_first() {
+            Some((count, played)) => (count.parse::<u8>().ok(), parse_cards(played)),
+            None => (None, None),
+        };
+        let mut pegger = Pegger::new();
+        for &card in played.iter().flatten() {
+            pegger.play_card(card).map_err(|_| lines.error())?;
+        }
+
+        if played.is_none() || count != Some(pegger.count()) {
+            return Err(lines.error());
+        }
+
+        let mut events = Vec::new();
+        while lines.next < lines.lines.len() {
+            let words = lines.next("event")?;
+            events.push(parse_event(&words).ok_or_else(|| lines.error())?);
+        }
+
+        let deal_start = events
+            .iter()
+            .rposition(|event| matches!(event, TableEvent::DealStarted { .. }))
+            .ok_or_else(|| lines.error())?;
+
+        // Every deal so far began with one shuffle
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut scratch = Deck::new();
+        for _ in events
+            .iter()
+            .filter(|event| matches!(event, TableEvent::DealStarted { .. }))
+        {
+            scratch.shuffle_with_rng(&mut rng);
+        }
+
+        Ok(TwoPlayerGame {
+            seed,
+            rng,
+            deck,
+            board,
+            dealer,
+            phase,
+            deals,
+            kept,
+            crib_parts,
+            crib,
+            starter,
+            round: PeggingRound::resume(pegger, hands, turn, said_go),
+            events,
+            deal_start,
+        })
+    }
+
+    pub fn load_snapshot<P: AsRef<Path>>(path: P) -> Result<TwoPlayerGame, GameSnapshotError> {
+        TwoPlayerGame::from_snapshot(BufReader::new(File::open(path)?))
+    }
+}
+
+const PLAYERS: [TwoPlayers; 2] = [TwoPlayers::PlayerOne, TwoPlayers::PlayerTwo];
+
+struct Lines<'a> {
+    lines: &'a [String],
+    next: usize,
+    dealt: CardSet,
+}
+
+impl<'a> Lines<'a> {
+    // The line just read, counting from one
+    fn error(&self) -> GameSnapshotError {
+        GameSnapshotError::Parse { line: self.next }
+    }
+
+    fn next(&mut self, keyword: &str) -> Result<Vec<&'a str>, GameSnapshotError> {
+        let line = self.lines.get(self.next);
+        self.next += 1;
+        let mut words = line
+            .map(|line| line.split_whitespace().collect::<Vec<&str>>())
+            .unwrap_or_default();
+        if words.first() != Some(&keyword) {
+            return Err(self.error());
+        }
+
+        words.remove(0);
+        Ok(words)
+    }
+
+    fn value<T: FromStr>(&mut self, keyword: &str) -> Result<T, GameSnapshotError> {
+        match *self.next(keyword)?.as_slice() {
+            [value] => value.parse().map_err(|_| self.error()),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn seat(&mut self, keyword: &str) -> Result<TwoPlayers, GameSnapshotError> {
+        match *self.next(keyword)?.as_slice() {
+            [player] => parse_seat(player).ok_or_else(|| self.error()),
+            _ => Err(self.error()),
+        }
+    }
+
+    fn seat_words(
+        &mut self,
+        keyword: &str,
+        player: Option<TwoPlayers>,
+    ) -> Result<Vec<&'a str>, GameSnapshotError> {
+        let mut words = self.next(keyword)?;
+        if let Some(player) = player {
+            if words.first() != Some(&seat(player)) {
+                return Err(self.error());
+            }
+
+            words.remove(0);
+        }
+
+        Ok(words)
+    }
+
+    // Cards that must all have been dealt from the deck, after the seat they belong to if any
+    fn card_list(
+        &mut self,
+        keyword: &str,
+        player: Option<TwoPlayers>,
+    ) -> Result<Vec<Card>, GameSnapshotError> {
+        let words = self.seat_words(keyword, player)?;
+        self.dealt_cards(&words)
+    }
+
+    // Exactly `N` different cards, or "-"
+    fn cards<const N: usize>(
+        &mut self,
+        keyword: &str,
+        player: Option<TwoPlayers>,
+    ) -> Result<Option<[Card; N]>, GameSnapshotError> {
+        let words = self.seat_words(keyword, player)?;
+        if words == ["-"] {
+            return Ok(None);
+        }
+
+        let cards = self.dealt_cards(&words)?;
+        if CardSet::from_cards(&cards).len() != N {
+            return Err(self.error());
+        }
+
+        let mut array = [cards[0]; N];
+        array.copy_from_slice(&cards);
+        Ok(Some(array))
+    }
+
+    fn dealt_cards(&self, words: &[&str]) -> Result<Vec<Card>, GameSnapshotError> {
+        match parse_cards(words) {
+            Some(cards) if CardSet::from_cards(&cards).is_subset(self.dealt) => Ok(cards),
+            _ => Err(self.error()),
+        }
+    }
+}
+
+fn flag(s: &str) -> Option<bool> {
+    match s {
+        "0" => Some(false),
+        "1" => Some(true),
+        _ => None,
+    }
+}
+
+fn phase_name(phase: GamePhase) -> &'static str {
+    match phase {
+        GamePhase::Discard => "discard",
+        GamePhase::Pegging => "pegging",
+        GamePhase::Show => "show",
+        GamePhase::Finished => "finished",
+    }
+}
+
+fn optional_cards(cards: Option<&[Card]>) -> String {
+    match cards {
+        Some(cards) => format_cards(cards),
+        None => "-".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::game::{GamePhase, GameSnapshotError, TwoPlayerGame};
+    use std::io::Cursor;
+
+    fn step(game: &mut TwoPlayerGame) {
+        match game.phase() {
+            GamePhase::Discard => {
+                let player = match game.deal(game.pone()) {
+                    Some(_) => game.pone(),
+                    None => game.dealer(),
+                };
+                let cards = game.deal(player).unwrap().cards();
+                let crib = [cards[1], cards[4]];
+                game.discard(player, crib).unwrap();
+            }
+            GamePhase::Pegging => {
+                let player = game.turn().unwrap();
+                let legal = game.legal_plays(player);
+                game.play(player, legal[legal.len() - 1]).unwrap();
+            }
+            GamePhase::Show => game.show().unwrap(),
+            GamePhase::Finished => {}
+        }
+    }
+
+    fn snapshot(game: &TwoPlayerGame) -> String {
+        let mut buffer = Vec::new();
+        game.write_snapshot(&mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_snapshot_resumes_game() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerTwo, 12);
+        let mut steps = 0;
+        while game.phase() != GamePhase::Finished {
+            let text = snapshot(&game);
+            let restored = TwoPlayerGame::from_snapshot(Cursor::new(text.as_bytes())).unwrap();
+            assert_eq!(snapshot(&restored), text);
+            assert_eq!(restored.events(), game.events());
+            assert_eq!(restored.deal_events(), game.deal_events());
+
+            if steps % 20 == 0 {
+                let mut original = game.clone();
+                let mut restored = restored;
+                while original.phase() != GamePhase::Finished {
+                    step(&mut original);
+                    step(&mut restored);
+                }
+
+                assert_eq!(restored.events(), original.events());
+                assert_eq!(restored.winner(), original.winner());
+            }
+
+            step(&mut game);
+            steps += 1;
+        }
+
+        let text = snapshot(&game);
+        let restored = TwoPlayerGame::from_snapshot(Cursor::new(text.as_bytes())).unwrap();
+        assert_eq!(restored.winner(), game.winner());
+    }
+
+    #[test]
+    fn test_snapshot_errors() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 3);
+        step(&mut game);
+        let text = snapshot(&game);
+
+        let error_line = |text: &str| match TwoPlayerGame::from_snapshot(Cursor::new(text)) {
+            Err(GameSnapshotError::Parse { line }) => line,
+            other => panic!("unexpected {:?}", other.map(|game| game.phase())),
+        };
+
+        assert_eq!(error_line(&text.replacen("snapshot 1", "snapshot 2", 1)), 1);
+        assert_eq!(
+            error_line(&text.replacen("board 121 0 0", "board 121 122 0", 1)),
+            5
+        );
+
+        // Cards that were never dealt can't be in anybody's hand
+        let deck: Vec<&str> = text.lines().nth(5).unwrap().split(' ').collect();
+        let kept = text
+            .lines()
+            .find(|line| line.starts_with("kept 2"))
+            .unwrap();
+        let tampered = kept.replacen(kept.split(' ').nth(2).unwrap(), deck[52], 1);
+        assert_eq!(error_line(&text.replacen(kept, &tampered, 1)), 11);
+
+        let truncated: Vec<&str> = text.lines().take(12).collect();
+        assert_eq!(error_line(&truncated.join("\n")), 13);
+    }
+}
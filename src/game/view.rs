@@ -0,0 +1,274 @@
+use crate::board::TwoPlayers;
+use crate::card::Card;
+use crate::game::{GamePhase, Situation, TableEvent, TwoPlayerGame};
+
+// What anyone watching the table knows: scores, the starter, the cards pegged so far, how many
+// cards each player holds and whatever has been shown. Neither view hands out the game itself, so
+// an unrevealed hand or crib can't be reached through them
+#[derive(Clone, Copy, Debug)]
+pub struct SpectatorView<'a> {
+    game: &'a TwoPlayerGame,
+}
+
+impl<'a> SpectatorView<'a> {
+    pub fn new(game: &'a TwoPlayerGame) -> SpectatorView<'a> {
+        SpectatorView { game }
+    }
+
+    pub fn phase(&self) -> GamePhase {
+        self.game.phase()
+    }
+
+    pub fn dealer(&self) -> TwoPlayers {
+        self.game.dealer()
+    }
+
+    pub fn crib_owner(&self) -> TwoPlayers {
+        self.game.dealer()
+    }
+
+    pub fn score(&self, player: TwoPlayers) -> u8 {
+        self.game.score(player)
+    }
+
+    pub fn target(&self) -> u8 {
+        self.game.board().target()
+    }
+
+    pub fn winner(&self) -> Option<TwoPlayers> {
+        self.game.winner()
+    }
+
+    pub fn starter(&self) -> Option<Card> {
+        self.game.starter()
+    }
+
+    pub fn turn(&self) -> Option<TwoPlayers> {
+        self.game.turn()
+    }
+
+    pub fn count(&self) -> u8 {
+        self.game.pegger().count()
+    }
+
+    // The cards of the current pegging sequence, up to 31
+    pub fn sequence(&self) -> &'a [Card] {
+        self.game.pegger().played_cards()
+    }
+
+    pub fn played_cards(&self, player: TwoPlayers) -> Vec<Card> {
+        self.deal_events()
+            .iter()
+            .filter_map(|event| match *event {
+                TableEvent::Played {
+                    player: played_by,
+                    card,
+                    ..
+                } if played_by == player => Some(card),
+                _ => None,
+            })
+            .collect()
+    }
+
+    pub fn has_discarded(&self, player: TwoPlayers) -> bool {
+        self.game.crib_part(player).is_some()
+    }
+
+    pub fn cards_held(&self, player: TwoPlayers) -> usize {
+        match (self.game.deal(player), self.game.kept_cards(player)) {
+            (Some(deal), _) => deal.cards().len(),
+            (None, Some(kept)) if self.phase() == GamePhase::Discard => kept.cards().len(),
+            _ => self.game.pegging_hand(player).len(),
+        }
+    }
+
+    pub fn shown_hand(&self, player: TwoPlayers) -> Option<[Card; 4]> {
+        self.shown(player, false)
+    }
+
+    pub fn shown_crib(&self) -> Option<[Card; 4]> {
+        self.shown(self.crib_owner(), true)
+    }
+
+    pub fn deal_events(&self) -> &'a [TableEvent] {
+        self.game.deal_events()
+    }
+
+    pub fn events(&self) -> &'a [TableEvent] {
+        self.game.events()
+    }
+
+    fn shown(&self, player: TwoPlayers, crib: bool) -> Option<[Card; 4]> {
+        self.deal_events().iter().find_map(|event| match *event {
+            TableEvent::Shown {
+                player: shown_by,
+                cards,
+                is_crib,
+                ..
+            } if shown_by == player && is_crib == crib => Some(cards),
+            _ => None,
+        })
+    }
+}
+
+// A seat's view: everything public plus its own cards
+#[derive(Clone, Copy, Debug)]
+pub struct PlayerView<'a> {
+    player: TwoPlayers,
+    table: SpectatorView<'a>,
+}
+
+impl<'a> PlayerView<'a> {
+    pub fn new(game: &'a TwoPlayerGame, player: TwoPlayers) -> PlayerView<'a> {
+        PlayerView {
+            player,
+            table: SpectatorView::new(game),
+        }
+    }
+
+    pub fn player(&self) -> TwoPlayers {
+        self.player
+    }
+
+    pub fn table(&self) -> &SpectatorView<'a> {
+        &self.table
+    }
+
+    pub fn is_dealer(&self) -> bool {
+        self.player == self.table.dealer()
+    }
+
+    // The six dealt cards until the discard, then the four kept ones
+    pub fn hand(&self) -> Vec<Card> {
+        let game = self.table.game;
+        if let Some(deal) = game.deal(self.player) {
+            return deal.cards().to_vec();
+        }
+
+        match game.kept_cards(self.player) {
+            Some(kept) => kept.cards().to_vec(),
+            None => self
+                .table
+                .shown_hand(self.player)
+                .map(|cards| cards.to_vec())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn discarded(&self) -> Option<&'a [Card]> {
+        self.table
+            .game
+            .crib_part(self.player)
+            .map(|part| part.cards())
+    }
+
+    // The kept cards not yet pegged
+    pub fn unplayed(&self) -> &'a [Card] {
+        self.table.game.pegging_hand(self.player)
+    }
+
+    pub fn legal_plays(&self) -> Vec<Card> {
+        self.table.game.legal_plays(self.player)
+    }
+
+    pub fn own_score(&self) -> u8 {
+        self.table.score(self.player)
+    }
+
+    pub fn opponent_score(&self) -> u8 {
+        self.table.score(self.player.other())
+    }
+
+    pub fn opponent_cards_held(&self) -> usize {
+        self.table.cards_held(self.player.other())
+    }
+
+    pub fn situation(&self) -> Situation<'a> {
+        Situation::new(self.table.game, self.player)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::RandomBot;
+    use crate::game::{play_two_player_game, GamePhase, PlayerView, SpectatorView, TwoPlayerGame};
+
+    #[test]
+    fn test_views_hide_unshown_cards() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 6);
+        let one = TwoPlayers::PlayerOne;
+        let two = TwoPlayers::PlayerTwo;
+        let view = PlayerView::new(&game, two);
+        assert_eq!(view.hand().len(), 6);
+        assert_eq!(view.opponent_cards_held(), 6);
+        assert!(!view.is_dealer());
+        assert_eq!(view.discarded(), None);
+
+        let crib = [
+            game.deal(one).unwrap().cards()[0],
+            game.deal(one).unwrap().cards()[1],
+        ];
+        game.discard(one, crib).unwrap();
+        let view = PlayerView::new(&game, two);
+        assert!(view.table().has_discarded(one));
+        assert_eq!(view.opponent_cards_held(), 4);
+        let view = PlayerView::new(&game, one);
+        assert_eq!(view.discarded(), Some(&crib[..]));
+        assert_eq!(view.hand().len(), 4);
+
+        let crib = [
+            game.deal(two).unwrap().cards()[0],
+            game.deal(two).unwrap().cards()[1],
+        ];
+        game.discard(two, crib).unwrap();
+        let pone = game.pone();
+        let card = game.legal_plays(pone)[0];
+        game.play(pone, card).unwrap();
+
+        let table = SpectatorView::new(&game);
+        assert_eq!(table.phase(), GamePhase::Pegging);
+        assert_eq!(table.cards_held(pone), 3);
+        assert_eq!(table.cards_held(game.dealer()), 4);
+        assert_eq!(table.played_cards(pone), vec![card]);
+        assert_eq!(table.sequence(), &[card]);
+        assert_eq!(table.shown_hand(pone), None);
+        assert_eq!(table.shown_crib(), None);
+
+        let view = PlayerView::new(&game, pone);
+        assert_eq!(view.hand().len(), 4);
+        assert_eq!(view.unplayed().len(), 3);
+        assert!(view.legal_plays().is_empty());
+    }
+
+    #[test]
+    fn test_views_reveal_shown_cards() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerTwo, 9);
+        play_two_player_game(&mut game, &mut RandomBot::new(1), &mut RandomBot::new(2)).unwrap();
+        let table = SpectatorView::new(&game);
+        let winner = table.winner().unwrap();
+        assert_eq!(table.score(winner), table.target());
+
+        // With this seed the dealer wins on the crib, so both hands and the crib were shown and
+        // are public, along with every pegged card
+        assert_eq!(winner, table.dealer());
+        let crib = table.shown_crib().unwrap();
+        for player in [winner, winner.other()] {
+            let hand = table.shown_hand(player).unwrap();
+            let own = PlayerView::new(&game, player);
+            assert_eq!(own.hand(), hand.to_vec());
+            assert!(table
+                .played_cards(player)
+                .iter()
+                .all(|card| hand.contains(card)));
+            assert!(own
+                .discarded()
+                .unwrap()
+                .iter()
+                .all(|card| crib.contains(card)));
+        }
+
+        let view = PlayerView::new(&game, winner.other());
+        assert_eq!(view.situation().opponent_score, table.target());
+    }
+}
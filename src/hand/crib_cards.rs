@@ -1,12 +1,17 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::card::Card;
+use crate::card_set::CardSet;
 use crate::hand::Hand;
 
+#[derive(Clone, Debug)]
 pub struct CribCards {
     cards: [Card; 4],
 }
 
 impl CribCards {
-    pub(in crate::hand) fn new(cards: [Card; 4]) -> CribCards {
+    pub(crate) fn new(cards: [Card; 4]) -> CribCards {
         CribCards { cards }
     }
 
@@ -17,4 +22,22 @@ impl CribCards {
     pub fn cards(&self) -> &[Card] {
         &self.cards
     }
+
+    pub fn card_set(&self) -> CardSet {
+        CardSet::from_cards(&self.cards)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for CribCards {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.cards.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for CribCards {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<CribCards, D::Error> {
+        crate::hand::distinct_cards(deserializer).map(CribCards::new)
+    }
 }
@@ -1,12 +1,14 @@
 use crate::card::Card;
+use crate::card_set::CardSet;
 use crate::hand::CribCards;
 
+#[derive(Clone, Debug)]
 pub struct TwoPlayerCribPart {
     cards: [Card; 2],
 }
 
 impl TwoPlayerCribPart {
-    pub(in crate::hand) fn new(cards: [Card; 2]) -> TwoPlayerCribPart {
+    pub(crate) fn new(cards: [Card; 2]) -> TwoPlayerCribPart {
         TwoPlayerCribPart { cards }
     }
 
@@ -14,12 +16,17 @@ impl TwoPlayerCribPart {
         &self.cards
     }
 
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::needless_pass_by_value))]
+    pub fn card_set(&self) -> CardSet {
+        CardSet::from_cards(&self.cards)
+    }
+
+    #[allow(clippy::needless_pass_by_value)]
     pub fn combine(self, other: TwoPlayerCribPart) -> CribCards {
         CribCards::new([self.cards[0], self.cards[1], other.cards[0], other.cards[1]])
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct ThreePlayerCribPart {
     card: Card,
 }
@@ -29,7 +36,7 @@ impl ThreePlayerCribPart {
         ThreePlayerCribPart { card }
     }
 
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::needless_pass_by_value))]
+    #[allow(clippy::needless_pass_by_value)]
     pub fn combine(
         self,
         other1: ThreePlayerCribPart,
@@ -40,6 +47,7 @@ impl ThreePlayerCribPart {
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct FourPlayerCribPart {
     card: Card,
 }
@@ -49,7 +57,7 @@ impl FourPlayerCribPart {
         FourPlayerCribPart { card }
     }
 
-    #[cfg_attr(feature = "cargo-clippy", allow(clippy::needless_pass_by_value))]
+    #[allow(clippy::needless_pass_by_value)]
     pub fn combine(
         self,
         other1: FourPlayerCribPart,
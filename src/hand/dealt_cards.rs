@@ -1,6 +1,8 @@
 use crate::card::Card;
+use crate::card_set::CardSet;
 use crate::hand::{FourPlayerCribPart, KeptCards, ThreePlayerCribPart, TwoPlayerCribPart};
 
+#[derive(Clone, Debug)]
 pub struct TwoPlayerDeal {
     cards: [Card; 6],
 }
@@ -14,12 +16,17 @@ impl TwoPlayerDeal {
         &self.cards
     }
 
+    pub fn card_set(&self) -> CardSet {
+        CardSet::from_cards(&self.cards)
+    }
+
     pub fn split(self, keep: [Card; 4], crib: [Card; 2]) -> (KeptCards, TwoPlayerCribPart) {
         // Todo: Make sure cards specified exist in self.cards
         (KeptCards::new(keep), TwoPlayerCribPart::new(crib))
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct ThreePlayerDeal {
     cards: [Card; 5],
 }
@@ -33,11 +40,16 @@ impl ThreePlayerDeal {
         &self.cards
     }
 
+    pub fn card_set(&self) -> CardSet {
+        CardSet::from_cards(&self.cards)
+    }
+
     pub fn split(self, keep: [Card; 4], crib: Card) -> (KeptCards, ThreePlayerCribPart) {
         (KeptCards::new(keep), ThreePlayerCribPart::new(crib))
     }
 }
 
+#[derive(Clone, Debug)]
 pub struct FourPlayerDeal {
     cards: [Card; 5],
 }
@@ -51,6 +63,10 @@ impl FourPlayerDeal {
         &self.cards
     }
 
+    pub fn card_set(&self) -> CardSet {
+        CardSet::from_cards(&self.cards)
+    }
+
     pub fn split(self, keep: [Card; 4], crib: Card) -> (KeptCards, FourPlayerCribPart) {
         (KeptCards::new(keep), FourPlayerCribPart::new(crib))
     }
@@ -1,12 +1,17 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
 use crate::card::Card;
+use crate::card_set::CardSet;
 use crate::hand::Hand;
 
+#[derive(Clone, Debug)]
 pub struct KeptCards {
     cards: [Card; 4],
 }
 
 impl KeptCards {
-    pub(in crate::hand) fn new(cards: [Card; 4]) -> KeptCards {
+    pub(crate) fn new(cards: [Card; 4]) -> KeptCards {
         KeptCards { cards }
     }
 
@@ -17,4 +22,22 @@ impl KeptCards {
     pub fn cards(&self) -> &[Card] {
         &self.cards
     }
+
+    pub fn card_set(&self) -> CardSet {
+        CardSet::from_cards(&self.cards)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for KeptCards {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.cards.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for KeptCards {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<KeptCards, D::Error> {
+        crate::hand::distinct_cards(deserializer).map(KeptCards::new)
+    }
 }
@@ -2,8 +2,13 @@ mod crib_cards;
 mod crib_part;
 mod dealt_cards;
 mod kept_cards;
+mod scoring;
 
-use crate::card::{Card, Rank};
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::card::Card;
+use crate::card_set::CardSet;
 use crate::deck::Deck;
 use crate::CribbageCoreError;
 
@@ -15,6 +20,9 @@ pub use self::dealt_cards::FourPlayerDeal;
 pub use self::dealt_cards::ThreePlayerDeal;
 pub use self::dealt_cards::TwoPlayerDeal;
 pub use self::kept_cards::KeptCards;
+pub use self::scoring::{
+    score_breakdown, score_hand, score_hands, score_hands_parallel, ScoreBreakdown,
+};
 
 pub fn deal_four_player_hand(deck: &mut Deck) -> Result<FourPlayerDeal, CribbageCoreError> {
     let cards = deck.draw_n(5)?;
@@ -37,7 +45,7 @@ pub fn deal_two_player_hand(deck: &mut Deck) -> Result<TwoPlayerDeal, CribbageCo
     ]))
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Hand {
     cards: [Card; 4],
     cut: Card,
@@ -59,187 +67,87 @@ impl Hand {
         &self.cards
     }
 
-    pub fn score(&mut self) -> u8 {
-        if let Some(score) = self.score {
-            return score;
-        }
-
-        let mut score = 0u8;
-        score += self.score_fifteens();
-        score += self.score_pairs();
-        score += self.score_runs();
-        score += self.score_flush();
-        score += self.score_nobs();
-        self.score = Some(score);
-        score
+    pub fn cut(&self) -> Card {
+        self.cut
     }
 
-    fn score_fifteens(&self) -> u8 {
-        let mut points = 0u8;
-        let subsets = vec![
-            self.sets_of_two(),
-            self.sets_of_three(),
-            self.sets_of_four(),
-            self.sets_of_five(),
-        ];
-
-        for subset in subsets {
-            for set in subset {
-                let mut sum = 0u8;
-                for card in &set {
-                    sum += card.rank().value();
-                }
-
-                if sum == 15 {
-                    points += 2;
-                }
-            }
-        }
-
-        points
+    pub fn is_crib(&self) -> bool {
+        self.is_crib
     }
 
-    fn score_flush(&self) -> u8 {
-        let mut points = 0;
-        let suit = self.cards[0].suit();
-        if self.cards[1..4].iter().all(|c| c.suit() == suit) {
-            points += 4;
-
-            if self.cut.suit() == suit {
-                points += 1;
-            } else if self.is_crib {
-                points = 0;
-            }
-        }
-
-        points
+    pub fn card_set(&self) -> CardSet {
+        let mut set = CardSet::from_cards(&self.cards);
+        set.insert(self.cut);
+        set
     }
 
-    fn score_nobs(&self) -> u8 {
-        for card in &self.cards {
-            if card.rank() == Rank::Jack && card.suit() == self.cut.suit() {
-                return 1;
-            }
+    pub fn score(&mut self) -> u8 {
+        if let Some(score) = self.score {
+            return score;
         }
 
-        0
+        let score = score_hand(self.cards, self.cut, self.is_crib);
+        self.score = Some(score);
+        score
     }
 
-    fn score_pairs(&self) -> u8 {
-        let mut points = 0u8;
-        let sets_of_two = self.sets_of_two();
-        for set in sets_of_two {
-            if set[0].rank() == set[1].rank() {
-                points += 2u8;
-            }
-        }
-
-        points
+    pub fn breakdown(&self) -> ScoreBreakdown {
+        score_breakdown(self.cards, self.cut, self.is_crib)
     }
+}
 
-    fn score_runs(&self) -> u8 {
-        let mut points = 0u8;
-        let mut temp = 0u8;
-
-        for mut set in self.sets_of_five() {
-            set.sort();
-            temp += Hand::score_run(&set);
-        }
-
-        points += temp;
-        if points != 0u8 {
-            return points;
-        }
-
-        for mut set in self.sets_of_four() {
-            set.sort();
-            temp += Hand::score_run(&set);
-        }
+#[cfg(feature = "serde")]
+pub(in crate::hand) fn distinct_cards<'de, D, const N: usize>(
+    deserializer: D,
+) -> Result<[Card; N], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use std::convert::TryInto;
+
+    let cards = Vec::<Card>::deserialize(deserializer)?;
+    if CardSet::from_cards(&cards).len() != cards.len() {
+        return Err(de::Error::custom("repeated card"));
+    }
 
-        points += temp;
-        if points != 0u8 {
-            return points;
-        }
+    let len = cards.len();
+    cards
+        .try_into()
+        .map_err(|_| de::Error::invalid_length(len, &format!("{} cards", N).as_str()))
+}
 
-        for mut set in self.sets_of_three() {
-            set.sort();
-            temp += Hand::score_run(&set);
-        }
+// The score is left out and recomputed when needed
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "Hand")]
+struct HandFields {
+    cards: [Card; 4],
+    cut: Card,
+    is_crib: bool,
+}
 
-        points += temp;
-        if points != 0u8 {
-            return points;
+#[cfg(feature = "serde")]
+impl Serialize for Hand {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        HandFields {
+            cards: self.cards,
+            cut: self.cut,
+            is_crib: self.is_crib,
         }
-
-        points
+        .serialize(serializer)
     }
+}
 
-    fn score_run(cards: &[Card]) -> u8 {
-        if cards.len() < 3usize {
-            return 0u8;
-        }
-
-        let mut points = 1u8;
-        for i in 1..cards.len() {
-            if cards[i].rank().ordinal() == (cards[i - 1].rank().ordinal() + 1) {
-                points += 1u8;
-            } else {
-                points = 0u8;
-                break;
-            }
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Hand {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Hand, D::Error> {
+        let fields = HandFields::deserialize(deserializer)?;
+        let hand = Hand::new(fields.cards, fields.cut, fields.is_crib);
+        if hand.card_set().len() != 5 {
+            return Err(de::Error::custom("a hand needs five different cards"));
         }
 
-        points
-    }
-
-    fn sets_of_two(&self) -> Vec<Vec<Card>> {
-        vec![
-            vec![self.cards[0], self.cards[1]],
-            vec![self.cards[0], self.cards[2]],
-            vec![self.cards[0], self.cards[3]],
-            vec![self.cards[0], self.cut],
-            vec![self.cards[1], self.cards[2]],
-            vec![self.cards[1], self.cards[3]],
-            vec![self.cards[1], self.cut],
-            vec![self.cards[2], self.cards[3]],
-            vec![self.cards[2], self.cut],
-            vec![self.cards[3], self.cut],
-        ]
-    }
-
-    fn sets_of_three(&self) -> Vec<Vec<Card>> {
-        vec![
-            vec![self.cards[0], self.cards[1], self.cards[2]],
-            vec![self.cards[0], self.cards[1], self.cards[3]],
-            vec![self.cards[0], self.cards[2], self.cards[3]],
-            vec![self.cards[1], self.cards[2], self.cards[3]],
-            vec![self.cards[0], self.cards[1], self.cut],
-            vec![self.cards[0], self.cards[2], self.cut],
-            vec![self.cards[1], self.cards[2], self.cut],
-            vec![self.cards[0], self.cards[3], self.cut],
-            vec![self.cards[1], self.cards[3], self.cut],
-            vec![self.cards[2], self.cards[3], self.cut],
-        ]
-    }
-
-    fn sets_of_four(&self) -> Vec<Vec<Card>> {
-        vec![
-            vec![self.cards[0], self.cards[1], self.cards[2], self.cards[3]],
-            vec![self.cards[0], self.cards[1], self.cards[2], self.cut],
-            vec![self.cards[0], self.cards[1], self.cards[3], self.cut],
-            vec![self.cards[0], self.cards[2], self.cards[3], self.cut],
-            vec![self.cards[1], self.cards[2], self.cards[3], self.cut],
-        ]
-    }
-
-    fn sets_of_five(&self) -> Vec<Vec<Card>> {
-        vec![vec![
-            self.cards[0],
-            self.cards[1],
-            self.cards[2],
-            self.cards[3],
-            self.cut,
-        ]]
+        Ok(hand)
     }
 }
 
@@ -249,12 +157,40 @@ mod tests {
     use crate::hand::Hand;
     use std::str::FromStr;
 
+    fn hand(cards: [&str; 4], cut: &str, is_crib: bool) -> Hand {
+        Hand::new(
+            [
+                Card::from_str(cards[0]).unwrap(),
+                Card::from_str(cards[1]).unwrap(),
+                Card::from_str(cards[2]).unwrap(),
+                Card::from_str(cards[3]).unwrap(),
+            ],
+            Card::from_str(cut).unwrap(),
+            is_crib,
+        )
+    }
+
+    #[test]
+    fn test_score() {
+        assert_eq!(hand(["5H", "5C", "5S", "JD"], "5D", false).score(), 29);
+        assert_eq!(hand(["5H", "5C", "5S", "JD"], "5D", true).score(), 29);
+        assert_eq!(hand(["5H", "5C", "5S", "5D"], "JD", false).score(), 28);
+        assert_eq!(hand(["4H", "4C", "5S", "6D"], "6C", false).score(), 24);
+        assert_eq!(hand(["3H", "3C", "3S", "4D"], "5C", false).score(), 21);
+        assert_eq!(hand(["AH", "2H", "3H", "4H"], "6C", false).score(), 10);
+        assert_eq!(hand(["AH", "2H", "3H", "4H"], "6C", true).score(), 6);
+        assert_eq!(hand(["AH", "2H", "3H", "4H"], "6H", true).score(), 11);
+        assert_eq!(hand(["2H", "4C", "6S", "8D"], "QC", false).score(), 0);
+        assert_eq!(hand(["JH", "2C", "4S", "6D"], "QH", false).score(), 1);
+        assert_eq!(hand(["AH", "2C", "3S", "4D"], "5C", false).score(), 7);
+    }
+
     #[allow(dead_code)]
     #[cfg_attr(feature = "extensive-tests", test)]
     fn test_hands() {
+        use crate::card::RANKS;
+        use crate::hand_scores::{hand_score_file_name, HandScoreReader};
         use std::env;
-        use std::fs::File;
-        use std::io::{BufRead, BufReader};
         use std::path::PathBuf;
 
         let key = "CRIBBAGE_CORE_RESOURCES_DIRECTORY";
@@ -267,55 +203,32 @@ mod tests {
         path.push(resources);
         path.push("cribbage_hand_scores");
 
-        let filenames = [
-            "A.txt", "2.txt", "3.txt", "4.txt", "5.txt", "6.txt", "7.txt", "8.txt", "9.txt",
-            "T.txt", "J.txt", "Q.txt", "K.txt",
-        ];
-
-        for filename in &filenames {
-            path.push(filename);
+        for rank in &RANKS {
+            path.push(hand_score_file_name(*rank));
             println!("Processing: {:?}", path);
-            let reader = BufReader::new(File::open(&path).unwrap());
-            for (line_number, line) in reader.lines().enumerate() {
-                let line = line.unwrap().to_string();
-                let tokens: Vec<&str> = line.split(' ').collect();
-                if tokens.len() != 7 {
-                    panic!("Line {} in {:?} is malformed", line_number + 1, path);
-                }
-
-                let cut = Card::from_str(tokens[0]).unwrap();
-                let card1 = Card::from_str(tokens[1]).unwrap();
-                let card2 = Card::from_str(tokens[2]).unwrap();
-                let card3 = Card::from_str(tokens[3]).unwrap();
-                let card4 = Card::from_str(tokens[4]).unwrap();
-                let expected_non_crib_hand_score: u8 = tokens[5].parse().unwrap();
-                let expected_crib_hand_score: u8 = tokens[6].parse().unwrap();
+            for entry in HandScoreReader::open(&path).unwrap() {
+                let entry = match entry {
+                    Ok(entry) => entry,
+                    Err(error) => panic!("{:?}: {}", path, error),
+                };
 
-                let mut hand = Hand::new([card1, card2, card3, card4], cut, false);
+                let mut hand = Hand::new(entry.cards, entry.cut, false);
                 let non_crib_hand_score = hand.score();
-                if non_crib_hand_score != expected_non_crib_hand_score {
+                if non_crib_hand_score != entry.non_crib_score {
                     panic!(
-                        "Line {} in {:?}: {:?} scored {} points as a non-crib hand,\
+                        "{:?}: {:?} scored {} points as a non-crib hand, \
                          but should have scored {} points",
-                        line_number + 1,
-                        path,
-                        hand,
-                        non_crib_hand_score,
-                        expected_non_crib_hand_score
+                        path, hand, non_crib_hand_score, entry.non_crib_score
                     );
                 }
 
-                let mut crib_hand = Hand::new([card1, card2, card3, card4], cut, true);
+                let mut crib_hand = Hand::new(entry.cards, entry.cut, true);
                 let crib_hand_score = crib_hand.score();
-                if crib_hand_score != expected_crib_hand_score {
+                if crib_hand_score != entry.crib_score {
                     panic!(
-                        "Line {} in {:?}: {:?} scored {} points as a crib hand,\
+                        "{:?}: {:?} scored {} points as a crib hand, \
                          but should have scored {} points",
-                        line_number + 1,
-                        path,
-                        hand,
-                        non_crib_hand_score,
-                        expected_non_crib_hand_score
+                        path, crib_hand, crib_hand_score, entry.crib_score
                     );
                 }
             }
@@ -323,4 +236,37 @@ mod tests {
             path.pop();
         }
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_hand_serde() {
+        use crate::hand::{CribCards, KeptCards};
+
+        let mut original = hand(["5H", "5C", "5S", "JD"], "5D", false);
+        let json = serde_json::to_string(&original).unwrap();
+        assert_eq!(
+            json,
+            r#"{"cards":["5H","5C","5S","JD"],"cut":"5D","is_crib":false}"#
+        );
+        let mut restored: Hand = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.cards(), original.cards());
+        assert_eq!(restored.score(), original.score());
+
+        for json in &[
+            r#"{"cards":["5H","5C","5S","5H"],"cut":"5D","is_crib":false}"#,
+            r#"{"cards":["5H","5C","5S","JD"],"cut":"5S","is_crib":true}"#,
+            r#"{"cards":["5H","5C","5S"],"cut":"5D","is_crib":false}"#,
+        ] {
+            assert!(serde_json::from_str::<Hand>(json).is_err(), "{}", json);
+        }
+
+        let kept: KeptCards = serde_json::from_str(r#"["AH","2H","3H","4H"]"#).unwrap();
+        assert_eq!(
+            serde_json::to_string(&kept).unwrap(),
+            r#"["AH","2H","3H","4H"]"#
+        );
+        assert!(serde_json::from_str::<KeptCards>(r#"["AH","2H","3H","AH"]"#).is_err());
+        assert!(serde_json::from_str::<CribCards>(r#"["AH","2H","3H","4H","5H"]"#).is_err());
+        assert!(serde_json::from_str::<CribCards>(r#"["AH","2H","3H","4H"]"#).is_ok());
+    }
 }
@@ -0,0 +1,250 @@
+use std::thread;
+
+use crate::card::{Card, Rank};
+use crate::CribbageCoreError;
+
+// Points by category, as a hand is counted aloud at the show
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ScoreBreakdown {
+    pub fifteens: u8,
+    pub pairs: u8,
+    pub runs: u8,
+    pub flush: u8,
+    pub nobs: u8,
+}
+
+impl ScoreBreakdown {
+    pub fn total(&self) -> u8 {
+        self.fifteens + self.pairs + self.runs + self.flush + self.nobs
+    }
+}
+
+pub fn score_breakdown(cards: [Card; 4], cut: Card, is_crib: bool) -> ScoreBreakdown {
+    let rank_counts = rank_counts(&cards, cut);
+    ScoreBreakdown {
+        fifteens: score_fifteens(&cards, cut),
+        pairs: score_pairs(&rank_counts),
+        runs: score_runs(&rank_counts),
+        flush: score_flush(&cards, cut, is_crib),
+        nobs: score_nobs(&cards, cut),
+    }
+}
+
+pub fn score_hand(cards: [Card; 4], cut: Card, is_crib: bool) -> u8 {
+    score_breakdown(cards, cut, is_crib).total()
+}
+
+pub fn score_hands(
+    hands: &[([Card; 4], Card, bool)],
+    scores: &mut [u8],
+) -> Result<(), CribbageCoreError> {
+    if hands.len() != scores.len() {
+        return Err(CribbageCoreError::MismatchedLength);
+    }
+
+    for (&(cards, cut, is_crib), score) in hands.iter().zip(scores.iter_mut()) {
+        *score = score_hand(cards, cut, is_crib);
+    }
+
+    Ok(())
+}
+
+pub fn score_hands_parallel(
+    hands: &[([Card; 4], Card, bool)],
+    scores: &mut [u8],
+    threads: usize,
+) -> Result<(), CribbageCoreError> {
+    if hands.len() != scores.len() {
+        return Err(CribbageCoreError::MismatchedLength);
+    }
+
+    let threads = match threads {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+
+    if threads == 1 || hands.len() < threads {
+        return score_hands(hands, scores);
+    }
+
+    let chunk_size = (hands.len() + threads - 1) / threads;
+    thread::scope(|scope| {
+        for (hands, scores) in hands.chunks(chunk_size).zip(scores.chunks_mut(chunk_size)) {
+            scope.spawn(move || score_hands(hands, scores));
+        }
+    });
+
+    Ok(())
+}
+
+fn rank_counts(cards: &[Card; 4], cut: Card) -> [u8; 13] {
+    let mut counts = [0u8; 13];
+    for card in cards.iter().chain(Some(&cut)) {
+        counts[(card.rank().ordinal() - 1) as usize] += 1;
+    }
+
+    counts
+}
+
+fn score_fifteens(cards: &[Card; 4], cut: Card) -> u8 {
+    // ways[n] is the number of subsets seen so far whose values sum to n
+    let mut ways = [0u8; 16];
+    ways[0] = 1;
+    for card in cards.iter().chain(Some(&cut)) {
+        let value = card.rank().value() as usize;
+        for sum in (value..16).rev() {
+            ways[sum] += ways[sum - value];
+        }
+    }
+
+    ways[15] * 2
+}
+
+fn score_flush(cards: &[Card; 4], cut: Card, is_crib: bool) -> u8 {
+    let mut points = 0;
+    let suit = cards[0].suit();
+    if cards[1..4].iter().all(|c| c.suit() == suit) {
+        points += 4;
+
+        if cut.suit() == suit {
+            points += 1;
+        } else if is_crib {
+            points = 0;
+        }
+    }
+
+    points
+}
+
+fn score_nobs(cards: &[Card; 4], cut: Card) -> u8 {
+    for card in cards {
+        if card.rank() == Rank::Jack && card.suit() == cut.suit() {
+            return 1;
+        }
+    }
+
+    0
+}
+
+fn score_pairs(rank_counts: &[u8; 13]) -> u8 {
+    rank_counts
+        .iter()
+        .map(|&count| count * count.saturating_sub(1))
+        .sum()
+}
+
+fn score_runs(rank_counts: &[u8; 13]) -> u8 {
+    let mut length = 0u8;
+    let mut combinations = 1u8;
+    for &count in rank_counts.iter().chain(Some(&0)) {
+        if count > 0 {
+            length += 1;
+            combinations *= count;
+        } else if length >= 3 {
+            // Five cards can't hold two separate runs, so the first one found is the only one
+            return length * combinations;
+        } else {
+            length = 0;
+            combinations = 1;
+        }
+    }
+
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::deck::Deck;
+    use crate::hand::{
+        score_breakdown, score_hand, score_hands, score_hands_parallel, Hand, ScoreBreakdown,
+    };
+    use crate::CribbageCoreError;
+    use std::str::FromStr;
+
+    fn sample_hands() -> Vec<([Card; 4], Card, bool)> {
+        let mut deck = Deck::new();
+        let mut hands = Vec::new();
+        for i in 0..500 {
+            if i % 10 == 0 {
+                deck.shuffle();
+            }
+
+            let cards = deck.draw_n(5).unwrap();
+            hands.push((
+                [cards[0], cards[1], cards[2], cards[3]],
+                cards[4],
+                i % 2 == 0,
+            ));
+        }
+
+        hands
+    }
+
+    #[test]
+    fn test_score_hands() {
+        let hands = sample_hands();
+        let mut scores = vec![0u8; hands.len()];
+        score_hands(&hands, &mut scores).unwrap();
+
+        for (&(cards, cut, is_crib), &score) in hands.iter().zip(scores.iter()) {
+            assert_eq!(score, Hand::new(cards, cut, is_crib).score());
+            assert_eq!(score, score_hand(cards, cut, is_crib));
+            assert_eq!(score, score_breakdown(cards, cut, is_crib).total());
+        }
+
+        assert_eq!(
+            score_hands(&hands, &mut scores[1..]),
+            Err(CribbageCoreError::MismatchedLength)
+        );
+    }
+
+    #[test]
+    fn test_score_hands_parallel() {
+        let hands = sample_hands();
+        let mut expected = vec![0u8; hands.len()];
+        score_hands(&hands, &mut expected).unwrap();
+
+        for &threads in &[0, 1, 3, 8, 1000] {
+            let mut scores = vec![0u8; hands.len()];
+            score_hands_parallel(&hands, &mut scores, threads).unwrap();
+            assert_eq!(scores, expected);
+        }
+
+        let mut scores = vec![0u8; hands.len() + 1];
+        assert_eq!(
+            score_hands_parallel(&hands, &mut scores, 4),
+            Err(CribbageCoreError::MismatchedLength)
+        );
+    }
+
+    #[test]
+    fn test_score_breakdown() {
+        let card = |s| Card::from_str(s).unwrap();
+        let hand = Hand::new(
+            [card("5H"), card("5C"), card("5S"), card("JD")],
+            card("5D"),
+            false,
+        );
+        assert_eq!(
+            hand.breakdown(),
+            ScoreBreakdown {
+                fifteens: 16,
+                pairs: 12,
+                runs: 0,
+                flush: 0,
+                nobs: 1,
+            }
+        );
+
+        let hand = Hand::new(
+            [card("2H"), card("3H"), card("4H"), card("9H")],
+            card("4C"),
+            true,
+        );
+        let breakdown = hand.breakdown();
+        assert_eq!((breakdown.fifteens, breakdown.pairs), (4, 2));
+        assert_eq!((breakdown.runs, breakdown.flush), (6, 0));
+        assert_eq!(breakdown.total(), 12);
+    }
+}
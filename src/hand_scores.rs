@@ -0,0 +1,486 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::card::{Card, Rank, Suit, RANKS, SUITS};
+use crate::card_set::CardSet;
+use crate::hand::score_hand;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct HandScoreEntry {
+    pub cut: Card,
+    pub cards: [Card; 4],
+    pub non_crib_score: u8,
+    pub crib_score: u8,
+}
+
+impl HandScoreEntry {
+    pub fn new(cards: [Card; 4], cut: Card) -> HandScoreEntry {
+        HandScoreEntry {
+            cut,
+            cards,
+            non_crib_score: score_hand(cards, cut, false),
+            crib_score: score_hand(cards, cut, true),
+        }
+    }
+
+    pub fn score(self, is_crib: bool) -> u8 {
+        if is_crib {
+            self.crib_score
+        } else {
+            self.non_crib_score
+        }
+    }
+}
+
+impl FromStr for HandScoreEntry {
+    type Err = ParseHandScoreError;
+
+    fn from_str(line: &str) -> Result<HandScoreEntry, ParseHandScoreError> {
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        if tokens.len() != 7 {
+            return Err(ParseHandScoreError::WrongTokenCount(tokens.len()));
+        }
+
+        let mut cards = [Card::new(Rank::Ace, Suit::Spades); 5];
+        let mut seen = CardSet::new();
+        for (card, token) in cards.iter_mut().zip(&tokens[..5]) {
+            *card = Card::from_str(token)
+                .map_err(|_| ParseHandScoreError::InvalidCard(token.to_string()))?;
+            if !seen.insert(*card) {
+                return Err(ParseHandScoreError::DuplicateCard(*card));
+            }
+        }
+
+        let parse_score = |token: &str| {
+            token
+                .parse::<u8>()
+                .map_err(|_| ParseHandScoreError::InvalidScore(token.to_string()))
+        };
+
+        Ok(HandScoreEntry {
+            cut: cards[0],
+            cards: [cards[1], cards[2], cards[3], cards[4]],
+            non_crib_score: parse_score(tokens[5])?,
+            crib_score: parse_score(tokens[6])?,
+        })
+    }
+}
+
+impl Display for HandScoreEntry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} {} {} {} {} {}",
+            self.cut,
+            self.cards[0],
+            self.cards[1],
+            self.cards[2],
+            self.cards[3],
+            self.non_crib_score,
+            self.crib_score
+        )
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseHandScoreError {
+    WrongTokenCount(usize),
+    InvalidCard(String),
+    InvalidScore(String),
+    DuplicateCard(Card),
+}
+
+impl Display for ParseHandScoreError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseHandScoreError::WrongTokenCount(count) => {
+                write!(f, "expected 7 tokens, found {}", count)
+            }
+            ParseHandScoreError::InvalidCard(token) => write!(f, "invalid card {:?}", token),
+            ParseHandScoreError::InvalidScore(token) => write!(f, "invalid score {:?}", token),
+            ParseHandScoreError::DuplicateCard(card) => write!(f, "duplicate card {}", card),
+        }
+    }
+}
+
+impl error::Error for ParseHandScoreError {}
+
+#[derive(Debug)]
+pub enum HandScoreTableError {
+    Io(io::Error),
+    Parse {
+        line: usize,
+        error: ParseHandScoreError,
+    },
+}
+
+impl Display for HandScoreTableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            HandScoreTableError::Io(error) => write!(f, "{}", error),
+            HandScoreTableError::Parse { line, error } => write!(f, "line {}: {}", line, error),
+        }
+    }
+}
+
+impl error::Error for HandScoreTableError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            HandScoreTableError::Io(error) => Some(error),
+            HandScoreTableError::Parse { error, .. } => Some(error),
+        }
+    }
+}
+
+impl From<io::Error> for HandScoreTableError {
+    fn from(error: io::Error) -> HandScoreTableError {
+        HandScoreTableError::Io(error)
+    }
+}
+
+pub fn hand_score_file_name(cut_rank: Rank) -> String {
+    format!("{}.txt", cut_rank)
+}
+
+pub fn generate_hand_scores_for_cut<W: Write>(
+    cut: Card,
+    writer: &mut HandScoreWriter<W>,
+) -> io::Result<()> {
+    let cards = CardSet::full()
+        .difference(CardSet::from_cards(&[cut]))
+        .to_vec();
+    for a in 0..cards.len() {
+        for b in (a + 1)..cards.len() {
+            for c in (b + 1)..cards.len() {
+                for d in (c + 1)..cards.len() {
+                    let hand = [cards[a], cards[b], cards[c], cards[d]];
+                    writer.write_entry(&HandScoreEntry::new(hand, cut))?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn generate_hand_scores_for_cut_rank<W: Write>(
+    cut_rank: Rank,
+    writer: &mut HandScoreWriter<W>,
+) -> io::Result<()> {
+    for suit in &SUITS {
+        generate_hand_scores_for_cut(Card::new(cut_rank, *suit), writer)?;
+    }
+
+    writer.flush()
+}
+
+pub fn generate_hand_score_directory<P: AsRef<Path>>(directory: P) -> io::Result<()> {
+    for rank in &RANKS {
+        let mut writer =
+            HandScoreWriter::create(directory.as_ref().join(hand_score_file_name(*rank)))?;
+        generate_hand_scores_for_cut_rank(*rank, &mut writer)?;
+    }
+
+    Ok(())
+}
+
+pub struct HandScoreReader<R> {
+    reader: R,
+    line: usize,
+    buffer: String,
+}
+
+impl HandScoreReader<BufReader<File>> {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, HandScoreTableError> {
+        Ok(HandScoreReader::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> HandScoreReader<R> {
+    pub fn new(reader: R) -> HandScoreReader<R> {
+        HandScoreReader {
+            reader,
+            line: 0,
+            buffer: String::new(),
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for HandScoreReader<R> {
+    type Item = Result<HandScoreEntry, HandScoreTableError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            self.buffer.clear();
+            match self.reader.read_line(&mut self.buffer) {
+                Ok(0) => return None,
+                Ok(_) => self.line += 1,
+                Err(error) => return Some(Err(error.into())),
+            }
+
+            if self.buffer.trim().is_empty() {
+                continue;
+            }
+
+            return Some(HandScoreEntry::from_str(&self.buffer).map_err(|error| {
+                HandScoreTableError::Parse {
+                    line: self.line,
+                    error,
+                }
+            }));
+        }
+    }
+}
+
+pub struct HandScoreWriter<W: Write> {
+    writer: W,
+}
+
+impl HandScoreWriter<BufWriter<File>> {
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(HandScoreWriter::new(BufWriter::new(File::create(path)?)))
+    }
+}
+
+impl<W: Write> HandScoreWriter<W> {
+    pub fn new(writer: W) -> HandScoreWriter<W> {
+        HandScoreWriter { writer }
+    }
+
+    pub fn write_entry(&mut self, entry: &HandScoreEntry) -> io::Result<()> {
+        writeln!(self.writer, "{}", entry)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+#[derive(Default)]
+pub struct HandScoreTable {
+    entries: Vec<HandScoreEntry>,
+    index: HashMap<(CardSet, Card), usize>,
+}
+
+impl HandScoreTable {
+    pub fn new() -> HandScoreTable {
+        HandScoreTable::default()
+    }
+
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<HandScoreTable, HandScoreTableError> {
+        let mut table = HandScoreTable::new();
+        table.extend_from_reader(reader)?;
+        Ok(table)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<HandScoreTable, HandScoreTableError> {
+        HandScoreTable::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn load_directory<P: AsRef<Path>>(
+        directory: P,
+    ) -> Result<HandScoreTable, HandScoreTableError> {
+        let mut table = HandScoreTable::new();
+        for rank in RANKS.iter() {
+            let path = directory.as_ref().join(hand_score_file_name(*rank));
+            table.extend_from_reader(BufReader::new(File::open(path)?))?;
+        }
+
+        Ok(table)
+    }
+
+    pub fn extend_from_reader<R: BufRead>(&mut self, reader: R) -> Result<(), HandScoreTableError> {
+        for entry in HandScoreReader::new(reader) {
+            self.insert(entry?);
+        }
+
+        Ok(())
+    }
+
+    pub fn insert(&mut self, entry: HandScoreEntry) {
+        let key = (CardSet::from_cards(&entry.cards), entry.cut);
+        match self.index.get(&key) {
+            Some(&position) => self.entries[position] = entry,
+            None => {
+                self.index.insert(key, self.entries.len());
+                self.entries.push(entry);
+            }
+        }
+    }
+
+    pub fn get(&self, cards: [Card; 4], cut: Card) -> Option<&HandScoreEntry> {
+        self.index
+            .get(&(CardSet::from_cards(&cards), cut))
+            .map(|&position| &self.entries[position])
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn entries(&self) -> &[HandScoreEntry] {
+        &self.entries
+    }
+
+    pub fn with_score(
+        &self,
+        score: u8,
+        is_crib: bool,
+    ) -> impl Iterator<Item = &HandScoreEntry> + '_ {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.score(is_crib) == score)
+    }
+
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = HandScoreWriter::new(writer);
+        for entry in &self.entries {
+            writer.write_entry(entry)?;
+        }
+
+        writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::{Card, Rank};
+    use crate::hand_scores::{
+        generate_hand_scores_for_cut, hand_score_file_name, HandScoreEntry, HandScoreReader,
+        HandScoreTable, HandScoreTableError, HandScoreWriter, ParseHandScoreError,
+    };
+    use std::io::Cursor;
+    use std::str::FromStr;
+
+    const TABLE: &str = "5D 5H 5C 5S JD 29 29\n\
+                         6C AH 2H 3H 4H 10 6\n\
+                         \n\
+                         QC 2H 4C 6S 8D 0 0\n";
+
+    fn card(s: &str) -> Card {
+        Card::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_entry() {
+        let entry = HandScoreEntry::from_str("5D 5H 5C 5S JD 29 29").unwrap();
+        assert_eq!(entry.cut, card("5D"));
+        assert_eq!(
+            entry.cards,
+            [card("5H"), card("5C"), card("5S"), card("JD")]
+        );
+        assert_eq!(entry.score(false), 29);
+        assert_eq!(entry.score(true), 29);
+        assert_eq!(entry.to_string(), "5D 5H 5C 5S JD 29 29");
+
+        assert_eq!(
+            HandScoreEntry::from_str("5D 5H 5C 5S JD 29"),
+            Err(ParseHandScoreError::WrongTokenCount(6))
+        );
+        assert_eq!(
+            HandScoreEntry::from_str("5D 5H 5C 5S XD 29 29"),
+            Err(ParseHandScoreError::InvalidCard("XD".to_string()))
+        );
+        assert_eq!(
+            HandScoreEntry::from_str("5D 5H 5C 5S JD 29 -1"),
+            Err(ParseHandScoreError::InvalidScore("-1".to_string()))
+        );
+        assert_eq!(
+            HandScoreEntry::from_str("5D 5H 5C 5S 5D 29 29"),
+            Err(ParseHandScoreError::DuplicateCard(card("5D")))
+        );
+    }
+
+    #[test]
+    fn test_reader_reports_line_numbers() {
+        let data = "5D 5H 5C 5S JD 29 29\n\n6C AH 2H 3H\n";
+        let results: Vec<_> = HandScoreReader::new(Cursor::new(data)).collect();
+        assert_eq!(results.len(), 2);
+        assert!(results[0].is_ok());
+        match &results[1] {
+            Err(HandScoreTableError::Parse { line, error }) => {
+                assert_eq!(*line, 3);
+                assert_eq!(*error, ParseHandScoreError::WrongTokenCount(4));
+            }
+            _ => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn test_table_lookup_and_filter() {
+        let table = HandScoreTable::from_reader(Cursor::new(TABLE)).unwrap();
+        assert_eq!(table.len(), 3);
+
+        let entry = table
+            .get([card("JD"), card("5S"), card("5C"), card("5H")], card("5D"))
+            .unwrap();
+        assert_eq!(entry.non_crib_score, 29);
+        assert!(table
+            .get([card("5D"), card("5S"), card("5C"), card("5H")], card("JD"))
+            .is_none());
+
+        let zeros: Vec<_> = table.with_score(0, false).collect();
+        assert_eq!(zeros.len(), 1);
+        assert_eq!(zeros[0].cut, card("QC"));
+        assert_eq!(table.with_score(6, true).count(), 1);
+        assert_eq!(table.with_score(6, false).count(), 0);
+    }
+
+    #[test]
+    fn test_table_round_trip() {
+        let table = HandScoreTable::from_reader(Cursor::new(TABLE)).unwrap();
+        let mut output = Vec::new();
+        table.write_to(&mut output).unwrap();
+
+        let expected: String = TABLE
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| format!("{}\n", line))
+            .collect();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(hand_score_file_name(Rank::Ace), "A.txt");
+        assert_eq!(hand_score_file_name(Rank::Ten), "T.txt");
+    }
+
+    #[test]
+    fn test_generate_for_cut() {
+        let mut writer = HandScoreWriter::new(Vec::new());
+        generate_hand_scores_for_cut(card("5D"), &mut writer).unwrap();
+        let output = writer.into_inner();
+
+        let table = HandScoreTable::from_reader(Cursor::new(&output)).unwrap();
+        assert_eq!(table.len(), 249_900);
+        assert!(table.entries().iter().all(|entry| entry.cut == card("5D")));
+        assert_eq!(
+            table.entries()[0],
+            HandScoreEntry::new([card("AS"), card("2S"), card("3S"), card("4S")], card("5D"))
+        );
+
+        let best = table
+            .get([card("5H"), card("5C"), card("5S"), card("JD")], card("5D"))
+            .unwrap();
+        assert_eq!(best.non_crib_score, 29);
+        assert_eq!(table.with_score(29, false).count(), 1);
+
+        let mut writer = HandScoreWriter::new(Vec::new());
+        generate_hand_scores_for_cut(card("5D"), &mut writer).unwrap();
+        assert_eq!(writer.into_inner(), output);
+    }
+}
@@ -0,0 +1,600 @@
+// A REST API over in-memory two-player games, with JSON bodies:
+//
+//   POST /games                   {"first_dealer": 1, "seed": 7, "opponent": "hard"}, all optional
+//                                 201 {"id": "...", "players": [{"seat": 1, "token": "..."}, ...]}
+//                                 With an opponent, only seat 1 gets a token and the bot plays
+//                                 seat 2 as soon as it is its turn.
+//   GET  /games/{id}/view         the caller's view of the game
+//   POST /games/{id}/discard      {"cards": ["5H", "JD"]}, answered with the new view
+//   POST /games/{id}/play         {"card": "5H"}, answered with the new view
+//   GET  /games/{id}/score        {"scores": {"1": 12, "2": 30}, "target": 121, "dealer": 2,
+//                                  "winner": null}
+//
+// Calls about a seat carry its token in an `X-Player-Token` header. Failures are answered with
+// {"error": "..."} and a 400, 401, 404 or 409 status. Hands are counted as soon as the pegging
+// ends and the next deal starts straight away, so a view carries the events of the current deal
+// in `events` and those of the deal before it, shows included, in `previous_deal`.
+
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Mutex;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::board::TwoPlayers;
+use crate::bots::Difficulty;
+use crate::card::Card;
+use crate::game::{GamePhase, Player, PlayerView, Situation, TableEvent, TwoPlayerGame};
+
+const TOKEN_HEADER: &str = "X-Player-Token";
+
+struct StoredGame {
+    game: TwoPlayerGame,
+    tokens: Vec<(TwoPlayers, String)>,
+    bot: Option<Box<dyn Player + Send>>,
+    observed: usize,
+}
+
+impl StoredGame {
+    fn seat(&self, token: Option<&str>) -> Option<TwoPlayers> {
+        let token = token?;
+        self.tokens
+            .iter()
+            .find(|(_, stored)| stored == token)
+            .map(|&(seat, _)| seat)
+    }
+
+    // Counts the hands once the pegging is over and lets the bot, if any, take its turns
+    fn settle(&mut self) -> Result<(), ApiError> {
+        let bot_seat = TwoPlayers::PlayerTwo;
+        loop {
+            if let Some(bot) = self.bot.as_mut() {
+                for event in &self.game.events()[self.observed..] {
+                    bot.observe(event, &Situation::new(&self.game, bot_seat));
+                }
+
+                self.observed = self.game.events().len();
+            }
+
+            let game = &mut self.game;
+            match (game.phase(), self.bot.as_mut()) {
+                (GamePhase::Show, _) => game.show()?,
+                (GamePhase::Discard, Some(bot)) if game.deal(bot_seat).is_some() => {
+                    let deal = game
+                        .deal(bot_seat)
+                        .cloned()
+                        .ok_or(ApiError::Conflict("the bot has no cards".to_string()))?;
+                    let crib = bot.choose_discard(&deal, &Situation::new(game, bot_seat));
+                    game.discard(bot_seat, crib)?;
+                }
+                (GamePhase::Pegging, Some(bot)) if game.turn() == Some(bot_seat) => {
+                    let legal = game.legal_plays(bot_seat);
+                    let card = bot.choose_peg(
+                        game.pegger(),
+                        game.pegging_hand(bot_seat),
+                        &legal,
+                        &Situation::new(game, bot_seat),
+                    );
+                    game.play(bot_seat, card)?;
+                }
+                _ => return Ok(()),
+            }
+        }
+    }
+
+    fn view(&self, seat: TwoPlayers) -> Value {
+        let view = PlayerView::new(&self.game, seat);
+        let table = view.table();
+        let hand = match table.phase() {
+            GamePhase::Discard => view.hand(),
+            _ => view.unplayed().to_vec(),
+        };
+
+        json!({
+            "seat": seat_number(seat),
+            "phase": phase_name(table.phase()),
+            "dealer": seat_number(table.dealer()),
+            "crib_owner": seat_number(table.crib_owner()),
+            "hand": cards_json(&hand),
+            "discarded": table.has_discarded(seat),
+            "opponent_cards": view.opponent_cards_held(),
+            "starter": table.starter().map(|card| card.to_string()),
+            "turn": table.turn().map(seat_number),
+            "legal": cards_json(&view.legal_plays()),
+            "count": table.count(),
+            "sequence": cards_json(table.sequence()),
+            "scores": self.scores(),
+            "winner": table.winner().map(seat_number),
+            "events": table.deal_events().iter().map(event_json).collect::<Vec<Value>>(),
+            "previous_deal": previous_deal_events(table.events(), table.deal_events().len())
+                .iter()
+                .map(event_json)
+                .collect::<Vec<Value>>(),
+        })
+    }
+
+    fn scores(&self) -> Value {
+        json!({
+            "1": self.game.score(TwoPlayers::PlayerOne),
+            "2": self.game.score(TwoPlayers::PlayerTwo),
+        })
+    }
+}
+
+#[derive(Debug)]
+enum ApiError {
+    BadRequest(String),
+    Unauthorized,
+    NotFound,
+    Conflict(String),
+}
+
+impl ApiError {
+    fn status(&self) -> u16 {
+        match self {
+            ApiError::BadRequest(_) => 400,
+            ApiError::Unauthorized => 401,
+            ApiError::NotFound => 404,
+            ApiError::Conflict(_) => 409,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            ApiError::BadRequest(message) | ApiError::Conflict(message) => message.clone(),
+            ApiError::Unauthorized => "missing or unknown player token".to_string(),
+            ApiError::NotFound => "not found".to_string(),
+        }
+    }
+}
+
+impl From<crate::CribbageCoreError> for ApiError {
+    fn from(error: crate::CribbageCoreError) -> ApiError {
+        ApiError::Conflict(error.to_string())
+    }
+}
+
+struct Games {
+    rng: StdRng,
+    games: HashMap<String, StoredGame>,
+}
+
+pub struct HttpServer {
+    server: Server,
+    games: Mutex<Games>,
+}
+
+impl HttpServer {
+    pub fn bind(address: &str, seed: u64) -> io::Result<HttpServer> {
+        let server = Server::http(address)
+            .map_err(|error| io::Error::new(io::ErrorKind::Other, error.to_string()))?;
+        Ok(HttpServer {
+            server,
+            games: Mutex::new(Games {
+                rng: StdRng::seed_from_u64(seed),
+                games: HashMap::new(),
+            }),
+        })
+    }
+
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.server.server_addr().to_ip()
+    }
+
+    pub fn run(&self) -> io::Result<()> {
+        for mut request in self.server.incoming_requests() {
+            let mut body = String::new();
+            let (status, value) = match request.as_reader().read_to_string(&mut body) {
+                Ok(_) => self.respond(&request, &body),
+                Err(_) => (400, json!({"error": "unreadable body"})),
+            };
+
+            let response = Response::from_string(value.to_string())
+                .with_status_code(status)
+                .with_header(
+                    Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                        .expect("static header"),
+                );
+            let _ = request.respond(response);
+        }
+
+        Ok(())
+    }
+
+    fn respond(&self, request: &Request, body: &str) -> (u16, Value) {
+        let token = request
+            .headers()
+            .iter()
+            .find(|header| header.field.equiv(TOKEN_HEADER))
+            .map(|header| header.value.as_str());
+        let method = match request.method() {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            _ => "",
+        };
+
+        match self.handle(method, request.url(), token, body) {
+            Ok((status, value)) => (status, value),
+            Err(error) => (error.status(), json!({"error": error.message()})),
+        }
+    }
+
+    fn handle(
+        &self,
+        method: &str,
+        url: &str,
+        token: Option<&str>,
+        body: &str,
+    ) -> Result<(u16, Value), ApiError> {
+        let body: Value = if body.trim().is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_str(body)
+                .map_err(|_| ApiError::BadRequest("invalid JSON".to_string()))?
+        };
+
+        let path: Vec<&str> = url
+            .split('?')
+            .next()
+            .unwrap_or_default()
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        let mut games = self
+            .games
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let ("POST", ["games"]) = (method, path.as_slice()) {
+            return games.create(&body).map(|value| (201, value));
+        }
+
+        let (id, action) = match path.as_slice() {
+            ["games", id, action] => (*id, *action),
+            _ => return Err(ApiError::NotFound),
+        };
+
+        let stored = games.games.get_mut(id).ok_or(ApiError::NotFound)?;
+        if let ("GET", "score") = (method, action) {
+            let game = &stored.game;
+            return Ok((
+                200,
+                json!({
+                    "scores": stored.scores(),
+                    "target": game.board().target(),
+                    "dealer": seat_number(game.dealer()),
+                    "winner": game.winner().map(seat_number),
+                }),
+            ));
+        }
+
+        let seat = stored.seat(token).ok_or(ApiError::Unauthorized)?;
+        match (method, action) {
+            ("GET", "view") => {}
+            ("POST", "discard") => {
+                let cards = body["cards"]
+                    .as_array()
+                    .and_then(|cards| {
+                        cards
+                            .iter()
+                            .map(card_from_json)
+                            .collect::<Option<Vec<Card>>>()
+                    })
+                    .filter(|cards| cards.len() == 2)
+                    .ok_or_else(|| ApiError::BadRequest("expected two cards".to_string()))?;
+                stored.game.discard(seat, [cards[0], cards[1]])?;
+            }
+            ("POST", "play") => {
+                let card = card_from_json(&body["card"])
+                    .ok_or_else(|| ApiError::BadRequest("expected a card".to_string()))?;
+                stored.game.play(seat, card)?;
+            }
+            _ => return Err(ApiError::NotFound),
+        }
+
+        stored.settle()?;
+        Ok((200, stored.view(seat)))
+    }
+}
+
+impl Games {
+    fn create(&mut self, body: &Value) -> Result<Value, ApiError> {
+        let first_dealer = match body["first_dealer"].as_u64() {
+            None | Some(1) => TwoPlayers::PlayerOne,
+            Some(2) => TwoPlayers::PlayerTwo,
+            Some(_) => return Err(ApiError::BadRequest("first_dealer is 1 or 2".to_string())),
+        };
+        let seed = body["seed"].as_u64().unwrap_or_else(|| self.rng.gen());
+        let bot = match body["opponent"].as_str() {
+            Some(name) => Some(
+                Difficulty::from_str(name)
+                    .map_err(|_| ApiError::BadRequest("unknown opponent".to_string()))?
+                    .bot(seed.wrapping_mul(2).wrapping_add(1)),
+            ),
+            None => None,
+        };
+
+        let mut seats = vec![TwoPlayers::PlayerOne];
+        if bot.is_none() {
+            seats.push(TwoPlayers::PlayerTwo);
+        }
+
+        let tokens: Vec<(TwoPlayers, String)> = seats
+            .into_iter()
+            .map(|seat| (seat, format!("{:016x}", self.rng.gen::<u64>())))
+            .collect();
+        let id = format!("{:012x}", self.rng.gen::<u64>() >> 16);
+        let players: Vec<Value> = tokens
+            .iter()
+            .map(|(seat, token)| json!({"seat": seat_number(*seat), "token": token}))
+            .collect();
+
+        let mut stored = StoredGame {
+            game: TwoPlayerGame::with_seed(first_dealer, seed),
+            tokens,
+            bot,
+            observed: 0,
+        };
+        stored.settle()?;
+        self.games.insert(id.clone(), stored);
+        Ok(json!({"id": id, "players": players}))
+    }
+}
+
+fn seat_number(player: TwoPlayers) -> u8 {
+    match player {
+        TwoPlayers::PlayerOne => 1,
+        TwoPlayers::PlayerTwo => 2,
+    }
+}
+
+fn phase_name(phase: GamePhase) -> &'static str {
+    match phase {
+        GamePhase::Discard => "discard",
+        GamePhase::Pegging => "pegging",
+        GamePhase::Show => "show",
+        GamePhase::Finished => "finished",
+    }
+}
+
+fn cards_json(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(Card::to_string).collect()
+}
+
+fn card_from_json(value: &Value) -> Option<Card> {
+    value.as_str().and_then(|s| Card::from_str(s).ok())
+}
+
+// The events from the deal before the current one, which ends with its shows
+fn previous_deal_events(events: &[TableEvent], current: usize) -> &[TableEvent] {
+    let deal_start = events.len() - current;
+    let previous_start = events[..deal_start]
+        .iter()
+        .rposition(|event| matches!(event, TableEvent::DealStarted { .. }))
+        .unwrap_or(deal_start);
+    &events[previous_start..deal_start]
+}
+
+fn event_json(event: &TableEvent) -> Value {
+    match *event {
+        TableEvent::DealStarted { dealer } => {
+            json!({"type": "deal", "dealer": seat_number(dealer)})
+        }
+        TableEvent::Discarded { player } => {
+            json!({"type": "discard", "seat": seat_number(player)})
+        }
+        TableEvent::StarterCut { starter, heels } => {
+            json!({"type": "starter", "card": starter.to_string(), "heels": heels})
+        }
+        TableEvent::Played {
+            player,
+            card,
+            count,
+            points,
+        } => json!({
+            "type": "play",
+            "seat": seat_number(player),
+            "card": card.to_string(),
+            "count": count,
+            "points": points,
+        }),
+        TableEvent::Go { player, .. } => json!({"type": "go", "seat": seat_number(player)}),
+        TableEvent::SequenceEnded { player, points } => {
+            json!({"type": "last", "seat": seat_number(player), "points": points})
+        }
+        TableEvent::Shown {
+            player,
+            cards,
+            is_crib,
+            points,
+        } => json!({
+            "type": "show",
+            "seat": seat_number(player),
+            "cards": cards_json(&cards),
+            "crib": is_crib,
+            "points": points,
+        }),
+        TableEvent::GameWon { winner } => json!({"type": "win", "seat": seat_number(winner)}),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::http::HttpServer;
+    use serde_json::{json, Value};
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpStream};
+    use std::sync::Arc;
+    use std::thread;
+
+    fn request(
+        address: SocketAddr,
+        method: &str,
+        path: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (u16, Value) {
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let token = token
+            .map(|token| format!("X-Player-Token: {}\r\n", token))
+            .unwrap_or_default();
+        let mut stream = TcpStream::connect(address).unwrap();
+        write!(
+            stream,
+            "{} {} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n{}Content-Length: {}\r\n\r\n{}",
+            method,
+            path,
+            token,
+            body.len(),
+            body
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let status = response[9..12].parse().unwrap();
+        let body = &response[response.find("\r\n\r\n").unwrap() + 4..];
+        (status, serde_json::from_str(body).unwrap())
+    }
+
+    fn start_server() -> SocketAddr {
+        let server = Arc::new(HttpServer::bind("127.0.0.1:0", 4).unwrap());
+        let address = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        address
+    }
+
+    #[test]
+    fn test_game_against_bot() {
+        let address = start_server();
+        let (status, created) = request(
+            address,
+            "POST",
+            "/games",
+            None,
+            Some(json!({"seed": 5, "opponent": "medium"})),
+        );
+        assert_eq!(status, 201);
+        assert_eq!(created["players"].as_array().unwrap().len(), 1);
+        let id = created["id"].as_str().unwrap();
+        let token = created["players"][0]["token"].as_str().unwrap();
+        let path = |action: &str| format!("/games/{}/{}", id, action);
+
+        assert_eq!(request(address, "GET", &path("view"), None, None).0, 401);
+        assert_eq!(
+            request(address, "GET", "/games/nope/score", None, None).0,
+            404
+        );
+
+        let (status, mut view) = request(address, "GET", &path("view"), Some(token), None);
+        assert_eq!(status, 200);
+        assert_eq!(view["hand"].as_array().unwrap().len(), 6);
+
+        let (status, error) = request(
+            address,
+            "POST",
+            &path("play"),
+            Some(token),
+            Some(json!({"card": view["hand"][0]})),
+        );
+        assert_eq!((status, error["error"].as_str()), (409, Some("WrongPhase")));
+        assert_eq!(view["previous_deal"], json!([]));
+
+        let mut shows_seen = 0;
+        while view["winner"].is_null() {
+            let (status, next) = if view["phase"] == "discard" {
+                let hand = &view["hand"];
+                let cards = json!([hand[0], hand[1]]);
+                request(
+                    address,
+                    "POST",
+                    &path("discard"),
+                    Some(token),
+                    Some(json!({ "cards": cards })),
+                )
+            } else {
+                assert_eq!(view["phase"], "pegging");
+                assert_eq!(view["turn"], 1);
+                request(
+                    address,
+                    "POST",
+                    &path("play"),
+                    Some(token),
+                    Some(json!({"card": view["legal"][0]})),
+                )
+            };
+
+            assert_eq!(status, 200, "{}", next);
+            view = next;
+
+            let previous = view["previous_deal"].as_array().unwrap();
+            if view["winner"].is_null() && !previous.is_empty() {
+                assert_eq!(previous[0]["type"], "deal");
+                let shows: Vec<&Value> = previous
+                    .iter()
+                    .filter(|event| event["type"] == "show")
+                    .collect();
+                assert_eq!(shows.len(), 3);
+                assert!(shows.iter().any(|show| show["seat"] == 2));
+                assert_eq!(shows[2]["crib"], true);
+                shows_seen += 1;
+            }
+        }
+
+        assert!(shows_seen > 0);
+
+        let (status, score) = request(address, "GET", &path("score"), None, None);
+        assert_eq!(status, 200);
+        assert_eq!(score["winner"], view["winner"]);
+        assert_eq!(score["scores"], view["scores"]);
+        let winner = score["winner"].to_string();
+        assert_eq!(score["scores"][winner.as_str()], 121);
+    }
+
+    #[test]
+    fn test_two_player_tokens() {
+        let address = start_server();
+        let (_, created) = request(
+            address,
+            "POST",
+            "/games",
+            None,
+            Some(json!({"first_dealer": 2})),
+        );
+        let id = created["id"].as_str().unwrap();
+        let one = created["players"][0]["token"].as_str().unwrap();
+        let two = created["players"][1]["token"].as_str().unwrap();
+        let path = format!("/games/{}/view", id);
+
+        let (_, view_one) = request(address, "GET", &path, Some(one), None);
+        let (_, view_two) = request(address, "GET", &path, Some(two), None);
+        assert_eq!(view_one["seat"], 1);
+        assert_eq!(view_two["seat"], 2);
+        assert_eq!(view_one["dealer"], 2);
+        assert_ne!(view_one["hand"], view_two["hand"]);
+
+        let (status, _) = request(
+            address,
+            "POST",
+            &format!("/games/{}/discard", id),
+            Some(one),
+            Some(json!({"cards": ["AS"]})),
+        );
+        assert_eq!(status, 400);
+        assert_eq!(
+            request(
+                address,
+                "POST",
+                "/games",
+                None,
+                Some(json!({"opponent": "wizard"}))
+            )
+            .0,
+            400
+        );
+    }
+}
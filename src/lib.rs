@@ -1,35 +1,88 @@
 use std::error;
 use std::fmt;
 
+mod arena;
 mod board;
+mod bots;
 mod card;
+mod card_set;
 mod deck;
+mod discard;
+mod engine;
+mod game;
 mod hand;
+mod hand_scores;
+#[cfg(feature = "http")]
+mod http;
+mod notation;
 mod pegging;
+mod pegging_analysis;
+mod position;
+#[cfg(feature = "server")]
+mod server;
+mod text;
+mod win_probability;
 
+pub use crate::arena::{run_arena, ArenaConfig, ArenaPlayerStats, ArenaReport};
 pub use crate::board::{
     custom_board, standard_four_player_board, standard_three_player_board,
     standard_two_player_board, Board, FourPlayerScore, FourPlayers, ThreePlayerScore, ThreePlayers,
     TwoPlayerScore, TwoPlayers,
 };
-pub use crate::card::{Card, Rank, Suit};
+pub use crate::bots::{
+    bot_by_name, Difficulty, GreedyBot, IsmctsBot, IsmctsConfig, PositionalBot, RandomBot,
+    StrongBot,
+};
+pub use crate::card::{Card, Rank, Suit, RANKS};
+pub use crate::card_set::{CardSet, CardSetIter};
 pub use crate::deck::Deck;
+pub use crate::discard::{analyze_discards, DiscardOption};
+pub use crate::engine::{run_engine, EngineError, EnginePlayer};
+pub use crate::game::{
+    play_recorded_game, play_two_player_game, replay, ConsolePlayer, GameLogEvent, GamePhase,
+    GameRecorder, GameSnapshotError, Player, PlayerView, ReplayError, Replayer, Situation,
+    SpectatorView, StdinLines, TableEvent, TwoPlayerGame,
+};
 pub use crate::hand::{
-    deal_four_player_hand, deal_three_player_hand, deal_two_player_hand, CribCards,
-    FourPlayerCribPart, FourPlayerDeal, Hand, KeptCards, ThreePlayerCribPart, ThreePlayerDeal,
-    TwoPlayerCribPart, TwoPlayerDeal,
+    deal_four_player_hand, deal_three_player_hand, deal_two_player_hand, score_breakdown,
+    score_hand, score_hands, score_hands_parallel, CribCards, FourPlayerCribPart, FourPlayerDeal,
+    Hand, KeptCards, ScoreBreakdown, ThreePlayerCribPart, ThreePlayerDeal, TwoPlayerCribPart,
+    TwoPlayerDeal,
 };
+pub use crate::hand_scores::{
+    generate_hand_score_directory, generate_hand_scores_for_cut, generate_hand_scores_for_cut_rank,
+    hand_score_file_name, HandScoreEntry, HandScoreReader, HandScoreTable, HandScoreTableError,
+    HandScoreWriter, ParseHandScoreError,
+};
+#[cfg(feature = "http")]
+pub use crate::http::HttpServer;
+pub use crate::notation::{GameRecord, NotationError};
 pub use crate::pegging::{
-    FourCardPegging, OneCardPegging, Pegger, ThreeCardPegging, TwoCardPegging,
+    solve_pegging, FourCardPegging, OneCardPegging, PegOutcome, Pegger, PeggingRound,
+    PeggingSolution, PeggingSolver, SolvedPlay, ThreeCardPegging, TwoCardPegging,
+};
+pub use crate::pegging_analysis::{estimate_pegging, CardEstimate, PeggingBudget, PeggingView};
+pub use crate::position::{
+    rank_discards_by_position, rank_pegging_by_position, DealCards, Position, PositionalDiscard,
+    PositionalPeg, RaceEstimator, WinEstimator,
 };
+#[cfg(feature = "server")]
+pub use crate::server::GameServer;
+pub use crate::win_probability::{DealDistribution, WinProbabilityTable, WinProbabilityTableError};
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum CribbageCoreError {
     InvalidCard,
     InvalidCardString,
+    InvalidPlayerCount,
     InvalidScoreId,
+    MismatchedLength,
     NotEnoughCards,
+    OutOfTurn,
+    PlayerQuit,
+    UnknownBot,
     WinnerExists,
+    WrongPhase,
 }
 
 impl fmt::Display for CribbageCoreError {
@@ -37,9 +90,15 @@ impl fmt::Display for CribbageCoreError {
         match *self {
             CribbageCoreError::InvalidCard
             | CribbageCoreError::InvalidCardString
+            | CribbageCoreError::InvalidPlayerCount
             | CribbageCoreError::InvalidScoreId
+            | CribbageCoreError::MismatchedLength
             | CribbageCoreError::NotEnoughCards
-            | CribbageCoreError::WinnerExists => write!(f, "{:?}", self),
+            | CribbageCoreError::OutOfTurn
+            | CribbageCoreError::PlayerQuit
+            | CribbageCoreError::UnknownBot
+            | CribbageCoreError::WinnerExists
+            | CribbageCoreError::WrongPhase => write!(f, "{:?}", self),
         }
     }
 }
@@ -49,9 +108,15 @@ impl error::Error for CribbageCoreError {
         match *self {
             CribbageCoreError::InvalidCard => "Invalid card played",
             CribbageCoreError::InvalidCardString => "Invalid string representation of card",
+            CribbageCoreError::InvalidPlayerCount => "Unsupported number of players",
             CribbageCoreError::InvalidScoreId => "Invalid score ID",
+            CribbageCoreError::MismatchedLength => "Input and output lengths differ",
             CribbageCoreError::NotEnoughCards => "Not enough cards in deck",
+            CribbageCoreError::OutOfTurn => "Not this player's turn",
+            CribbageCoreError::PlayerQuit => "A player left the game",
+            CribbageCoreError::UnknownBot => "Unknown bot name or difficulty",
             CribbageCoreError::WinnerExists => "Winner already exists",
+            CribbageCoreError::WrongPhase => "Action not allowed in this phase of the game",
         }
     }
 
@@ -59,9 +124,15 @@ impl error::Error for CribbageCoreError {
         match *self {
             CribbageCoreError::InvalidCard
             | CribbageCoreError::InvalidCardString
+            | CribbageCoreError::InvalidPlayerCount
             | CribbageCoreError::InvalidScoreId
+            | CribbageCoreError::MismatchedLength
             | CribbageCoreError::NotEnoughCards
-            | CribbageCoreError::WinnerExists => None,
+            | CribbageCoreError::OutOfTurn
+            | CribbageCoreError::PlayerQuit
+            | CribbageCoreError::UnknownBot
+            | CribbageCoreError::WinnerExists
+            | CribbageCoreError::WrongPhase => None,
         }
     }
 }
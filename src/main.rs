@@ -1,130 +1,563 @@
-use std::io::{stdin, stdout, Write};
+use std::env;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process::{self, Command};
+use std::str::FromStr;
+use std::time::Duration;
 
-use cribbage_core::{deal_two_player_hand, standard_two_player_board, Deck, Pegger};
+use cribbage_core::{
+    analyze_discards, play_two_player_game, run_arena, run_engine, ArenaConfig, Card,
+    ConsolePlayer, CribbageCoreError, Difficulty, DiscardOption, EnginePlayer, Hand, TwoPlayerGame,
+    TwoPlayers,
+};
+
+const USAGE: &str = "Usage: cribbage-core
+       cribbage-core play [--difficulty BOT] [--seed N] [--first-dealer human|bot]
+       cribbage-core score [--crib] --cut CARD CARD CARD CARD CARD
+       cribbage-core score [--crib] [--file PATH]
+       cribbage-core advise (--dealer | --pone) [--samples N] [--seed N] CARD...
+       cribbage-core arena [--games N] [--seed N] [--threads N] [--same-dealer] BOT BOT
+       cribbage-core engine [--seed N] BOT
+       cribbage-core referee [--seed N] [--timeout MS] COMMAND COMMAND
+       cribbage-core serve [--address HOST:PORT] [--seed N]
+       cribbage-core http [--address HOST:PORT] [--seed N]";
 
 pub fn main() {
-    let board = standard_two_player_board();
-    let mut deck = Deck::new();
-    let _pegger = Pegger::new();
+    let args: Vec<String> = env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => play_console_game(),
+        Some("play") => play(&args[1..]),
+        Some("score") => score(&args[1..]),
+        Some("advise") => advise(&args[1..]),
+        Some("arena") => arena(&args[1..]),
+        Some("engine") => engine(&args[1..]),
+        Some("referee") => referee(&args[1..]),
+        Some("serve") => serve(&args[1..]),
+        Some("http") => http(&args[1..]),
+        Some("-h") | Some("--help") => println!("{}", USAGE),
+        Some(arg) => usage_error(&format!("Unknown command: {}", arg)),
+    }
+}
 
-    while board.winner().is_none() {
-        deck.shuffle();
+fn play_console_game() {
+    let mut game = TwoPlayerGame::new(TwoPlayers::PlayerOne);
+    let mut player_one = ConsolePlayer::stdio("Player 1");
+    player_one.set_opponent_name("Player 2");
+    let mut player_two = ConsolePlayer::stdio("Player 2");
+    player_two.set_narrating(false);
 
-        let player_one_deal = deal_two_player_hand(&mut deck).unwrap();
-        let player_two_deal = deal_two_player_hand(&mut deck).unwrap();
+    match play_two_player_game(&mut game, &mut player_one, &mut player_two) {
+        Ok(TwoPlayers::PlayerOne) => println!("Player 1 wins!"),
+        Ok(TwoPlayers::PlayerTwo) => println!("Player 2 wins!"),
+        Err(CribbageCoreError::PlayerQuit) => println!("Game abandoned."),
+        Err(error) => eprintln!("Game ended unexpectedly: {}", error),
+    }
+}
 
-        println!("Player 1 Cards:");
-        for (index, card) in player_one_deal.cards().iter().enumerate() {
-            println!("{}: {:?} ", index, card);
+// One person at the console against a bot, whose cards are only seen once they are shown
+fn play(args: &[String]) {
+    let mut difficulty = Difficulty::Medium;
+    let mut seed = None;
+    let mut human_deals = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--difficulty" => difficulty = parse_value(arg, args.next()),
+            "--seed" => seed = Some(parse_value(arg, args.next())),
+            "--first-dealer" => match args.next().map(String::as_str) {
+                Some("human") => human_deals = Some(true),
+                Some("bot") => human_deals = Some(false),
+                _ => usage_error(&format!("Invalid value for {}", arg)),
+            },
+            _ => usage_error(&format!("Unexpected argument: {}", arg)),
         }
+    }
 
-        print!("Please select 4 cards to keep (by 0 index): ");
-        let _ = stdout().flush();
-        let mut input = String::new();
-        stdin().read_line(&mut input).expect("Error reading input.");
-        let indices: Vec<usize> = input
-            .split_whitespace()
-            .map(|i| i.parse::<usize>().unwrap())
-            .collect();
-        let keep = [
-            player_one_deal.cards()[indices[0]],
-            player_one_deal.cards()[indices[1]],
-            player_one_deal.cards()[indices[2]],
-            player_one_deal.cards()[indices[3]],
-        ];
-
-        print!("Please select 2 cards to move into crib (by 0 index): ");
-        let _ = stdout().flush();
-        let mut input = String::new();
-        stdin().read_line(&mut input).expect("Error reading input.");
-        let indices: Vec<usize> = input
-            .split_whitespace()
-            .map(|i| i.parse::<usize>().unwrap())
-            .collect();
-        let crib = [
-            player_one_deal.cards()[indices[0]],
-            player_one_deal.cards()[indices[1]],
-        ];
+    // Without a seed the game is still reproducible from the one printed here
+    let seed = seed.unwrap_or_else(rand::random::<u64>);
+    let first_dealer = match human_deals.unwrap_or(seed % 2 == 0) {
+        true => TwoPlayers::PlayerOne,
+        false => TwoPlayers::PlayerTwo,
+    };
+    println!("Playing the {} bot with seed {}", difficulty, seed);
 
-        let (player_one_kept, player_one_crib) = player_one_deal.split(keep, crib);
+    let mut game = TwoPlayerGame::with_seed(first_dealer, seed);
+    let mut human = ConsolePlayer::stdio("Player 1");
+    // Seeded apart from the deck, as in the arena, so the bot's sampling never mirrors the deal
+    let mut bot = difficulty.bot(seed.wrapping_mul(2).wrapping_add(1));
+    match play_two_player_game(&mut game, &mut human, bot.as_mut()) {
+        Ok(TwoPlayers::PlayerOne) => println!("You win!"),
+        Ok(TwoPlayers::PlayerTwo) => println!("The {} bot wins.", difficulty),
+        Err(CribbageCoreError::PlayerQuit) => println!("Game abandoned."),
+        Err(error) => eprintln!("Game ended unexpectedly: {}", error),
+    }
+}
 
-        println!("Player 2 Cards:");
-        for (index, card) in player_two_deal.cards().iter().enumerate() {
-            println!("{}: {:?} ", index, card);
+// Scores the hand given on the command line, or else one hand per line of the file or stdin, with
+// each line written the same way as the arguments
+fn score(args: &[String]) {
+    let mut path = None;
+    let mut is_crib = false;
+    let mut words = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--crib" => is_crib = true,
+            "--file" => match args.next() {
+                Some(value) => path = Some(value),
+                None => usage_error(&format!("Invalid value for {}", arg)),
+            },
+            _ => words.push(arg.as_str()),
         }
+    }
 
-        print!("Please select 4 cards to keep (by 0 index): ");
-        let _ = stdout().flush();
-        let mut input = String::new();
-        stdin().read_line(&mut input).expect("Error reading input.");
-        let indices: Vec<usize> = input
-            .split_whitespace()
-            .map(|i| i.parse::<usize>().unwrap())
-            .collect();
-        let keep = [
-            player_two_deal.cards()[indices[0]],
-            player_two_deal.cards()[indices[1]],
-            player_two_deal.cards()[indices[2]],
-            player_two_deal.cards()[indices[3]],
-        ];
-
-        print!("Please select 2 cards to move into crib (by 0 index): ");
-        let _ = stdout().flush();
-        let mut input = String::new();
-        stdin().read_line(&mut input).expect("Error reading input.");
-        let indices: Vec<usize> = input
-            .split_whitespace()
-            .map(|i| i.parse::<usize>().unwrap())
-            .collect();
-        let crib = [
-            player_two_deal.cards()[indices[0]],
-            player_two_deal.cards()[indices[1]],
-        ];
-        let (player_two_kept, player_two_crib) = player_two_deal.split(keep, crib);
+    if !words.is_empty() {
+        if path.is_some() {
+            usage_error("Give either a hand or --file, not both");
+        }
+
+        match parse_hand(&words, is_crib) {
+            Ok(mut hand) => println!("{}", format_score(&mut hand)),
+            Err(message) => usage_error(&message),
+        }
+
+        return;
+    }
+
+    let input: Box<dyn BufRead> = match path {
+        Some(path) => match File::open(path) {
+            Ok(file) => Box::new(BufReader::new(file)),
+            Err(error) => {
+                eprintln!("Could not open {}: {}", path, error);
+                process::exit(1);
+            }
+        },
+        None => Box::new(io::stdin().lock()),
+    };
+
+    let mut failed = false;
+    for (number, line) in input.lines().enumerate() {
+        let line = match line {
+            Ok(line) => line,
+            Err(error) => {
+                eprintln!("Could not read line {}: {}", number + 1, error);
+                process::exit(1);
+            }
+        };
+
+        match score_line(&line, is_crib) {
+            Some(Ok(score)) => println!("{}", score),
+            Some(Err(message)) => {
+                eprintln!("Line {}: {}", number + 1, message);
+                failed = true;
+            }
+            None => {}
+        }
+    }
+
+    if failed {
+        process::exit(1);
+    }
+}
+
+// None for a blank line or a comment starting with #. A line scores as a crib when `is_crib` is
+// set or the line has its own --crib
+fn score_line(line: &str, is_crib: bool) -> Option<Result<String, String>> {
+    let words: Vec<&str> = line.split_whitespace().collect();
+    if words.is_empty() || words[0].starts_with('#') {
+        return None;
+    }
+
+    Some(parse_hand(&words, is_crib).map(|mut hand| format_score(&mut hand)))
+}
+
+fn parse_hand(words: &[&str], mut is_crib: bool) -> Result<Hand, String> {
+    let mut cards = Vec::new();
+    let mut cut = None;
+    let mut words = words.iter();
+    while let Some(&word) = words.next() {
+        match word {
+            "--crib" => is_crib = true,
+            "--cut" => match words.next() {
+                Some(word) => cut = Some(parse_card(word)?),
+                None => return Err("--cut needs a card".to_string()),
+            },
+            _ => cards.push(parse_card(word)?),
+        }
+    }
+
+    let cut = cut.ok_or_else(|| "The cut card is missing".to_string())?;
+    let cards = match cards.as_slice() {
+        &[a, b, c, d] => [a, b, c, d],
+        _ => return Err(format!("A hand needs 4 cards, not {}", cards.len())),
+    };
+
+    let hand = Hand::new(cards, cut, is_crib);
+    if hand.card_set().len() != 5 {
+        return Err("A card appears twice".to_string());
+    }
+
+    Ok(hand)
+}
+
+fn parse_card(word: &str) -> Result<Card, String> {
+    Card::from_str(word).map_err(|_| format!("Invalid card: {}", word))
+}
+
+fn format_score(hand: &mut Hand) -> String {
+    let cards: Vec<String> = hand.cards().iter().map(Card::to_string).collect();
+    let breakdown = hand.breakdown();
+    format!(
+        "{} + {}{}: {} (fifteens {}, pairs {}, runs {}, flush {}, nobs {})",
+        cards.join(" "),
+        hand.cut(),
+        if hand.is_crib() { " crib" } else { "" },
+        hand.score(),
+        breakdown.fifteens,
+        breakdown.pairs,
+        breakdown.runs,
+        breakdown.flush,
+        breakdown.nobs
+    )
+}
+
+// Ranks every keep of a six or five card deal by net expected points; the crib is estimated from
+// sampled throws by the other players
+fn advise(args: &[String]) {
+    let mut is_dealer = None;
+    let mut samples = 1000;
+    let mut seed = 0;
+    let mut cards = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dealer" => is_dealer = Some(true),
+            "--pone" => is_dealer = Some(false),
+            "--samples" => samples = parse_value(arg, args.next()),
+            "--seed" => seed = parse_value(arg, args.next()),
+            _ => match parse_card(arg) {
+                Ok(card) => cards.push(card),
+                Err(message) => usage_error(&message),
+            },
+        }
+    }
+
+    let is_dealer = is_dealer.unwrap_or_else(|| usage_error("Give either --dealer or --pone"));
+    let options = match analyze_discards(&cards, is_dealer, samples, seed) {
+        Ok(options) => options,
+        Err(CribbageCoreError::InvalidCard) => usage_error("A card appears twice"),
+        Err(_) => usage_error("Give six cards, or five for three or four players"),
+    };
+
+    for line in format_advice(&options, is_dealer) {
+        println!("{}", line);
+    }
+}
 
-        let crib = player_one_crib.combine(player_two_crib);
+// A header and one row per option, in the order given
+fn format_advice(options: &[DiscardOption], is_dealer: bool) -> Vec<String> {
+    let format_cards = |cards: &[Card]| {
+        cards
+            .iter()
+            .map(Card::to_string)
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let mut lines =
+        vec!["Rank  Keep         Throw    Hand    Crib     Net  Best cut   Worst cut".to_string()];
+    for (rank, option) in options.iter().enumerate() {
+        let best_cut = format!("{} ({})", option.best_cut.0, option.best_cut.1);
+        let crib_ev = if is_dealer {
+            option.crib_ev
+        } else {
+            -option.crib_ev
+        };
+        lines.push(format!(
+            "{:>4}  {:<11}  {:<5}  {:>6.2}  {:>6.2}  {:>6.2}  {:<9}  {} ({})",
+            rank + 1,
+            format_cards(&option.keep),
+            format_cards(&option.discard),
+            option.hand_ev,
+            crib_ev,
+            option.net(is_dealer),
+            best_cut,
+            option.worst_cut.0,
+            option.worst_cut.1
+        ));
+    }
+
+    lines
+}
 
-        print!("Player one kept: ");
-        for card in player_one_kept.cards() {
-            print!("{:?} ", card);
+fn arena(args: &[String]) {
+    let mut config = ArenaConfig::default();
+    let mut bots = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => config.games = parse_value(arg, args.next()),
+            "--seed" => config.seed = parse_value(arg, args.next()),
+            "--threads" => config.threads = parse_value(arg, args.next()),
+            "--same-dealer" => config.alternate_dealer = false,
+            _ => match Difficulty::from_str(arg) {
+                Ok(difficulty) => bots.push(difficulty),
+                Err(_) => usage_error(&format!("Unknown bot: {}", arg)),
+            },
         }
-        println!();
+    }
 
-        print!("Player two kept: ");
-        for card in player_two_kept.cards() {
-            print!("{:?} ", card);
+    let (one, two) = match bots.as_slice() {
+        [one, two] => (*one, *two),
+        _ => usage_error("The arena needs exactly two bots"),
+    };
+
+    println!("Player 1: {}, Player 2: {}", one, two);
+    match run_arena(&config, |seed| one.bot(seed), |seed| two.bot(seed)) {
+        Ok(report) => print!("{}", report),
+        Err(error) => {
+            eprintln!("Arena stopped unexpectedly: {}", error);
+            process::exit(1);
         }
-        println!();
+    }
+}
 
-        print!("Crib: ");
-        for card in crib.cards() {
-            print!("{:?} ", card);
+fn engine(args: &[String]) {
+    let mut seed = 0;
+    let mut bot = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = parse_value(arg, args.next()),
+            _ => match Difficulty::from_str(arg) {
+                Ok(difficulty) if bot.is_none() => bot = Some(difficulty),
+                _ => usage_error(&format!("Unexpected argument: {}", arg)),
+            },
         }
-        println!();
+    }
 
-        let cut = deck.draw().unwrap();
-        println!("Cut card: {:?}", cut);
+    let difficulty = bot.unwrap_or_else(|| usage_error("The engine needs a bot"));
+    let name = format!("cribbage-core {}", difficulty);
+    let stdin = io::stdin();
+    if let Err(error) = run_engine(
+        difficulty.bot(seed).as_mut(),
+        &name,
+        stdin.lock(),
+        io::stdout(),
+    ) {
+        eprintln!("Engine stopped unexpectedly: {}", error);
+        process::exit(1);
+    }
+}
 
-        let mut player_one_hand = player_one_kept.add_cut_card(cut);
-        let mut player_two_hand = player_two_kept.add_cut_card(cut);
-        let mut crib = crib.add_cut_card(cut);
+fn referee(args: &[String]) {
+    let mut seed = 0;
+    let mut timeout = 5000;
+    let mut commands = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = parse_value(arg, args.next()),
+            "--timeout" => timeout = parse_value(arg, args.next()),
+            _ => commands.push(arg),
+        }
+    }
 
-        print!("Player one hand: ");
-        for card in player_one_hand.cards() {
-            print!("{:?} ", card);
+    if commands.len() != 2 {
+        usage_error("The referee needs exactly two engine commands");
+    }
+
+    let mut engines = Vec::new();
+    for command in commands {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .unwrap_or_else(|| usage_error("Empty engine command"));
+        let mut command = Command::new(program);
+        command.args(words);
+        match EnginePlayer::spawn(&mut command, Duration::from_millis(timeout)) {
+            Ok(engine) => engines.push(engine),
+            Err(error) => {
+                eprintln!("Unable to start {}: {}", program, error);
+                process::exit(1);
+            }
+        }
+    }
+
+    let mut player_two = engines.pop().unwrap();
+    let mut player_one = engines.pop().unwrap();
+    let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, seed);
+    let result = play_two_player_game(&mut game, &mut player_one, &mut player_two);
+    for (seat, engine) in &[(1, &player_one), (2, &player_two)] {
+        for fault in engine.faults() {
+            eprintln!("Player {} ({}): {}", seat, engine.name(), fault);
         }
-        println!("Player one hand points: {}", &player_one_hand.score());
+    }
 
-        print!("Player two hand: ");
-        for card in player_two_hand.cards() {
-            print!("{:?} ", card);
+    match result {
+        Ok(winner) => println!(
+            "Player {} wins {}-{}",
+            if winner == TwoPlayers::PlayerOne {
+                1
+            } else {
+                2
+            },
+            game.score(winner),
+            game.score(winner.other())
+        ),
+        Err(error) => {
+            eprintln!("Game ended unexpectedly: {}", error);
+            process::exit(1);
         }
-        println!("Player two hand points: {}", player_two_hand.score());
+    }
+}
 
-        print!("Crib: ");
-        for card in crib.cards() {
-            print!("{:?} ", card);
+#[cfg(feature = "server")]
+fn serve(args: &[String]) {
+    let mut address = "0.0.0.0:7121".to_string();
+    let mut seed = rand::random();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--address" => address = parse_value(arg, args.next()),
+            "--seed" => seed = parse_value(arg, args.next()),
+            _ => usage_error(&format!("Unexpected argument: {}", arg)),
         }
-        println!("Crib points: {}", crib.score());
+    }
+
+    let result = cribbage_core::GameServer::bind(&address, seed).and_then(|server| {
+        println!("Serving cribbage tables on {}", server.local_addr()?);
+        server.run()
+    });
+    if let Err(error) = result {
+        eprintln!("Server stopped: {}", error);
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "server"))]
+fn serve(_args: &[String]) {
+    usage_error("This build does not include the server feature");
+}
+
+#[cfg(feature = "http")]
+fn http(args: &[String]) {
+    let mut address = "0.0.0.0:8121".to_string();
+    let mut seed = rand::random();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--address" => address = parse_value(arg, args.next()),
+            "--seed" => seed = parse_value(arg, args.next()),
+            _ => usage_error(&format!("Unexpected argument: {}", arg)),
+        }
+    }
+
+    let result = cribbage_core::HttpServer::bind(&address, seed).and_then(|server| {
+        if let Some(address) = server.local_addr() {
+            println!("Serving the HTTP API on {}", address);
+        }
+        server.run()
+    });
+    if let Err(error) = result {
+        eprintln!("Server stopped: {}", error);
+        process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "http"))]
+fn http(_args: &[String]) {
+    usage_error("This build does not include the http feature");
+}
+
+fn parse_value<T: FromStr>(flag: &str, value: Option<&String>) -> T {
+    match value.and_then(|value| value.parse().ok()) {
+        Some(value) => value,
+        None => usage_error(&format!("Invalid value for {}", flag)),
+    }
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(2);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{format_advice, score_line};
+    use cribbage_core::{analyze_discards, Card};
+    use std::str::FromStr;
+
+    #[test]
+    fn test_score_line() {
+        assert_eq!(
+            score_line("5h 5c 5s jd --cut 5d", false),
+            Some(Ok(
+                "5H 5C 5S JD + 5D: 29 (fifteens 16, pairs 12, runs 0, flush 0, nobs 1)".to_string()
+            ))
+        );
+        assert_eq!(
+            score_line("2H 4H 6H 8H --cut KS --crib", false),
+            Some(Ok(
+                "2H 4H 6H 8H + KS crib: 0 (fifteens 0, pairs 0, runs 0, flush 0, nobs 0)"
+                    .to_string()
+            ))
+        );
+        assert_eq!(
+            score_line("2H 4H 6H 8H --cut KS", false).map(|score| score.unwrap().contains(": 4 (")),
+            Some(true)
+        );
+
+        // --crib before a batch applies to every line
+        assert_eq!(
+            score_line("2H 4H 6H 8H --cut KS", true),
+            score_line("2H 4H 6H 8H --cut KS --crib", false)
+        );
+        assert_eq!(
+            score_line("2H 4H 6H 8H --cut KS --crib", true),
+            score_line("2H 4H 6H 8H --cut KS --crib", false)
+        );
+
+        assert_eq!(score_line("# hands from the last game", false), None);
+        assert_eq!(score_line("   ", false), None);
+        assert_eq!(
+            score_line("5H 5C 5S JD", false),
+            Some(Err("The cut card is missing".to_string()))
+        );
+        assert_eq!(
+            score_line("5H 5C 5S JD --cut 5H", false),
+            Some(Err("A card appears twice".to_string()))
+        );
+        assert_eq!(
+            score_line("5H 5C 5S --cut 5D", false),
+            Some(Err("A hand needs 4 cards, not 3".to_string()))
+        );
+        assert_eq!(
+            score_line("5H 5C 5S XX --cut 5D", false),
+            Some(Err("Invalid card: XX".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_advice_table_order() {
+        let cards: Vec<_> = "5H 5C 5S JD 2C 9S"
+            .split(' ')
+            .map(|card| Card::from_str(card).unwrap())
+            .collect();
+        let options = analyze_discards(&cards, true, 50, 1).unwrap();
+        let lines = format_advice(&options, true);
+        assert_eq!(lines.len(), 16);
+        assert!(lines[0].starts_with("Rank  Keep"));
+        assert!(lines[1].starts_with("   1  5H 5C 5S JD  2C 9S"));
+
+        let nets: Vec<f64> = lines[1..]
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                let fields: Vec<&str> = line.split_whitespace().collect();
+                assert_eq!(fields[0], (index + 1).to_string());
+                fields[9].parse().unwrap()
+            })
+            .collect();
+        assert!(nets.windows(2).all(|pair| pair[0] >= pair[1]));
     }
 }
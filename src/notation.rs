@@ -0,0 +1,798 @@
+// A plain-text record of a two-player game, in the spirit of chess's PGN. A header of tags is
+// followed by one section per deal:
+//
+//   [PlayerOne "Alice"]
+//   [PlayerTwo "Bob"]
+//   [Date "2026-10-19"]
+//   [Rules "two-player 121"]
+//   [Seed "42"]
+//   [FirstDealer "1"]
+//
+//   Deal 1 dealer 1
+//   Hand 2: 5H 5C 5S JD QS KH
+//   Hand 1: AS 2S 3S 4S 9D TC
+//   Discard 2: QS KH
+//   Discard 1: 9D TC
+//   Starter: JH +2
+//   Pegging: 2:5H/5 1:AS/6 2:5C/10+2 ... go:1/28 last:2+1 ...
+//   Show 2: 5H 5C 5S JD = 29
+//   Show 1: AS 2S 3S 4S = 9 claimed 8
+//   Crib 1: QS KH 9D TC = 2
+//   Board: 31 13
+//   Winner: 1
+//
+// Pegging tokens are <seat>:<card>/<count>, followed by +<points> when the card scored. The board
+// line gives both totals after the deal. Players and date are optional; the other tags are
+// required. `Scored` events aren't written out; the parser rebuilds them from the points
+
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+use crate::board::TwoPlayers;
+use crate::card::Card;
+use crate::game::GameLogEvent;
+use crate::text::{format_cards, parse_seat, seat};
+
+const RULES: &str = "two-player";
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct NotationError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Display for NotationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl error::Error for NotationError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameRecord {
+    pub player_one: Option<String>,
+    pub player_two: Option<String>,
+    pub date: Option<String>,
+    pub log: Vec<GameLogEvent>,
+}
+
+impl GameRecord {
+    pub fn new(log: Vec<GameLogEvent>) -> GameRecord {
+        GameRecord {
+            player_one: None,
+            player_two: None,
+            date: None,
+            log,
+        }
+    }
+}
+
+impl Display for GameRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let tags = [
+            ("PlayerOne", &self.player_one),
+            ("PlayerTwo", &self.player_two),
+            ("Date", &self.date),
+        ];
+        for (name, value) in &tags {
+            if let Some(value) = value {
+                writeln!(f, "[{} \"{}\"]", name, escape(value))?;
+            }
+        }
+
+        let mut writer = DealWriter {
+            totals: [0, 0],
+            pegging: Vec::new(),
+            board_pending: false,
+            deals: 0,
+        };
+        for event in &self.log {
+            writer.write(f, event)?;
+        }
+
+        writer.end_deal(f)
+    }
+}
+
+// Writes the deal sections, collecting pegging into one line and closing each deal with the board
+struct DealWriter {
+    totals: [u8; 2],
+    pegging: Vec<String>,
+    board_pending: bool,
+    deals: usize,
+}
+
+impl DealWriter {
+    fn write(&mut self, f: &mut Formatter<'_>, event: &GameLogEvent) -> fmt::Result {
+        let token = match *event {
+            GameLogEvent::Played {
+                player,
+                card,
+                count,
+                points,
+            } => Some(format!(
+                "{}:{}/{}{}",
+                seat(player),
+                card,
+                count,
+                bonus(points)
+            )),
+            GameLogEvent::Go { player, count } => Some(format!("go:{}/{}", seat(player), count)),
+            GameLogEvent::SequenceEnded { player, points } => {
+                Some(format!("last:{}{}", seat(player), bonus(points)))
+            }
+            _ => None,
+        };
+
+        if let Some(token) = token {
+            self.pegging.push(token);
+            return Ok(());
+        }
+
+        if let GameLogEvent::Scored { player, total, .. } = *event {
+            self.totals[index(player)] = total;
+            return Ok(());
+        }
+
+        self.flush_pegging(f)?;
+        match *event {
+            GameLogEvent::Started {
+                seed,
+                first_dealer,
+                target,
+            } => {
+                writeln!(f, "[Rules \"{} {}\"]", RULES, target)?;
+                writeln!(f, "[Seed \"{}\"]", seed)?;
+                writeln!(f, "[FirstDealer \"{}\"]", seat(first_dealer))
+            }
+            GameLogEvent::DealStarted { dealer } => {
+                self.end_deal(f)?;
+                self.deals += 1;
+                self.board_pending = true;
+                writeln!(f)?;
+                writeln!(f, "Deal {} dealer {}", self.deals, seat(dealer))
+            }
+            GameLogEvent::Dealt { player, cards } => {
+                writeln!(f, "Hand {}: {}", seat(player), format_cards(&cards))
+            }
+            GameLogEvent::Discarded { player, cards } => {
+                writeln!(f, "Discard {}: {}", seat(player), format_cards(&cards))
+            }
+            GameLogEvent::StarterCut { starter, heels } => {
+                write!(f, "Starter: {}", starter)?;
+                if heels > 0 {
+                    write!(f, " +{}", heels)?;
+                }
+
+                writeln!(f)
+            }
+            GameLogEvent::Shown {
+                player,
+                cards,
+                is_crib,
+                score,
+                points,
+            } => {
+                let kind = if is_crib { "Crib" } else { "Show" };
+                write!(
+                    f,
+                    "{} {}: {} = {}",
+                    kind,
+                    seat(player),
+                    format_cards(&cards),
+                    score
+                )?;
+                if points != score {
+                    write!(f, " claimed {}", points)?;
+                }
+
+                writeln!(f)
+            }
+            GameLogEvent::GameWon { winner } => {
+                self.end_deal(f)?;
+                writeln!(f, "Winner: {}", seat(winner))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn flush_pegging(&mut self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.pegging.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(f, "Pegging: {}", self.pegging.join(" "))?;
+        self.pegging.clear();
+        Ok(())
+    }
+
+    fn end_deal(&mut self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.flush_pegging(f)?;
+        if self.board_pending {
+            self.board_pending = false;
+            writeln!(f, "Board: {} {}", self.totals[0], self.totals[1])?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = NotationError;
+
+    fn from_str(s: &str) -> Result<GameRecord, NotationError> {
+        let mut parser = Parser {
+            record: GameRecord::new(Vec::new()),
+            tags: Tags::default(),
+            totals: [0, 0],
+            target: 0,
+            deals: 0,
+            in_deal: false,
+            finished: false,
+        };
+
+        for (number, line) in s.lines().enumerate() {
+            let tokens = tokens(line);
+            if tokens.is_empty() {
+                continue;
+            }
+
+            parser.line(number + 1, line, &tokens)?;
+        }
+
+        let line = s.lines().count().max(1);
+        parser.finish(line)
+    }
+}
+
+#[derive(Default)]
+struct Tags {
+    rules: Option<u8>,
+    seed: Option<u64>,
+    first_dealer: Option<TwoPlayers>,
+}
+
+struct Parser {
+    record: GameRecord,
+    tags: Tags,
+    totals: [u8; 2],
+    target: u8,
+    deals: usize,
+    in_deal: bool,
+    finished: bool,
+}
+
+// A word of a line with the column it starts at, counting from one
+#[derive(Clone, Copy, Debug)]
+struct Token<'a> {
+    column: usize,
+    text: &'a str,
+}
+
+fn tokens(line: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (column, (offset, c)) in line.char_indices().enumerate() {
+        match (c.is_whitespace(), start) {
+            (false, None) => start = Some((column + 1, offset)),
+            (true, Some((column, begin))) => {
+                tokens.push(Token {
+                    column,
+                    text: &line[begin..offset],
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+
+    if let Some((column, begin)) = start {
+        tokens.push(Token {
+            column,
+            text: &line[begin..],
+        });
+    }
+
+    tokens
+}
+
+impl Parser {
+    fn line(&mut self, line: usize, text: &str, tokens: &[Token<'_>]) -> Result<(), NotationError> {
+        let error = |token: &Token<'_>, message: &str| NotationError {
+            line,
+            column: token.column,
+            message: message.to_string(),
+        };
+
+        let first = &tokens[0];
+        if self.finished {
+            return Err(error(first, "nothing may follow the winner"));
+        }
+
+        if first.text.starts_with('[') {
+            if self.record.log.is_empty() {
+                return self.tag(line, text);
+            }
+
+            return Err(error(first, "tags must come before the first deal"));
+        }
+
+        if self.record.log.is_empty() {
+            self.start(line, first.column)?;
+        }
+
+        let (keyword, rest) = (first.text, &tokens[1..]);
+        if keyword != "Deal" && keyword != "Winner:" && !self.in_deal {
+            return Err(error(first, "expected a deal"));
+        }
+
+        match keyword {
+            "Deal" => match rest {
+                [number, dealer_word, dealer] if dealer_word.text == "dealer" => {
+                    if number.text.parse() != Ok(self.deals + 1) {
+                        return Err(error(number, "deals must be numbered in order"));
+                    }
+
+                    if self.in_deal {
+                        return Err(error(first, "expected a board line before the next deal"));
+                    }
+
+                    let dealer =
+                        parse_seat(dealer.text).ok_or_else(|| error(dealer, "bad seat"))?;
+                    self.deals += 1;
+                    self.in_deal = true;
+                    self.push(GameLogEvent::DealStarted { dealer });
+                }
+                _ => return Err(error(first, "expected Deal <number> dealer <seat>")),
+            },
+            "Hand" | "Discard" => {
+                let (player, cards) = match rest.split_first() {
+                    Some((seat, cards)) => (self.seat_label(line, seat)?, cards),
+                    None => return Err(error(first, "expected a seat")),
+                };
+                let cards = parse_cards(line, cards)?;
+                let event = match (keyword, cards.as_slice()) {
+                    ("Hand", &[a, b, c, d, e, f]) => GameLogEvent::Dealt {
+                        player,
+                        cards: [a, b, c, d, e, f],
+                    },
+                    ("Discard", &[a, b]) => GameLogEvent::Discarded {
+                        player,
+                        cards: [a, b],
+                    },
+                    _ => return Err(error(first, "wrong number of cards")),
+                };
+                self.push(event);
+            }
+            "Starter:" => {
+                let (starter, heels) = match rest {
+                    [card] => (card, 0),
+                    [card, heels] => (card, points(line, heels)?),
+                    _ => return Err(error(first, "expected Starter: <card> [+<points>]")),
+                };
+                let starter = parse_card(line, starter)?;
+                self.push(GameLogEvent::StarterCut { starter, heels });
+                self.score(self.dealer(), heels);
+            }
+            "Pegging:" => {
+                for token in rest {
+                    self.peg(line, token)?;
+                }
+            }
+            "Show" | "Crib" => self.show(line, keyword == "Crib", tokens)?,
+            "Board:" => match rest {
+                [one, two] => {
+                    for (token, &total) in [one, two].iter().zip(&self.totals) {
+                        if token.text.parse() != Ok(total) {
+                            return Err(error(token, &format!("the board shows {}", total)));
+                        }
+                    }
+
+                    self.in_deal = false;
+                }
+                _ => return Err(error(first, "expected Board: <total> <total>")),
+            },
+            "Winner:" => match rest {
+                [winner] => {
+                    let winner =
+                        parse_seat(winner.text).ok_or_else(|| error(winner, "bad seat"))?;
+                    if self.in_deal {
+                        return Err(error(first, "expected a board line before the winner"));
+                    }
+
+                    self.push(GameLogEvent::GameWon { winner });
+                    self.finished = true;
+                }
+                _ => return Err(error(first, "expected Winner: <seat>")),
+            },
+            _ => return Err(error(first, "unknown line")),
+        }
+
+        Ok(())
+    }
+
+    fn tag(&mut self, line: usize, text: &str) -> Result<(), NotationError> {
+        let column = |offset: usize| text[..offset].chars().count() + 1;
+        let error = |offset: usize, message: &str| NotationError {
+            line,
+            column: column(offset),
+            message: message.to_string(),
+        };
+
+        let indent = text.len() - text.trim_start().len();
+        let inner = match text.trim().strip_suffix(']') {
+            Some(inner) => &inner[1..],
+            None => return Err(error(text.trim_end().len(), "expected ]")),
+        };
+        let name_end = inner.find(char::is_whitespace).unwrap_or(inner.len());
+        let name = &inner[..name_end];
+        let quoted = inner[name_end..].trim_start();
+        let value_offset = indent + 1 + inner.len() - quoted.len();
+        let value =
+            unquote(quoted).ok_or_else(|| error(value_offset, "expected a quoted value"))?;
+
+        match name {
+            "PlayerOne" => self.record.player_one = Some(value),
+            "PlayerTwo" => self.record.player_two = Some(value),
+            "Date" => self.record.date = Some(value),
+            "Rules" => {
+                let target = match value.split_whitespace().collect::<Vec<&str>>().as_slice() {
+                    [rules, target] if *rules == RULES => target.parse().ok(),
+                    _ => None,
+                };
+                self.tags.rules =
+                    Some(target.ok_or_else(|| error(value_offset, "unsupported rules"))?);
+            }
+            "Seed" => {
+                self.tags.seed = Some(value.parse().map_err(|_| error(value_offset, "bad seed"))?);
+            }
+            "FirstDealer" => {
+                self.tags.first_dealer =
+                    Some(parse_seat(&value).ok_or_else(|| error(value_offset, "bad seat"))?);
+            }
+            _ => return Err(error(indent + 1, "unknown tag")),
+        }
+
+        Ok(())
+    }
+
+    fn start(&mut self, line: usize, column: usize) -> Result<(), NotationError> {
+        let (target, seed, first_dealer) =
+            match (self.tags.rules, self.tags.seed, self.tags.first_dealer) {
+                (Some(target), Some(seed), Some(first_dealer)) => (target, seed, first_dealer),
+                _ => {
+                    return Err(NotationError {
+                        line,
+                        column,
+                        message: "the Rules, Seed and FirstDealer tags are required".to_string(),
+                    })
+                }
+            };
+
+        self.target = target;
+        self.record.log.push(GameLogEvent::Started {
+            seed,
+            first_dealer,
+            target,
+        });
+        Ok(())
+    }
+
+    fn finish(mut self, line: usize) -> Result<GameRecord, NotationError> {
+        if self.record.log.is_empty() {
+            self.start(line, 1)?;
+        }
+
+        if self.in_deal {
+            return Err(NotationError {
+                line,
+                column: 1,
+                message: "the last deal has no board line".to_string(),
+            });
+        }
+
+        Ok(self.record)
+    }
+
+    fn push(&mut self, event: GameLogEvent) {
+        self.record.log.push(event);
+    }
+
+    fn score(&mut self, player: TwoPlayers, points: u8) {
+        if points == 0 {
+            return;
+        }
+
+        let total = &mut self.totals[index(player)];
+        *total = total.saturating_add(points).min(self.target);
+        let total = *total;
+        self.push(GameLogEvent::Scored {
+            player,
+            points,
+            total,
+        });
+    }
+
+    fn dealer(&self) -> TwoPlayers {
+        self.record
+            .log
+            .iter()
+            .rev()
+            .find_map(|event| match *event {
+                GameLogEvent::DealStarted { dealer } => Some(dealer),
+                _ => None,
+            })
+            .unwrap_or(TwoPlayers::PlayerOne)
+    }
+
+    fn seat_label(&self, line: usize, token: &Token<'_>) -> Result<TwoPlayers, NotationError> {
+        token
+            .text
+            .strip_suffix(':')
+            .and_then(parse_seat)
+            .ok_or_else(|| NotationError {
+                line,
+                column: token.column,
+                message: "expected <seat>:".to_string(),
+            })
+    }
+
+    fn peg(&mut self, line: usize, token: &Token<'_>) -> Result<(), NotationError> {
+        let error = || {
+            NotationError {
+            line,
+            column: token.column,
+            message: "expected <seat>:<card>/<count>[+<points>], go:<seat>/<count> or last:<seat>+<points>"
+                .to_string(),
+        }
+        };
+
+        let (head, points) = match token.text.split_once('+') {
+            Some((head, points)) => (head, points.parse::<u8>().map_err(|_| error())?),
+            None => (token.text, 0),
+        };
+        let (who, what) = head.split_once(':').ok_or_else(error)?;
+        let event = match who {
+            "go" => {
+                let (player, count) = what.split_once('/').ok_or_else(error)?;
+                GameLogEvent::Go {
+                    player: parse_seat(player).ok_or_else(error)?,
+                    count: count.parse().map_err(|_| error())?,
+                }
+            }
+            "last" => GameLogEvent::SequenceEnded {
+                player: parse_seat(what).ok_or_else(error)?,
+                points,
+            },
+            _ => {
+                let (card, count) = what.split_once('/').ok_or_else(error)?;
+                GameLogEvent::Played {
+                    player: parse_seat(who).ok_or_else(error)?,
+                    card: Card::from_str(card).map_err(|_| error())?,
+                    count: count.parse().map_err(|_| error())?,
+                    points,
+                }
+            }
+        };
+
+        self.push(event);
+        match event {
+            GameLogEvent::Played { player, points, .. }
+            | GameLogEvent::SequenceEnded { player, points } => self.score(player, points),
+            _ => {}
+        }
+
+        Ok(())
+    }
+
+    fn show(
+        &mut self,
+        line: usize,
+        is_crib: bool,
+        tokens: &[Token<'_>],
+    ) -> Result<(), NotationError> {
+        let error = |token: &Token<'_>, message: &str| NotationError {
+            line,
+            column: token.column,
+            message: message.to_string(),
+        };
+
+        let player = match tokens.get(1) {
+            Some(token) => self.seat_label(line, token)?,
+            None => return Err(error(&tokens[0], "expected a seat")),
+        };
+        let (cards, score, claimed) = match &tokens[2..] {
+            [a, b, c, d, equals, score] if equals.text == "=" => ([a, b, c, d], score, None),
+            [a, b, c, d, equals, score, word, claimed]
+                if equals.text == "=" && word.text == "claimed" =>
+            {
+                ([a, b, c, d], score, Some(claimed))
+            }
+            _ => return Err(error(&tokens[0], "expected four cards, = and a score")),
+        };
+
+        let cards: Vec<Token<'_>> = cards.iter().map(|&&token| token).collect();
+        let cards = parse_cards(line, &cards)?;
+        let score = number(line, score)?;
+        let points = match claimed {
+            Some(claimed) => number(line, claimed)?,
+            None => score,
+        };
+
+        self.push(GameLogEvent::Shown {
+            player,
+            cards: [cards[0], cards[1], cards[2], cards[3]],
+            is_crib,
+            score,
+            points,
+        });
+        self.score(player, points);
+        Ok(())
+    }
+}
+
+// A quoted tag value, with `\"` and `\\` escapes
+fn unquote(quoted: &str) -> Option<String> {
+    let mut chars = quoted.strip_prefix('"')?.chars();
+    let mut value = String::new();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            '"' if chars.as_str().is_empty() => return Some(value),
+            '"' => return None,
+            c => value.push(c),
+        }
+    }
+
+    None
+}
+
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn index(player: TwoPlayers) -> usize {
+    match player {
+        TwoPlayers::PlayerOne => 0,
+        TwoPlayers::PlayerTwo => 1,
+    }
+}
+
+fn bonus(points: u8) -> String {
+    if points == 0 {
+        String::new()
+    } else {
+        format!("+{}", points)
+    }
+}
+
+fn parse_card(line: usize, token: &Token<'_>) -> Result<Card, NotationError> {
+    Card::from_str(token.text).map_err(|_| NotationError {
+        line,
+        column: token.column,
+        message: format!("bad card {}", token.text),
+    })
+}
+
+fn parse_cards(line: usize, tokens: &[Token<'_>]) -> Result<Vec<Card>, NotationError> {
+    tokens.iter().map(|token| parse_card(line, token)).collect()
+}
+
+fn number(line: usize, token: &Token<'_>) -> Result<u8, NotationError> {
+    token.text.parse().map_err(|_| NotationError {
+        line,
+        column: token.column,
+        message: format!("bad number {}", token.text),
+    })
+}
+
+fn points(line: usize, token: &Token<'_>) -> Result<u8, NotationError> {
+    match token.text.strip_prefix('+') {
+        Some(text) => number(line, &Token { text, ..*token }),
+        None => Err(NotationError {
+            line,
+            column: token.column,
+            message: "expected +<points>".to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::{GreedyBot, RandomBot};
+    use crate::game::{play_recorded_game, replay, GameRecorder};
+    use crate::notation::{GameRecord, NotationError};
+
+    fn recorded_game(seed: u64) -> GameRecord {
+        let mut recorder = GameRecorder::with_seed(TwoPlayers::PlayerTwo, seed);
+        play_recorded_game(
+            &mut recorder,
+            &mut GreedyBot::new(),
+            &mut RandomBot::new(seed),
+        )
+        .unwrap();
+        let mut record = GameRecord::new(recorder.into_log());
+        record.player_one = Some("Greedy \"G\" Bot".to_string());
+        record.player_two = Some("Random".to_string());
+        record.date = Some("2026-10-19".to_string());
+        record
+    }
+
+    #[test]
+    fn test_notation_round_trip() {
+        for seed in 0..5 {
+            let record = recorded_game(seed);
+            let text = record.to_string();
+            assert!(text.starts_with("[PlayerOne \"Greedy \\\"G\\\" Bot\"]\n"));
+            assert!(text.contains("\nDeal 1 dealer 2\n"));
+            assert!(text.ends_with(&format!(
+                "Winner: {}\n",
+                if replay(&record.log).unwrap().winner() == Some(TwoPlayers::PlayerOne) {
+                    "1"
+                } else {
+                    "2"
+                }
+            )));
+
+            let parsed: GameRecord = text.parse().unwrap();
+            assert_eq!(parsed, record);
+            assert_eq!(parsed.to_string(), text);
+            assert!(replay(&parsed.log).is_ok());
+
+            // Extra spacing and blank lines parse, and come back out canonical
+            let loose = text.replace(": ", ":   ").replace('\n', "\n\n");
+            assert_eq!(loose.parse::<GameRecord>().unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn test_notation_errors() {
+        let error = |text: &str| text.parse::<GameRecord>().unwrap_err();
+        let header = "[Rules \"two-player 121\"]\n[Seed \"3\"]\n[FirstDealer \"1\"]\n";
+        let deal = "Deal 1 dealer 1\nHand 2: 5H 5C 5S JD QS KH\n";
+
+        assert_eq!(
+            error(&format!("{}{}Hand 1: AS 2S 3S 4S 9D 1C\n", header, deal)),
+            NotationError {
+                line: 6,
+                column: 24,
+                message: "bad card 1C".to_string(),
+            }
+        );
+        assert_eq!(error("[Seed \"x\"]\n").column, 7);
+        assert_eq!(error("  [Colour \"red\"]\n").column, 4);
+        assert_eq!(error("[Seed \"3]\n").line, 1);
+        assert_eq!(
+            error(deal).message,
+            "the Rules, Seed and FirstDealer tags are required"
+        );
+        assert_eq!(
+            error(&format!("{}{}Starter: JH +2\nBoard: 0 0\n", header, deal)),
+            NotationError {
+                line: 7,
+                column: 8,
+                message: "the board shows 2".to_string(),
+            }
+        );
+        assert_eq!(
+            error(&format!("{}{}Pegging: 2:5H/5 1:AS\n", header, deal)),
+            NotationError {
+                line: 6,
+                column: 17,
+                message: "expected <seat>:<card>/<count>[+<points>], go:<seat>/<count> or \
+                          last:<seat>+<points>"
+                    .to_string(),
+            }
+        );
+        assert_eq!(error(&format!("{}{}", header, deal)).line, 5);
+        assert_eq!(error(&format!("{}Deal 2 dealer 1\n", header)).column, 6);
+    }
+}
@@ -2,7 +2,7 @@ use crate::card::Card;
 use crate::pegging::{Pegger, ThreeCardPegging};
 use crate::CribbageCoreError;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct FourCardPegging {
     cards: [Card; 4],
 }
@@ -12,6 +12,10 @@ impl FourCardPegging {
         FourCardPegging { cards }
     }
 
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
     pub fn play_card(
         self,
         card: Card,
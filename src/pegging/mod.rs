@@ -1,17 +1,25 @@
 mod four_card_pegging;
 mod one_card_pegging;
+mod round;
+mod solver;
 mod three_card_pegging;
 mod two_card_pegging;
 
 pub use self::four_card_pegging::FourCardPegging;
 pub use self::one_card_pegging::OneCardPegging;
+pub use self::round::{PegOutcome, PeggingRound};
+pub use self::solver::{solve_pegging, PeggingSolution, PeggingSolver, SolvedPlay};
 pub use self::three_card_pegging::ThreeCardPegging;
 pub use self::two_card_pegging::TwoCardPegging;
 
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize};
+
 use crate::card::Card;
 use crate::CribbageCoreError;
 
-#[derive(Default)]
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
 pub struct Pegger {
     count: u8,
     played_cards: Vec<Card>,
@@ -46,6 +54,14 @@ impl Pegger {
         self.count
     }
 
+    pub fn played_cards(&self) -> &[Card] {
+        &self.played_cards
+    }
+
+    pub fn can_play(&self, card: Card) -> bool {
+        self.count + card.rank().value() <= 31
+    }
+
     /*
     Note: Caller must implement logic to assign the "go" point to the appropriate player
           whenever a count of 31 isn't reached exactly. In the event that a count of 31 is reached
@@ -101,7 +117,41 @@ impl Pegger {
     }
 }
 
+// The count must be what the played cards add up to
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Pegger {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Pegger, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "Pegger")]
+        struct Fields {
+            count: u8,
+            played_cards: Vec<Card>,
+        }
+
+        let fields = Fields::deserialize(deserializer)?;
+        if crate::card_set::CardSet::from_cards(&fields.played_cards).len()
+            != fields.played_cards.len()
+        {
+            return Err(de::Error::custom("repeated card"));
+        }
+
+        let mut pegger = Pegger::new();
+        for &card in &fields.played_cards {
+            pegger
+                .play_card(card)
+                .map_err(|_| de::Error::custom("count beyond 31"))?;
+        }
+
+        if pegger.count != fields.count {
+            return Err(de::Error::custom("count does not match the played cards"));
+        }
+
+        Ok(pegger)
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::bool_assert_comparison)]
 mod test {
     use crate::card::{Card, Rank, Suit};
     use crate::CribbageCoreError;
@@ -265,4 +315,29 @@ mod test {
             Err(CribbageCoreError::InvalidCard)
         );
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_pegger_serde() {
+        let mut pegger = Pegger::new();
+        pegger
+            .play_card(Card::new(Rank::Ten, Suit::Spades))
+            .unwrap();
+        pegger
+            .play_card(Card::new(Rank::Five, Suit::Hearts))
+            .unwrap();
+        let json = serde_json::to_string(&pegger).unwrap();
+        assert_eq!(json, r#"{"count":15,"played_cards":["TS","5H"]}"#);
+        let restored: Pegger = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.count(), 15);
+        assert_eq!(restored.played_cards(), pegger.played_cards());
+
+        for json in &[
+            r#"{"count":14,"played_cards":["TS","5H"]}"#,
+            r#"{"count":20,"played_cards":["TS","TS"]}"#,
+            r#"{"count":40,"played_cards":["TS","TH","TD","TC"]}"#,
+        ] {
+            assert!(serde_json::from_str::<Pegger>(json).is_err(), "{}", json);
+        }
+    }
 }
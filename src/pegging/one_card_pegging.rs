@@ -1,8 +1,10 @@
+use std::slice;
+
 use crate::card::Card;
 use crate::pegging::Pegger;
 use crate::CribbageCoreError;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct OneCardPegging {
     card: Card,
 }
@@ -12,6 +14,10 @@ impl OneCardPegging {
         OneCardPegging { card }
     }
 
+    pub fn cards(&self) -> &[Card] {
+        slice::from_ref(&self.card)
+    }
+
     pub fn play_card(
         self,
         card: Card,
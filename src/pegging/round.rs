@@ -0,0 +1,179 @@
+use crate::board::{TwoPlayerScore, TwoPlayers};
+use crate::card::Card;
+use crate::pegging::Pegger;
+use crate::CribbageCoreError;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PegOutcome {
+    pub player: TwoPlayers,
+    pub points: u8,
+    pub count: u8,
+    pub go: Option<TwoPlayers>,
+    pub sequence_ended: bool,
+}
+
+impl PegOutcome {
+    pub fn total_points(&self) -> u8 {
+        self.points + u8::from(self.sequence_ended)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PeggingRound {
+    pegger: Pegger,
+    hands: TwoPlayerScore<Vec<Card>>,
+    turn: TwoPlayers,
+    said_go: TwoPlayerScore<bool>,
+}
+
+impl PeggingRound {
+    pub fn new(hands: TwoPlayerScore<Vec<Card>>, first: TwoPlayers) -> PeggingRound {
+        PeggingRound::resume(Pegger::new(), hands, first, TwoPlayerScore::default())
+    }
+
+    pub fn resume(
+        pegger: Pegger,
+        hands: TwoPlayerScore<Vec<Card>>,
+        turn: TwoPlayers,
+        said_go: TwoPlayerScore<bool>,
+    ) -> PeggingRound {
+        PeggingRound {
+            pegger,
+            hands,
+            turn,
+            said_go,
+        }
+    }
+
+    pub fn pegger(&self) -> &Pegger {
+        &self.pegger
+    }
+
+    pub fn hand(&self, player: TwoPlayers) -> &[Card] {
+        &self.hands[player]
+    }
+
+    pub fn turn(&self) -> TwoPlayers {
+        self.turn
+    }
+
+    pub fn said_go(&self, player: TwoPlayers) -> bool {
+        self.said_go[player]
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.hands[TwoPlayers::PlayerOne].is_empty() && self.hands[TwoPlayers::PlayerTwo].is_empty()
+    }
+
+    pub fn can_play(&self, player: TwoPlayers) -> bool {
+        self.hands[player]
+            .iter()
+            .any(|&card| self.pegger.can_play(card))
+    }
+
+    pub fn legal_plays(&self) -> Vec<Card> {
+        self.hands[self.turn]
+            .iter()
+            .copied()
+            .filter(|&card| self.pegger.can_play(card))
+            .collect()
+    }
+
+    pub fn play(&mut self, card: Card) -> Result<PegOutcome, CribbageCoreError> {
+        let player = self.turn;
+        let index = self.hands[player]
+            .iter()
+            .position(|&c| c == card)
+            .ok_or(CribbageCoreError::InvalidCard)?;
+        let points = self.pegger.play_card(card)?;
+        self.hands[player].remove(index);
+
+        let mut outcome = PegOutcome {
+            player,
+            points,
+            count: self.pegger.count(),
+            go: None,
+            sequence_ended: false,
+        };
+
+        let other = player.other();
+        if self.can_play(other) {
+            self.turn = other;
+            return Ok(outcome);
+        }
+
+        if !self.hands[other].is_empty() && !self.said_go[other] {
+            self.said_go[other] = true;
+            outcome.go = Some(other);
+        }
+
+        if self.can_play(player) {
+            return Ok(outcome);
+        }
+
+        // Neither player can continue the count, so the last player to lay a card scores the go
+        // (or last card), on top of the point the pegger already gave for an exact 31
+        outcome.sequence_ended = true;
+        self.pegger.reset();
+        self.said_go = TwoPlayerScore::default();
+        if !self.hands[other].is_empty() {
+            self.turn = other;
+        }
+
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{TwoPlayerScore, TwoPlayers};
+    use crate::card::Card;
+    use crate::pegging::PeggingRound;
+    use crate::CribbageCoreError;
+    use std::str::FromStr;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split(' ').map(|c| Card::from_str(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_round() {
+        let one = TwoPlayers::PlayerOne;
+        let two = TwoPlayers::PlayerTwo;
+        let mut round = PeggingRound::new(
+            TwoPlayerScore::new([cards("KH QH 2C AC"), cards("KS QS 9D 3D")]),
+            one,
+        );
+
+        assert_eq!(
+            round.play(cards("KS")[0]),
+            Err(CribbageCoreError::InvalidCard)
+        );
+        assert_eq!(round.play(cards("KH")[0]).unwrap().points, 0);
+        assert_eq!(round.play(cards("KS")[0]).unwrap().points, 2);
+
+        let outcome = round.play(cards("QH")[0]).unwrap();
+        assert_eq!(outcome.go, Some(two));
+        assert!(!outcome.sequence_ended);
+        assert_eq!(round.turn(), one);
+        assert_eq!(round.legal_plays(), cards("AC"));
+
+        let outcome = round.play(cards("AC")[0]).unwrap();
+        assert_eq!(outcome.count, 31);
+        assert!(outcome.sequence_ended);
+        assert_eq!(outcome.total_points(), 2);
+        assert_eq!(round.pegger().count(), 0);
+        assert_eq!(round.turn(), two);
+        assert!(!round.said_go(two));
+
+        round.play(cards("QS")[0]).unwrap();
+        round.play(cards("2C")[0]).unwrap();
+        assert!(!round.play(cards("9D")[0]).unwrap().sequence_ended);
+        assert_eq!(round.turn(), two);
+
+        let outcome = round.play(cards("3D")[0]).unwrap();
+        assert!(outcome.sequence_ended);
+        assert_eq!(outcome.go, None);
+        assert!(round.is_finished());
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use crate::board::{TwoPlayerScore, TwoPlayers};
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::pegging::{FourCardPegging, PeggingRound};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct SolvedPlay {
+    pub player: TwoPlayers,
+    pub card: Card,
+    pub points: u8,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PeggingSolution {
+    pub player: TwoPlayers,
+    pub net: i16,
+    pub line: Vec<SolvedPlay>,
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+struct PositionKey {
+    hands: [u64; 2],
+    sequence: u64,
+    flags: u8,
+}
+
+#[derive(Debug, Default)]
+pub struct PeggingSolver {
+    memo: HashMap<PositionKey, (i16, Card)>,
+}
+
+impl PeggingSolver {
+    pub fn new() -> PeggingSolver {
+        PeggingSolver {
+            memo: HashMap::new(),
+        }
+    }
+
+    pub fn positions(&self) -> usize {
+        self.memo.len()
+    }
+
+    pub fn solve(&mut self, round: &PeggingRound) -> PeggingSolution {
+        let player = round.turn();
+        let net = self.value(round);
+
+        let mut line = Vec::new();
+        let mut round = round.clone();
+        while let Some(&(_, card)) = self.memo.get(&key(&round)) {
+            let outcome = match round.play(card) {
+                Ok(outcome) => outcome,
+                Err(_) => break,
+            };
+
+            line.push(SolvedPlay {
+                player: outcome.player,
+                card,
+                points: outcome.total_points(),
+            });
+        }
+
+        PeggingSolution { player, net, line }
+    }
+
+    // Net points for the player to move over the rest of the round, assuming both sides play
+    // perfectly. Suits never matter in pegging, but keeping exact cards keeps the lines readable
+    fn value(&mut self, round: &PeggingRound) -> i16 {
+        if round.is_finished() {
+            return 0;
+        }
+
+        let key = key(round);
+        if let Some(&(value, _)) = self.memo.get(&key) {
+            return value;
+        }
+
+        let mover = round.turn();
+        let mut best: Option<(i16, Card)> = None;
+        for card in round.legal_plays() {
+            let mut next = round.clone();
+            let outcome = match next.play(card) {
+                Ok(outcome) => outcome,
+                Err(_) => continue,
+            };
+
+            let rest = self.value(&next);
+            let rest = if next.turn() == mover { rest } else { -rest };
+            let value = i16::from(outcome.total_points()) + rest;
+            if best.map_or(true, |(best_value, _)| value > best_value) {
+                best = Some((value, card));
+            }
+        }
+
+        match best {
+            Some((value, card)) => {
+                self.memo.insert(key, (value, card));
+                value
+            }
+            None => 0,
+        }
+    }
+}
+
+pub fn solve_pegging(hands: TwoPlayerScore<FourCardPegging>, first: TwoPlayers) -> PeggingSolution {
+    let hands = TwoPlayerScore::new([
+        hands[TwoPlayers::PlayerOne].cards().to_vec(),
+        hands[TwoPlayers::PlayerTwo].cards().to_vec(),
+    ]);
+    PeggingSolver::new().solve(&PeggingRound::new(hands, first))
+}
+
+fn key(round: &PeggingRound) -> PositionKey {
+    let one = TwoPlayers::PlayerOne;
+    let two = TwoPlayers::PlayerTwo;
+    let sequence = round
+        .pegger()
+        .played_cards()
+        .iter()
+        .fold(0u64, |packed, card| {
+            (packed << 4) | u64::from(card.rank().ordinal())
+        });
+    let flags = u8::from(round.turn() == two)
+        | u8::from(round.said_go(one)) << 1
+        | u8::from(round.said_go(two)) << 2
+        | (round.pegger().played_cards().len() as u8) << 3;
+
+    PositionKey {
+        hands: [
+            CardSet::from_cards(round.hand(one)).bits(),
+            CardSet::from_cards(round.hand(two)).bits(),
+        ],
+        sequence,
+        flags,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{TwoPlayerScore, TwoPlayers};
+    use crate::card::Card;
+    use crate::deck::Deck;
+    use crate::pegging::{solve_pegging, FourCardPegging, PeggingRound, PeggingSolver};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::str::FromStr;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split(' ').map(|c| Card::from_str(c).unwrap()).collect()
+    }
+
+    fn four(s: &str) -> FourCardPegging {
+        let cards = cards(s);
+        FourCardPegging::new([cards[0], cards[1], cards[2], cards[3]])
+    }
+
+    fn brute_force(round: &PeggingRound) -> i16 {
+        if round.is_finished() {
+            return 0;
+        }
+
+        let mover = round.turn();
+        round
+            .legal_plays()
+            .into_iter()
+            .map(|card| {
+                let mut next = round.clone();
+                let points = i16::from(next.play(card).unwrap().total_points());
+                let rest = brute_force(&next);
+                points + if next.turn() == mover { rest } else { -rest }
+            })
+            .max()
+            .unwrap()
+    }
+
+    fn replay(round: &PeggingRound, line: &[crate::pegging::SolvedPlay]) -> i16 {
+        let player = round.turn();
+        let mut round = round.clone();
+        let mut net = 0;
+        for play in line {
+            assert_eq!(round.turn(), play.player);
+            let points = i16::from(round.play(play.card).unwrap().total_points());
+            assert_eq!(points, i16::from(play.points));
+            net += if play.player == player {
+                points
+            } else {
+                -points
+            };
+        }
+
+        assert!(round.is_finished());
+        net
+    }
+
+    #[test]
+    fn test_solve_known_hands() {
+        let one = TwoPlayers::PlayerOne;
+        let hands = TwoPlayerScore::new([four("KH QH 2C AC"), four("KS QS 9D 3D")]);
+        let solution = solve_pegging(hands, one);
+        assert_eq!(solution.player, one);
+        assert_eq!(solution.line.len(), 8);
+
+        let round = PeggingRound::new(
+            TwoPlayerScore::new([cards("KH QH 2C AC"), cards("KS QS 9D 3D")]),
+            one,
+        );
+        assert_eq!(solution.net, brute_force(&round));
+        assert_eq!(replay(&round, &solution.line), solution.net);
+
+        let solution = solve_pegging(
+            TwoPlayerScore::new([four("5H 5C 5S 5D"), four("KS QS JD TD")]),
+            TwoPlayers::PlayerTwo,
+        );
+        assert_eq!(solution.player, TwoPlayers::PlayerTwo);
+        assert_eq!(solution.line[0].points, 0);
+        assert_eq!(solution.line[1].points, 2);
+    }
+
+    #[test]
+    fn test_solver_matches_brute_force() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let mut deck = Deck::new();
+        for index in 0..25 {
+            deck.shuffle_with_rng(&mut rng);
+            let hands = TwoPlayerScore::new([deck.draw_n(4).unwrap(), deck.draw_n(4).unwrap()]);
+            let first = if index % 2 == 0 {
+                TwoPlayers::PlayerOne
+            } else {
+                TwoPlayers::PlayerTwo
+            };
+            let round = PeggingRound::new(hands, first);
+
+            let mut solver = PeggingSolver::new();
+            let solution = solver.solve(&round);
+            assert_eq!(solution.net, brute_force(&round));
+            assert_eq!(replay(&round, &solution.line), solution.net);
+            assert!(solver.positions() > 0);
+
+            let mut partial = round.clone();
+            let card = partial.legal_plays()[0];
+            partial.play(card).unwrap();
+            assert_eq!(solver.solve(&partial).net, brute_force(&partial));
+        }
+    }
+}
@@ -2,7 +2,7 @@ use crate::card::Card;
 use crate::pegging::{Pegger, TwoCardPegging};
 use crate::CribbageCoreError;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct ThreeCardPegging {
     cards: [Card; 3],
 }
@@ -12,6 +12,10 @@ impl ThreeCardPegging {
         ThreeCardPegging { cards }
     }
 
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
     pub fn play_card(
         self,
         card: Card,
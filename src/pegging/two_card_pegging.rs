@@ -2,7 +2,7 @@ use crate::card::Card;
 use crate::pegging::{OneCardPegging, Pegger};
 use crate::CribbageCoreError;
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct TwoCardPegging {
     cards: [Card; 2],
 }
@@ -12,6 +12,10 @@ impl TwoCardPegging {
         TwoCardPegging { cards }
     }
 
+    pub fn cards(&self) -> &[Card] {
+        &self.cards
+    }
+
     pub fn play_card(
         self,
         card: Card,
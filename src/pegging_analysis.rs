@@ -0,0 +1,404 @@
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{TwoPlayerScore, TwoPlayers};
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::game::{Situation, TableEvent, TwoPlayerGame};
+use crate::pegging::{PegOutcome, Pegger, PeggingRound};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PeggingBudget {
+    Samples(usize),
+    Time(Duration),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CardEstimate {
+    pub card: Card,
+    pub expected_net: f64,
+    pub samples: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct PeggingView {
+    hand: Vec<Card>,
+    pegger: Pegger,
+    seen: CardSet,
+    opponent_cards: usize,
+    opponent_played: Vec<Card>,
+    opponent_min_value: u8,
+    own_said_go: bool,
+    opponent_said_go: bool,
+}
+
+impl PeggingView {
+    pub fn new(
+        hand: &[Card],
+        pegger: &Pegger,
+        seen: CardSet,
+        opponent_cards: usize,
+    ) -> PeggingView {
+        let mut seen = seen;
+        seen.extend(hand.iter().copied());
+        seen.extend(pegger.played_cards().iter().copied());
+        PeggingView {
+            hand: hand.to_vec(),
+            pegger: pegger.clone(),
+            seen,
+            opponent_cards,
+            opponent_played: Vec::new(),
+            opponent_min_value: 1,
+            own_said_go: false,
+            opponent_said_go: false,
+        }
+    }
+
+    pub fn from_situation(
+        hand: &[Card],
+        pegger: &Pegger,
+        situation: &Situation<'_>,
+    ) -> PeggingView {
+        let mut seen = CardSet::new();
+        seen.extend(situation.starter);
+        seen = seen | situation.crib_part;
+        let mut view = PeggingView::new(hand, pegger, seen, 4);
+        view.observe_events(situation.player, situation.deal_events);
+        view
+    }
+
+    pub fn from_game(game: &TwoPlayerGame, player: TwoPlayers) -> PeggingView {
+        let mut seen = CardSet::new();
+        seen.extend(game.starter());
+        if let Some(crib_part) = game.crib_part(player) {
+            seen = seen | crib_part.card_set();
+        }
+
+        let mut view = PeggingView::new(game.pegging_hand(player), game.pegger(), seen, 4);
+        view.observe_events(player, game.deal_events());
+        view
+    }
+
+    pub fn hand(&self) -> &[Card] {
+        &self.hand
+    }
+
+    pub fn pegger(&self) -> &Pegger {
+        &self.pegger
+    }
+
+    pub fn seen(&self) -> CardSet {
+        self.seen
+    }
+
+    pub fn opponent_cards(&self) -> usize {
+        self.opponent_cards
+    }
+
+    pub fn opponent_played(&self) -> &[Card] {
+        &self.opponent_played
+    }
+
+    pub fn opponent_min_value(&self) -> u8 {
+        self.opponent_min_value
+    }
+
+    pub fn legal_plays(&self) -> Vec<Card> {
+        self.hand
+            .iter()
+            .copied()
+            .filter(|&card| self.pegger.can_play(card))
+            .collect()
+    }
+
+    pub fn observe_cards(&mut self, cards: CardSet) {
+        self.seen = self.seen | cards;
+    }
+
+    // An opponent who says go at a count holds nothing that fits under 31, and every card they
+    // still hold was in their hand at that point
+    pub fn record_opponent_go(&mut self, count: u8) {
+        self.opponent_min_value = self.opponent_min_value.max(32 - count.min(31));
+        self.opponent_said_go = true;
+    }
+
+    fn observe_events(&mut self, player: TwoPlayers, events: &[TableEvent]) {
+        self.opponent_played.clear();
+        for event in events {
+            match *event {
+                TableEvent::Played {
+                    player: played_by,
+                    card,
+                    ..
+                } => {
+                    self.seen.insert(card);
+                    if played_by != player {
+                        self.opponent_played.push(card);
+                    }
+                }
+                TableEvent::Go { player: go, count } => {
+                    if go == player {
+                        self.own_said_go = true;
+                    } else {
+                        self.record_opponent_go(count);
+                    }
+                }
+                TableEvent::SequenceEnded { .. } => {
+                    self.own_said_go = false;
+                    self.opponent_said_go = false;
+                }
+                _ => {}
+            }
+        }
+
+        self.opponent_cards = 4usize.saturating_sub(self.opponent_played.len());
+    }
+
+    fn sample_opponent_hand<R: Rng + ?Sized>(&self, rng: &mut R) -> Vec<Card> {
+        let unseen = self.seen.complement();
+        let mut pool: Vec<Card> = unseen
+            .iter()
+            .filter(|card| card.rank().value() >= self.opponent_min_value)
+            .collect();
+
+        // Fall back to every unseen card if the inferences can't be satisfied
+        if pool.len() < self.opponent_cards {
+            pool = unseen.to_vec();
+        }
+
+        let (chosen, _) = pool.partial_shuffle(rng, self.opponent_cards);
+        chosen.to_vec()
+    }
+
+    pub(crate) fn sample_round<R: Rng + ?Sized>(
+        &self,
+        player: TwoPlayers,
+        rng: &mut R,
+    ) -> PeggingRound {
+        let mut hands = TwoPlayerScore::default();
+        hands[player] = self.hand.clone();
+        hands[player.other()] = self.sample_opponent_hand(rng);
+        let mut said_go = TwoPlayerScore::default();
+        said_go[player] = self.own_said_go;
+        said_go[player.other()] = self.opponent_said_go;
+        PeggingRound::resume(self.pegger.clone(), hands, player, said_go)
+    }
+}
+
+pub fn estimate_pegging(view: &PeggingView, budget: PeggingBudget, seed: u64) -> Vec<CardEstimate> {
+    let mut estimates: Vec<CardEstimate> = sample_pegging(view, budget, seed, |_, outcomes| {
+        outcomes
+            .iter()
+            .map(|outcome| {
+                let points = f64::from(outcome.total_points());
+                match outcome.player {
+                    TwoPlayers::PlayerOne => points,
+                    TwoPlayers::PlayerTwo => -points,
+                }
+            })
+            .sum()
+    })
+    .into_iter()
+    .map(|(card, expected_net, samples)| CardEstimate {
+        card,
+        expected_net,
+        samples,
+    })
+    .collect();
+    estimates.sort_by(|a, b| b.expected_net.total_cmp(&a.expected_net));
+    estimates
+}
+
+// Plays out the rest of the pegging for every legal card against sampled opponent hands and
+// averages `value` over the playouts. The viewing player is PlayerOne in the outcomes, and the
+// opponent's sampled hand is passed as it was before the playout began
+pub(crate) fn sample_pegging<F>(
+    view: &PeggingView,
+    budget: PeggingBudget,
+    seed: u64,
+    mut value: F,
+) -> Vec<(Card, f64, usize)>
+where
+    F: FnMut(&[Card], &[PegOutcome]) -> f64,
+{
+    let legal = view.legal_plays();
+    if legal.is_empty() {
+        return Vec::new();
+    }
+
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut totals = vec![0.0; legal.len()];
+    let mut samples = 0;
+    let start = Instant::now();
+    loop {
+        let done = match budget {
+            PeggingBudget::Samples(limit) => samples >= limit.max(1),
+            PeggingBudget::Time(limit) => samples > 0 && start.elapsed() >= limit,
+        };
+        if done {
+            break;
+        }
+
+        // Every candidate card faces the same sampled opponent hand
+        let round = view.sample_round(TwoPlayers::PlayerOne, &mut rng);
+        let opponent_hand = round.hand(TwoPlayers::PlayerTwo).to_vec();
+        for (total, &card) in totals.iter_mut().zip(legal.iter()) {
+            let outcomes = simulate(round.clone(), card, &mut rng);
+            *total += value(&opponent_hand, &outcomes);
+        }
+
+        samples += 1;
+    }
+
+    legal
+        .into_iter()
+        .zip(totals)
+        .map(|(card, total)| (card, total / samples as f64, samples))
+        .collect()
+}
+
+fn simulate<R: Rng + ?Sized>(mut round: PeggingRound, card: Card, rng: &mut R) -> Vec<PegOutcome> {
+    let mut outcomes = Vec::new();
+    let mut next = Some(card);
+    while let Some(card) = next {
+        match round.play(card) {
+            Ok(outcome) => outcomes.push(outcome),
+            Err(_) => break,
+        }
+
+        next = if round.is_finished() {
+            None
+        } else {
+            rollout_card(&round, rng)
+        };
+    }
+
+    outcomes
+}
+
+pub(crate) fn rollout_card<R: Rng + ?Sized>(round: &PeggingRound, rng: &mut R) -> Option<Card> {
+    let mut legal = round.legal_plays();
+    legal.shuffle(rng);
+    legal.into_iter().max_by_key(|&card| {
+        let mut pegger = round.pegger().clone();
+        pegger.play_card(card).unwrap_or(0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::card::Card;
+    use crate::card_set::CardSet;
+    use crate::game::{GamePhase, Situation, TwoPlayerGame};
+    use crate::pegging::Pegger;
+    use crate::pegging_analysis::{estimate_pegging, PeggingBudget, PeggingView};
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::str::FromStr;
+    use std::time::Duration;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split(' ').map(|c| Card::from_str(c).unwrap()).collect()
+    }
+
+    fn pegger(s: &str) -> Pegger {
+        let mut pegger = Pegger::new();
+        for card in cards(s) {
+            pegger.play_card(card).unwrap();
+        }
+
+        pegger
+    }
+
+    #[test]
+    fn test_estimate_prefers_thirty_one() {
+        let view = PeggingView::new(
+            &cards("5C 4D 9S"),
+            &pegger("KH QS 6H"),
+            CardSet::from_cards(&cards("2S")),
+            3,
+        );
+        assert_eq!(view.legal_plays(), cards("5C 4D"));
+
+        let estimates = estimate_pegging(&view, PeggingBudget::Samples(200), 7);
+        assert_eq!(estimates.len(), 2);
+        assert_eq!(estimates[0].card, cards("5C")[0]);
+        assert_eq!(estimates[0].samples, 200);
+        assert!(estimates[0].expected_net > estimates[1].expected_net);
+
+        let again = estimate_pegging(&view, PeggingBudget::Samples(200), 7);
+        assert_eq!(again, estimates);
+
+        let timed = estimate_pegging(&view, PeggingBudget::Time(Duration::from_millis(5)), 7);
+        assert!(timed[0].samples >= 1);
+    }
+
+    #[test]
+    fn test_go_inference() {
+        let mut view = PeggingView::new(&cards("AC 2C"), &pegger("KH QS"), CardSet::new(), 3);
+        view.record_opponent_go(25);
+        assert_eq!(view.opponent_min_value(), 7);
+
+        let seen = view.seen();
+        let mut rng = StdRng::seed_from_u64(3);
+        for _ in 0..100 {
+            let hand = view.sample_opponent_hand(&mut rng);
+            assert_eq!(hand.len(), 3);
+            assert!(hand.iter().all(|card| card.rank().value() >= 7));
+            assert!(hand.iter().all(|&card| !seen.contains(card)));
+        }
+    }
+
+    #[test]
+    fn test_view_from_game() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 11);
+        for player in [TwoPlayers::PlayerTwo, TwoPlayers::PlayerOne] {
+            let deal = game.deal(player).unwrap().cards().to_vec();
+            game.discard(player, [deal[0], deal[1]]).unwrap();
+        }
+        assert_eq!(game.phase(), GamePhase::Pegging);
+
+        let pone = TwoPlayers::PlayerTwo;
+        let card = game.legal_plays(pone)[0];
+        game.play(pone, card).unwrap();
+
+        let dealer = TwoPlayers::PlayerOne;
+        let view = PeggingView::from_game(&game, dealer);
+        assert_eq!(view.hand(), game.pegging_hand(dealer));
+        assert_eq!(view.opponent_cards(), 3);
+        assert!(view.seen().contains(card));
+        assert!(view.seen().contains(game.starter().unwrap()));
+        assert_eq!(view.seen().len(), 4 + 2 + 1 + 1);
+
+        let estimates = estimate_pegging(&view, PeggingBudget::Samples(20), 0);
+        assert_eq!(estimates.len(), game.legal_plays(dealer).len());
+    }
+
+    #[test]
+    fn test_view_from_situation_excludes_own_discards() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerOne, 11);
+        let mut discards = Vec::new();
+        for player in [TwoPlayers::PlayerTwo, TwoPlayers::PlayerOne] {
+            let deal = game.deal(player).unwrap().cards().to_vec();
+            game.discard(player, [deal[0], deal[1]]).unwrap();
+            discards.push([deal[0], deal[1]]);
+        }
+
+        let dealer = TwoPlayers::PlayerOne;
+        let situation = Situation::new(&game, dealer);
+        assert_eq!(situation.crib_part, CardSet::from_cards(&discards[1]));
+
+        let view =
+            PeggingView::from_situation(game.pegging_hand(dealer), game.pegger(), &situation);
+        let mut rng = StdRng::seed_from_u64(5);
+        for _ in 0..200 {
+            let hand = view.sample_opponent_hand(&mut rng);
+            assert_eq!(hand.len(), 4);
+            assert!(hand.iter().all(|card| !discards[1].contains(card)));
+        }
+    }
+}
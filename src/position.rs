@@ -0,0 +1,490 @@
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+
+use crate::board::TwoPlayers;
+use crate::card::Card;
+use crate::card_set::CardSet;
+use crate::discard::{analyze_discards, DiscardOption};
+use crate::game::Situation;
+use crate::hand::score_hand;
+use crate::pegging_analysis::{sample_pegging, PeggingBudget, PeggingView};
+use crate::CribbageCoreError;
+
+const DEALER_PEGGING: f64 = 4.2;
+const PONE_PEGGING: f64 = 3.3;
+
+pub trait WinEstimator {
+    // Chance that the player on `own_score` wins from a board where the next deal is about to
+    // start, with `is_dealer` saying whether that player deals it
+    fn win_probability(
+        &self,
+        own_score: u8,
+        opponent_score: u8,
+        is_dealer: bool,
+        target: u8,
+    ) -> f64;
+}
+
+impl<E: WinEstimator + ?Sized> WinEstimator for &E {
+    fn win_probability(
+        &self,
+        own_score: u8,
+        opponent_score: u8,
+        is_dealer: bool,
+        target: u8,
+    ) -> f64 {
+        (**self).win_probability(own_score, opponent_score, is_dealer, target)
+    }
+}
+
+impl<E: WinEstimator + ?Sized> WinEstimator for Box<E> {
+    fn win_probability(
+        &self,
+        own_score: u8,
+        opponent_score: u8,
+        is_dealer: bool,
+        target: u8,
+    ) -> f64 {
+        (**self).win_probability(own_score, opponent_score, is_dealer, target)
+    }
+}
+
+// Normal approximation of the race to the target: both players average about 13 points a deal
+// with the dealer a few points ahead of the pone, and the spread grows with the deals left
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct RaceEstimator;
+
+impl WinEstimator for RaceEstimator {
+    fn win_probability(
+        &self,
+        own_score: u8,
+        opponent_score: u8,
+        is_dealer: bool,
+        target: u8,
+    ) -> f64 {
+        if own_score >= target {
+            return 1.0;
+        }
+
+        if opponent_score >= target {
+            return 0.0;
+        }
+
+        let own_needed = f64::from(target - own_score);
+        let opponent_needed = f64::from(target - opponent_score);
+        let deal_edge = if is_dealer { 2.5 } else { -2.5 };
+        let deals_left = (own_needed + opponent_needed) / 26.0;
+        let spread = 7.0 * (deals_left.max(0.25) * 2.0).sqrt();
+        normal_cdf((opponent_needed - own_needed + deal_edge) / spread)
+    }
+}
+
+fn normal_cdf(x: f64) -> f64 {
+    // Abramowitz and Stegun 7.1.26
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.327_591_1 * z);
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Position {
+    pub own_score: u8,
+    pub opponent_score: u8,
+    pub is_dealer: bool,
+    pub target: u8,
+}
+
+impl Position {
+    pub fn new(own_score: u8, opponent_score: u8, is_dealer: bool) -> Position {
+        Position {
+            own_score,
+            opponent_score,
+            is_dealer,
+            target: 121,
+        }
+    }
+
+    pub fn from_situation(situation: &Situation<'_>) -> Position {
+        Position {
+            own_score: situation.own_score,
+            opponent_score: situation.opponent_score,
+            is_dealer: situation.is_dealer(),
+            target: situation.target,
+        }
+    }
+
+    pub fn win_probability<E: WinEstimator + ?Sized>(&self, estimator: &E) -> f64 {
+        estimator.win_probability(
+            self.own_score,
+            self.opponent_score,
+            self.is_dealer,
+            self.target,
+        )
+    }
+
+    // Chance of winning once this deal is played out: pegging points in the order they were
+    // scored, then the pone's hand, the dealer's hand and the crib, stopping at the first player
+    // to reach the target
+    fn after_deal<E: WinEstimator + ?Sized>(
+        &self,
+        pegging: &[(bool, u8)],
+        own_hand: u8,
+        opponent_hand: u8,
+        crib: u8,
+        estimator: &E,
+    ) -> f64 {
+        let mut own = u16::from(self.own_score);
+        let mut opponent = u16::from(self.opponent_score);
+        let target = u16::from(self.target);
+
+        let (first, second) = if self.is_dealer {
+            ((false, opponent_hand), (true, own_hand))
+        } else {
+            ((true, own_hand), (false, opponent_hand))
+        };
+        let show = [first, second, (self.is_dealer, crib)];
+
+        for &(is_own, points) in pegging.iter().chain(show.iter()) {
+            if is_own {
+                own += u16::from(points);
+                if own >= target {
+                    return 1.0;
+                }
+            } else {
+                opponent += u16::from(points);
+                if opponent >= target {
+                    return 0.0;
+                }
+            }
+        }
+
+        estimator.win_probability(own as u8, opponent as u8, !self.is_dealer, self.target)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DealCards {
+    pub kept: [Card; 4],
+    pub crib_part: [Card; 2],
+    pub starter: Card,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PositionalDiscard {
+    pub option: DiscardOption,
+    pub win_probability: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PositionalPeg {
+    pub card: Card,
+    pub win_probability: f64,
+    pub samples: usize,
+}
+
+pub fn rank_discards_by_position<E: WinEstimator + ?Sized>(
+    cards: &[Card],
+    position: Position,
+    estimator: &E,
+    samples: usize,
+    seed: u64,
+) -> Result<Vec<PositionalDiscard>, CribbageCoreError> {
+    if cards.len() != 6 {
+        return Err(CribbageCoreError::NotEnoughCards);
+    }
+
+    let options = analyze_discards(cards, position.is_dealer, samples, seed)?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut unseen = CardSet::from_cards(cards).complement().to_vec();
+    let mut totals = vec![0.0; options.len()];
+    let samples = samples.max(1);
+
+    // Every option is scored against the same sampled cut, opponent hand and pegging
+    for _ in 0..samples {
+        unseen.shuffle(&mut rng);
+        let cut = unseen[0];
+        let (opponent_keep, opponent_throw) = likely_keep(&unseen[1..7], unseen[7]);
+        let opponent_hand = score_hand(opponent_keep, cut, false);
+        let (own_mean, opponent_mean) = if position.is_dealer {
+            (DEALER_PEGGING, PONE_PEGGING)
+        } else {
+            (PONE_PEGGING, DEALER_PEGGING)
+        };
+        let pegging = alternate_pegging(
+            !position.is_dealer,
+            poisson(own_mean, &mut rng),
+            poisson(opponent_mean, &mut rng),
+        );
+
+        for (total, option) in totals.iter_mut().zip(options.iter()) {
+            let own_hand = score_hand(option.keep, cut, false);
+            let crib = [
+                option.discard[0],
+                option.discard[1],
+                opponent_throw[0],
+                opponent_throw[1],
+            ];
+            let crib = score_hand(crib, cut, true);
+            *total += position.after_deal(&pegging, own_hand, opponent_hand, crib, estimator);
+        }
+    }
+
+    let mut ranked: Vec<PositionalDiscard> = options
+        .into_iter()
+        .zip(totals)
+        .map(|(option, total)| PositionalDiscard {
+            option,
+            win_probability: total / samples as f64,
+        })
+        .collect();
+    ranked.sort_by(|a, b| {
+        b.win_probability.total_cmp(&a.win_probability).then(
+            b.option
+                .net(position.is_dealer)
+                .total_cmp(&a.option.net(position.is_dealer)),
+        )
+    });
+    Ok(ranked)
+}
+
+pub fn rank_pegging_by_position<E: WinEstimator + ?Sized>(
+    view: &PeggingView,
+    deal: DealCards,
+    position: Position,
+    estimator: &E,
+    budget: PeggingBudget,
+    seed: u64,
+) -> Vec<PositionalPeg> {
+    let DealCards {
+        kept,
+        crib_part,
+        starter,
+    } = deal;
+    let mut rng = StdRng::seed_from_u64(seed ^ 0x5eed);
+    let own_hand = score_hand(kept, starter, false);
+    let mut known = view.seen() | CardSet::from_cards(&kept) | CardSet::from_cards(&crib_part);
+    known.insert(starter);
+
+    let mut ranked: Vec<PositionalPeg> =
+        sample_pegging(view, budget, seed, |opponent, outcomes| {
+            let pegging: Vec<(bool, u8)> = outcomes
+                .iter()
+                .map(|outcome| {
+                    (
+                        outcome.player == TwoPlayers::PlayerOne,
+                        outcome.total_points(),
+                    )
+                })
+                .collect();
+
+            let opponent_cards: Vec<Card> = view
+                .opponent_played()
+                .iter()
+                .chain(opponent)
+                .copied()
+                .collect();
+            let mut unseen = (known | CardSet::from_cards(&opponent_cards))
+                .complement()
+                .to_vec();
+            let others: Vec<Card> = unseen.partial_shuffle(&mut rng, 2).0.to_vec();
+            let crib = score_hand(
+                [crib_part[0], crib_part[1], others[0], others[1]],
+                starter,
+                true,
+            );
+            let opponent_hand = match opponent_cards.len() {
+                4 => score_hand(
+                    [
+                        opponent_cards[0],
+                        opponent_cards[1],
+                        opponent_cards[2],
+                        opponent_cards[3],
+                    ],
+                    starter,
+                    false,
+                ),
+                _ => 0,
+            };
+
+            position.after_deal(&pegging, own_hand, opponent_hand, crib, estimator)
+        })
+        .into_iter()
+        .map(|(card, win_probability, samples)| PositionalPeg {
+            card,
+            win_probability,
+            samples,
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.win_probability.total_cmp(&a.win_probability));
+    ranked
+}
+
+// What a sensible opponent keeps from six cards, judged against a random card so the real cut
+// doesn't leak into their choice
+fn likely_keep(cards: &[Card], pseudo_cut: Card) -> ([Card; 4], [Card; 2]) {
+    let mut best: Option<([Card; 4], [Card; 2], u8)> = None;
+    for first in 0..cards.len() {
+        for second in first + 1..cards.len() {
+            let keep: Vec<Card> = (0..cards.len())
+                .filter(|&index| index != first && index != second)
+                .map(|index| cards[index])
+                .collect();
+            let keep = [keep[0], keep[1], keep[2], keep[3]];
+            let score = score_hand(keep, pseudo_cut, false);
+            if best.map_or(true, |(_, _, best_score)| score > best_score) {
+                best = Some((keep, [cards[first], cards[second]], score));
+            }
+        }
+    }
+
+    best.map_or(
+        (
+            [cards[0], cards[1], cards[2], cards[3]],
+            [cards[4], cards[5]],
+        ),
+        |(keep, throw, _)| (keep, throw),
+    )
+}
+
+// Pegging points handed out one at a time in turn, pone first, which is close enough to decide
+// who pegs out first when both are near the end
+fn alternate_pegging(own_first: bool, own: u8, opponent: u8) -> Vec<(bool, u8)> {
+    let mut pegging = Vec::with_capacity(usize::from(own) + usize::from(opponent));
+    let (mut own, mut opponent) = (own, opponent);
+    let mut own_turn = own_first;
+    while own > 0 || opponent > 0 {
+        if own_turn && own > 0 {
+            pegging.push((true, 1));
+            own -= 1;
+        } else if !own_turn && opponent > 0 {
+            pegging.push((false, 1));
+            opponent -= 1;
+        }
+
+        own_turn = !own_turn;
+    }
+
+    pegging
+}
+
+fn poisson<R: Rng + ?Sized>(mean: f64, rng: &mut R) -> u8 {
+    let limit = (-mean).exp();
+    let mut product = rng.gen::<f64>();
+    let mut count = 0;
+    while product > limit && count < u8::MAX {
+        product *= rng.gen::<f64>();
+        count += 1;
+    }
+
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::card_set::CardSet;
+    use crate::pegging::Pegger;
+    use crate::pegging_analysis::{PeggingBudget, PeggingView};
+    use crate::position::{
+        alternate_pegging, rank_discards_by_position, rank_pegging_by_position, DealCards,
+        Position, RaceEstimator, WinEstimator,
+    };
+    use std::str::FromStr;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split(' ').map(|c| Card::from_str(c).unwrap()).collect()
+    }
+
+    #[test]
+    fn test_race_estimator() {
+        let race = RaceEstimator;
+        assert_eq!(race.win_probability(121, 120, false, 121), 1.0);
+        assert_eq!(race.win_probability(60, 121, true, 121), 0.0);
+
+        let even = race.win_probability(0, 0, true, 121);
+        assert!(even > 0.5 && even < 0.6);
+        let sum =
+            race.win_probability(90, 70, false, 121) + race.win_probability(70, 90, true, 121);
+        assert!((sum - 1.0).abs() < 1e-9);
+        assert!(
+            race.win_probability(100, 90, false, 121) > race.win_probability(90, 90, false, 121)
+        );
+    }
+
+    #[test]
+    fn test_counting_order() {
+        let race = RaceEstimator;
+        let pone = Position::new(119, 119, false);
+        assert_eq!(pone.after_deal(&[], 2, 20, 20, &race), 1.0);
+        assert_eq!(pone.after_deal(&[(false, 2)], 2, 0, 0, &race), 0.0);
+
+        let dealer = Position::new(119, 119, true);
+        assert_eq!(dealer.after_deal(&[], 20, 2, 0, &race), 0.0);
+        assert_eq!(
+            dealer.after_deal(&[(true, 1), (false, 1), (true, 1)], 0, 2, 0, &race),
+            1.0
+        );
+
+        assert_eq!(
+            alternate_pegging(false, 2, 1),
+            vec![(false, 1), (true, 1), (true, 1)]
+        );
+    }
+
+    #[test]
+    fn test_rank_discards_by_position() {
+        let hand = cards("5H 5C 5S JD KC 2D");
+        let race = RaceEstimator;
+        let ranked =
+            rank_discards_by_position(&hand, Position::new(10, 10, false), &race, 200, 1).unwrap();
+        assert_eq!(ranked.len(), 15);
+        for pair in ranked.windows(2) {
+            assert!(pair[0].win_probability >= pair[1].win_probability);
+        }
+
+        // Needing only two points as pone, keeping a pair is as good as keeping the 29 hand
+        let ranked =
+            rank_discards_by_position(&hand, Position::new(119, 100, false), &race, 200, 1)
+                .unwrap();
+        assert!(ranked[0].win_probability > 0.99);
+        assert!(ranked[0].option.worst_cut.1 >= 2);
+
+        assert!(
+            rank_discards_by_position(&hand[..5], Position::new(0, 0, false), &race, 1, 1).is_err()
+        );
+    }
+
+    #[test]
+    fn test_rank_pegging_by_position() {
+        let mut pegger = Pegger::new();
+        pegger.play_card(cards("TS")[0]).unwrap();
+        let hand = cards("5C 9H 2D AS");
+        let view = PeggingView::new(&hand, &pegger, CardSet::from_cards(&cards("KD")), 3);
+        let ranked = rank_pegging_by_position(
+            &view,
+            DealCards {
+                kept: [hand[0], hand[1], hand[2], hand[3]],
+                crib_part: [cards("3C")[0], cards("4C")[0]],
+                starter: cards("KD")[0],
+            },
+            Position::new(119, 120, true),
+            &RaceEstimator,
+            PeggingBudget::Samples(100),
+            2,
+        );
+        assert_eq!(ranked.len(), 4);
+        assert_eq!(ranked[0].card, cards("5C")[0]);
+        assert_eq!(ranked[0].win_probability, 1.0);
+        assert!(ranked[1].win_probability < 1.0);
+    }
+}
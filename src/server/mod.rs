@@ -0,0 +1,687 @@
+// A TCP server hosting cribbage tables for two to four players. Clients send one JSON object per
+// line and the server answers the same way. The server owns every table and only ever tells a
+// client about its own cards; other hands are revealed when they are shown.
+//
+// Client messages:
+//
+//   {"type": "join", "name": "Ann", "table": "main", "players": 3}
+//       Takes the next free seat at `table`, creating it for `players` (2 to 4, default 2) if it
+//       does not exist yet. The reply is {"type": "welcome", "table": ..., "seat": 0,
+//       "token": "..."}; keep the token to reconnect.
+//   {"type": "rejoin", "token": "..."}
+//       Reclaims a seat after a dropped connection and answers with a welcome.
+//   {"type": "discard", "cards": ["5H", "JD"]}
+//       Throws to the crib: two cards at a table of two, otherwise one.
+//   {"type": "play", "card": "5H"}
+//       Lays a card during the pegging.
+//   {"type": "state"}
+//       Asks for the current state.
+//
+// Whenever a table changes, every connected player receives its own view of it:
+//
+//   {"type": "state", "table": "main", "seat": 0, "phase": "waiting" | "discard" | "pegging"
+//    | "finished", "target": 121, "dealer": 0, "crib_size": 0,
+//    "starter": "5H" | null, "hand": ["AS", ...], "discard_count": 1, "turn": 1 | null,
+//    "legal": [...], "count": 10, "sequence": ["TD"], "winner": null,
+//    "players": [{"seat": 0, "name": "Ann", "score": 12, "cards": 4, "discarded": true,
+//                 "connected": true}, ...],
+//    "events": [...], "previous_deal": [...]}
+//
+// Scores come straight from the table's `Board`. The events are those of the current deal, and
+// `previous_deal` keeps those of the deal before it, so the hands and crib shown at its end are
+// not lost when the next deal starts:
+//
+//   {"type": "deal", "dealer": 0}                 {"type": "discard", "seat": 1}
+//   {"type": "starter", "card": "JH", "heels": 2}  {"type": "go", "seat": 2}
+//   {"type": "play", "seat": 1, "card": "5H", "count": 15, "points": 2}
+//   {"type": "last", "seat": 1, "points": 1}       {"type": "win", "seat": 0}
+//   {"type": "show", "seat": 0, "cards": [...], "crib": false, "points": 8}
+//
+// A request that cannot be carried out is answered with {"type": "error", "message": "..."}.
+// Once a seat has been rejoined, only the new connection may act for it.
+
+mod table;
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::{json, Value};
+
+use crate::card::Card;
+use crate::server::table::{SeatEvent, Table, TablePhase};
+
+// Messages for a client go through its own writer thread, so a client that stops reading never
+// blocks a thread holding the lobby
+type Connection = (u64, Sender<Value>);
+
+struct Seat {
+    name: String,
+    token: String,
+    connection: Option<Connection>,
+}
+
+struct Hosted {
+    table: Table,
+    seats: Vec<Seat>,
+}
+
+impl Hosted {
+    fn is_full(&self) -> bool {
+        self.seats.len() == self.table.seats()
+    }
+
+    fn broadcast(&self, name: &str) {
+        for (index, seat) in self.seats.iter().enumerate() {
+            if let Some((_, sender)) = &seat.connection {
+                send(sender, self.view(name, index));
+            }
+        }
+    }
+
+    fn is_connected(&self, seat: usize, connection: u64) -> bool {
+        matches!(self.seats[seat].connection, Some((id, _)) if id == connection)
+    }
+
+    fn view(&self, name: &str, seat: usize) -> Value {
+        let table = &self.table;
+        let phase = match table.phase() {
+            _ if !self.is_full() => "waiting",
+            TablePhase::Discard => "discard",
+            TablePhase::Pegging => "pegging",
+            TablePhase::Finished => "finished",
+        };
+
+        let players: Vec<Value> = self
+            .seats
+            .iter()
+            .enumerate()
+            .map(|(index, player)| {
+                json!({
+                    "seat": index,
+                    "name": player.name,
+                    "score": table.score(index),
+                    "cards": table.hand(index).len(),
+                    "discarded": table.has_discarded(index),
+                    "connected": player.connection.is_some(),
+                })
+            })
+            .collect();
+
+        json!({
+            "type": "state",
+            "table": name,
+            "seat": seat,
+            "phase": phase,
+            "target": table.target(),
+            "dealer": table.dealer(),
+            "crib_size": table.crib_size(),
+            "starter": table.starter().map(|card| card.to_string()),
+            "hand": cards_json(table.hand(seat)),
+            "discard_count": table.discard_size(),
+            "turn": table.turn(),
+            "legal": cards_json(&table.legal_plays(seat)),
+            "count": table.count(),
+            "sequence": cards_json(table.sequence()),
+            "winner": table.winner(),
+            "players": players,
+            "events": table.deal_events().iter().map(event_json).collect::<Vec<Value>>(),
+            "previous_deal": table
+                .previous_deal_events()
+                .iter()
+                .map(event_json)
+                .collect::<Vec<Value>>(),
+        })
+    }
+}
+
+struct Lobby {
+    rng: StdRng,
+    next_connection: u64,
+    tables: HashMap<String, Hosted>,
+}
+
+impl Lobby {
+    fn join(
+        &mut self,
+        message: &Value,
+        connection: Connection,
+    ) -> Result<(String, usize, String), String> {
+        let name = message["name"].as_str().unwrap_or("Player").to_string();
+        let table_name = message["table"].as_str().unwrap_or("main").to_string();
+        let players = message["players"].as_u64().unwrap_or(2) as usize;
+        let token = format!("{:016x}", self.rng.gen::<u64>());
+        let seed = self.rng.gen();
+
+        let hosted = match self.tables.get_mut(&table_name) {
+            Some(hosted) => hosted,
+            None => {
+                let table = Table::new(players, seed)
+                    .map_err(|_| "a table needs 2 to 4 players".to_string())?;
+                self.tables.entry(table_name.clone()).or_insert(Hosted {
+                    table,
+                    seats: Vec::new(),
+                })
+            }
+        };
+
+        if hosted.is_full() {
+            return Err("the table is full".to_string());
+        }
+
+        hosted.seats.push(Seat {
+            name,
+            token: token.clone(),
+            connection: Some(connection),
+        });
+        Ok((table_name, hosted.seats.len() - 1, token))
+    }
+
+    fn rejoin(
+        &mut self,
+        message: &Value,
+        connection: Connection,
+    ) -> Result<(String, usize, String), String> {
+        let token = message["token"].as_str().unwrap_or_default();
+        for (name, hosted) in &mut self.tables {
+            if let Some(seat) = hosted.seats.iter().position(|seat| seat.token == token) {
+                hosted.seats[seat].connection = Some(connection);
+                return Ok((name.clone(), seat, token.to_string()));
+            }
+        }
+
+        Err("unknown token".to_string())
+    }
+
+    // The table and seat of a client, provided the seat has not since been rejoined elsewhere
+    fn seat<'a>(
+        &mut self,
+        seated: &'a Option<(String, usize)>,
+        connection: u64,
+    ) -> Result<(&'a String, usize, &mut Hosted), String> {
+        let (name, seat) = seated
+            .as_ref()
+            .ok_or_else(|| "join a table first".to_string())?;
+        let hosted = self
+            .tables
+            .get_mut(name)
+            .ok_or_else(|| "the table has closed".to_string())?;
+        if !hosted.is_connected(*seat, connection) {
+            return Err("the seat was rejoined from another connection".to_string());
+        }
+
+        Ok((name, *seat, hosted))
+    }
+
+    fn act(
+        &mut self,
+        seated: &Option<(String, usize)>,
+        connection: u64,
+        message: &Value,
+    ) -> Result<String, String> {
+        let (name, seat, hosted) = self.seat(seated, connection)?;
+
+        match message["type"].as_str() {
+            Some(_) if !hosted.is_full() => return Err("waiting for players".to_string()),
+            Some("discard") => {
+                let cards = message["cards"]
+                    .as_array()
+                    .and_then(|cards| {
+                        cards
+                            .iter()
+                            .map(card_from_json)
+                            .collect::<Option<Vec<Card>>>()
+                    })
+                    .ok_or_else(|| "invalid cards".to_string())?;
+                hosted.table.discard(seat, &cards)
+            }
+            Some("play") => {
+                let card =
+                    card_from_json(&message["card"]).ok_or_else(|| "invalid card".to_string())?;
+                hosted.table.play(seat, card)
+            }
+            _ => return Err("unknown message type".to_string()),
+        }
+        .map_err(|error| error.to_string())?;
+
+        Ok(name.clone())
+    }
+
+    fn disconnect(&mut self, connection: u64) -> Option<String> {
+        for (name, hosted) in &mut self.tables {
+            for seat in &mut hosted.seats {
+                if matches!(seat.connection, Some((id, _)) if id == connection) {
+                    seat.connection = None;
+                    return Some(name.clone());
+                }
+            }
+        }
+
+        None
+    }
+}
+
+pub struct GameServer {
+    listener: TcpListener,
+    lobby: Arc<Mutex<Lobby>>,
+}
+
+impl GameServer {
+    pub fn bind<A: ToSocketAddrs>(address: A, seed: u64) -> io::Result<GameServer> {
+        Ok(GameServer {
+            listener: TcpListener::bind(address)?,
+            lobby: Arc::new(Mutex::new(Lobby {
+                rng: StdRng::seed_from_u64(seed),
+                next_connection: 0,
+                tables: HashMap::new(),
+            })),
+        })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    // Accepts clients until the listener fails, serving each one on its own thread
+    pub fn run(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let stream = stream?;
+            let lobby = Arc::clone(&self.lobby);
+            thread::spawn(move || {
+                let _ = serve_client(&lobby, stream);
+            });
+        }
+
+        Ok(())
+    }
+}
+
+fn serve_client(lobby: &Mutex<Lobby>, stream: TcpStream) -> io::Result<()> {
+    let connection = {
+        let mut lobby = lobby
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        lobby.next_connection += 1;
+        lobby.next_connection
+    };
+
+    // States go out as small writes, so don't let them wait on acknowledgements
+    stream.set_nodelay(true)?;
+    let sender = spawn_writer(stream.try_clone()?);
+    let mut seated: Option<(String, usize)> = None;
+    let reader = BufReader::new(stream);
+    for line in reader.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let message: Value = match serde_json::from_str(&line) {
+            Ok(message) => message,
+            Err(_) => {
+                send(&sender, error_json("invalid JSON"));
+                continue;
+            }
+        };
+
+        let mut lobby = lobby
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let result = match message["type"].as_str() {
+            Some(kind @ "join") | Some(kind @ "rejoin") if seated.is_none() => {
+                let joined = if kind == "join" {
+                    lobby.join(&message, (connection, sender.clone()))
+                } else {
+                    lobby.rejoin(&message, (connection, sender.clone()))
+                };
+
+                joined.map(|(name, seat, token)| {
+                    send(
+                        &sender,
+                        json!({"type": "welcome", "table": name, "seat": seat, "token": token}),
+                    );
+                    seated = Some((name.clone(), seat));
+                    name
+                })
+            }
+            Some("join") | Some("rejoin") => Err("already seated".to_string()),
+            Some("state") => match lobby.seat(&seated, connection) {
+                Ok((name, seat, hosted)) => {
+                    send(&sender, hosted.view(name, seat));
+                    continue;
+                }
+                Err(message) => Err(message),
+            },
+            _ => lobby.act(&seated, connection, &message),
+        };
+
+        match result {
+            Ok(name) => {
+                if let Some(hosted) = lobby.tables.get(&name) {
+                    hosted.broadcast(&name);
+                }
+            }
+            Err(message) => send(&sender, error_json(&message)),
+        }
+    }
+
+    let mut lobby = lobby
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(name) = lobby.disconnect(connection) {
+        if let Some(hosted) = lobby.tables.get(&name) {
+            hosted.broadcast(&name);
+        }
+    }
+
+    Ok(())
+}
+
+// Writes each message as a line until the client goes away or every sender has been dropped
+fn spawn_writer(mut stream: TcpStream) -> Sender<Value> {
+    let (sender, receiver) = mpsc::channel::<Value>();
+    thread::spawn(move || {
+        for message in receiver {
+            if writeln!(stream, "{}", message)
+                .and_then(|_| stream.flush())
+                .is_err()
+            {
+                break;
+            }
+        }
+    });
+
+    sender
+}
+
+fn send(sender: &Sender<Value>, message: Value) {
+    let _ = sender.send(message);
+}
+
+fn error_json(message: &str) -> Value {
+    json!({"type": "error", "message": message})
+}
+
+fn cards_json(cards: &[Card]) -> Vec<String> {
+    cards.iter().map(Card::to_string).collect()
+}
+
+fn card_from_json(value: &Value) -> Option<Card> {
+    value.as_str().and_then(|s| Card::from_str(s).ok())
+}
+
+fn event_json(event: &SeatEvent) -> Value {
+    match *event {
+        SeatEvent::DealStarted { dealer } => json!({"type": "deal", "dealer": dealer}),
+        SeatEvent::Discarded { seat } => json!({"type": "discard", "seat": seat}),
+        SeatEvent::StarterCut { starter, heels } => {
+            json!({"type": "starter", "card": starter.to_string(), "heels": heels})
+        }
+        SeatEvent::Played {
+            seat,
+            card,
+            count,
+            points,
+        } => json!({
+            "type": "play",
+            "seat": seat,
+            "card": card.to_string(),
+            "count": count,
+            "points": points,
+        }),
+        SeatEvent::Go { seat } => json!({"type": "go", "seat": seat}),
+        SeatEvent::SequenceEnded { seat, points } => {
+            json!({"type": "last", "seat": seat, "points": points})
+        }
+        SeatEvent::Shown {
+            seat,
+            cards,
+            is_crib,
+            points,
+        } => json!({
+            "type": "show",
+            "seat": seat,
+            "cards": cards_json(&cards),
+            "crib": is_crib,
+            "points": points,
+        }),
+        SeatEvent::GameWon { winner } => json!({"type": "win", "seat": winner}),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::server::GameServer;
+    use serde_json::{json, Value};
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::{SocketAddr, TcpStream};
+    use std::thread;
+
+    struct Client {
+        reader: BufReader<TcpStream>,
+        stream: TcpStream,
+    }
+
+    impl Client {
+        fn connect(address: SocketAddr) -> Client {
+            let stream = TcpStream::connect(address).unwrap();
+            stream.set_nodelay(true).unwrap();
+            Client {
+                reader: BufReader::new(stream.try_clone().unwrap()),
+                stream,
+            }
+        }
+
+        fn send(&mut self, message: Value) {
+            writeln!(self.stream, "{}", message).unwrap();
+        }
+
+        fn receive(&mut self) -> Value {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+
+        fn receive_type(&mut self, kind: &str) -> Value {
+            loop {
+                let message = self.receive();
+                if message["type"] == kind {
+                    return message;
+                }
+            }
+        }
+    }
+
+    fn start_server() -> SocketAddr {
+        let server = GameServer::bind("127.0.0.1:0", 3).unwrap();
+        let address = server.local_addr().unwrap();
+        thread::spawn(move || server.run());
+        address
+    }
+
+    // Discards the first cards and lays the first legal card until a state satisfies `done`
+    fn play_until<F>(mut client: Client, seat: u64, done: F) -> Value
+    where
+        F: Fn(&Value) -> bool,
+    {
+        loop {
+            let state = client.receive();
+            if state["type"] != "state" {
+                continue;
+            }
+
+            let players = state["players"].as_array().unwrap();
+            for player in players {
+                if player["seat"] != seat {
+                    assert!(player.get("hand").is_none());
+                }
+            }
+
+            if done(&state) {
+                return state;
+            }
+
+            let hand = state["hand"].as_array().unwrap();
+            if state["phase"] == "discard" && players[seat as usize]["discarded"] == false {
+                let count = state["discard_count"].as_u64().unwrap() as usize;
+                client.send(json!({"type": "discard", "cards": hand[..count].to_vec()}));
+            } else if state["phase"] == "pegging" && state["turn"] == seat {
+                let card = state["legal"][0].clone();
+                client.send(json!({"type": "play", "card": card}));
+            }
+        }
+    }
+
+    #[test]
+    fn test_three_player_game() {
+        let address = start_server();
+        let mut clients = Vec::new();
+        for name in &["Ann", "Bob", "Cy"] {
+            let mut client = Client::connect(address);
+            client.send(json!({"type": "join", "name": name, "table": "lan", "players": 3}));
+            let welcome = client.receive_type("welcome");
+            assert_eq!(welcome["table"], "lan");
+            clients.push((client, welcome["seat"].as_u64().unwrap()));
+        }
+
+        let mut late = Client::connect(address);
+        late.send(json!({"type": "join", "name": "Dee", "table": "lan"}));
+        assert_eq!(late.receive()["message"], "the table is full");
+
+        let players: Vec<_> = clients
+            .into_iter()
+            .map(|(client, seat)| {
+                thread::spawn(move || play_until(client, seat, |state| !state["winner"].is_null()))
+            })
+            .collect();
+        let finals: Vec<Value> = players
+            .into_iter()
+            .map(|player| player.join().unwrap())
+            .collect();
+
+        let winner = finals[0]["winner"].as_u64().unwrap() as usize;
+        for state in &finals {
+            assert_eq!(state["winner"], winner);
+            assert_eq!(state["phase"], "finished");
+            assert_eq!(state["players"][winner]["score"], 121);
+            assert_eq!(state["players"], finals[0]["players"]);
+        }
+    }
+
+    #[test]
+    fn test_reconnection() {
+        let address = start_server();
+        let mut ann = Client::connect(address);
+        ann.send(json!({"type": "join", "name": "Ann"}));
+        let token = ann.receive_type("welcome")["token"].clone();
+        ann.send(json!({"type": "discard", "cards": ["AS", "2S"]}));
+        assert_eq!(ann.receive_type("error")["message"], "waiting for players");
+
+        let mut bob = Client::connect(address);
+        bob.send(json!({"type": "join", "name": "Bob"}));
+        assert_eq!(bob.receive_type("welcome")["seat"], 1);
+        let state = bob.receive_type("state");
+        assert_eq!(state["phase"], "discard");
+        assert_eq!(state["hand"].as_array().unwrap().len(), 6);
+        assert_eq!(state["players"][0]["cards"], 6);
+
+        let hand = ann.receive_type("state")["hand"].clone();
+        drop(ann);
+        loop {
+            let state = bob.receive_type("state");
+            if state["players"][0]["connected"] == false {
+                break;
+            }
+        }
+
+        let mut intruder = Client::connect(address);
+        intruder.send(json!({"type": "rejoin", "token": "0"}));
+        assert_eq!(intruder.receive()["message"], "unknown token");
+
+        let mut ann = Client::connect(address);
+        ann.send(json!({"type": "rejoin", "token": token}));
+        assert_eq!(ann.receive_type("welcome")["seat"], 0);
+        let state = ann.receive_type("state");
+        assert_eq!(state["hand"], hand);
+        assert_eq!(state["players"][0]["connected"], true);
+        assert_eq!(state["players"][1]["name"], "Bob");
+
+        ann.send(json!({"type": "play", "card": "AS"}));
+        assert_eq!(ann.receive_type("error")["message"], "WrongPhase");
+        ann.send(json!({"type": "dance"}));
+        assert_eq!(ann.receive_type("error")["message"], "unknown message type");
+    }
+
+    #[test]
+    fn test_rejoin_takes_over_seat() {
+        let address = start_server();
+        let mut ann = Client::connect(address);
+        ann.send(json!({"type": "join", "name": "Ann"}));
+        let token = ann.receive_type("welcome")["token"].clone();
+        let mut bob = Client::connect(address);
+        bob.send(json!({"type": "join", "name": "Bob"}));
+        bob.receive_type("welcome");
+        let hand = ann.receive_type("state")["hand"].clone();
+
+        let mut again = Client::connect(address);
+        again.send(json!({"type": "rejoin", "token": token}));
+        assert_eq!(again.receive_type("welcome")["seat"], 0);
+
+        let cards = json!([hand[0], hand[1]]);
+        ann.send(json!({"type": "discard", "cards": cards}));
+        assert_eq!(
+            ann.receive_type("error")["message"],
+            "the seat was rejoined from another connection"
+        );
+        ann.send(json!({"type": "state"}));
+        assert!(ann.receive_type("error").is_object());
+
+        again.send(json!({"type": "discard", "cards": cards}));
+        loop {
+            let state = again.receive_type("state");
+            if state["players"][0]["discarded"] == true {
+                break;
+            }
+        }
+    }
+    #[test]
+    fn test_shown_hands_reach_clients() {
+        let address = start_server();
+        let mut players = Vec::new();
+        for name in &["Ann", "Bob"] {
+            let mut client = Client::connect(address);
+            client.send(json!({"type": "join", "name": name, "table": "show"}));
+            let seat = client.receive_type("welcome")["seat"].as_u64().unwrap();
+            players.push(thread::spawn(move || {
+                play_until(client, seat, |state| {
+                    !state["previous_deal"].as_array().unwrap().is_empty()
+                })
+            }));
+        }
+
+        for player in players {
+            let state = player.join().unwrap();
+            assert_eq!(state["events"][0], json!({"type": "deal", "dealer": 1}));
+            let previous = state["previous_deal"].as_array().unwrap();
+            assert_eq!(previous[0], json!({"type": "deal", "dealer": 0}));
+            let shows: Vec<&Value> = previous
+                .iter()
+                .filter(|event| event["type"] == "show")
+                .collect();
+            assert_eq!(shows.len(), 3);
+            assert_eq!(shows[0]["seat"], 1);
+            assert_eq!(shows[2]["seat"], 0);
+            assert_eq!(shows[2]["crib"], true);
+            assert!(shows
+                .iter()
+                .all(|show| show["cards"].as_array().unwrap().len() == 4));
+            assert!(previous.iter().any(|event| event["type"] == "last"));
+        }
+    }
+}
@@ -0,0 +1,563 @@
+use std::mem;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::board::{
+    standard_four_player_board, standard_three_player_board, standard_two_player_board, Board,
+    FourPlayerScore, FourPlayers, ThreePlayerScore, ThreePlayers, TwoPlayerScore, TwoPlayers,
+};
+use crate::card::{Card, Rank};
+use crate::deck::Deck;
+use crate::hand::score_hand;
+use crate::pegging::Pegger;
+use crate::CribbageCoreError;
+
+const TWO_SEATS: [TwoPlayers; 2] = [TwoPlayers::PlayerOne, TwoPlayers::PlayerTwo];
+const THREE_SEATS: [ThreePlayers; 3] = [
+    ThreePlayers::PlayerOne,
+    ThreePlayers::PlayerTwo,
+    ThreePlayers::PlayerThree,
+];
+const FOUR_SEATS: [FourPlayers; 4] = [
+    FourPlayers::PlayerOne,
+    FourPlayers::PlayerTwo,
+    FourPlayers::PlayerThree,
+    FourPlayers::PlayerFour,
+];
+
+#[derive(Clone, Debug)]
+enum Scores {
+    Two(Board<TwoPlayers, TwoPlayerScore<u8>>),
+    Three(Board<ThreePlayers, ThreePlayerScore<u8>>),
+    Four(Board<FourPlayers, FourPlayerScore<u8>>),
+}
+
+impl Scores {
+    fn score(&self, seat: usize) -> u8 {
+        match self {
+            Scores::Two(board) => board.score(TWO_SEATS[seat]),
+            Scores::Three(board) => board.score(THREE_SEATS[seat]),
+            Scores::Four(board) => board.score(FOUR_SEATS[seat]),
+        }
+    }
+
+    fn target(&self) -> u8 {
+        match self {
+            Scores::Two(board) => board.target(),
+            Scores::Three(board) => board.target(),
+            Scores::Four(board) => board.target(),
+        }
+    }
+
+    // Returns whether the points won the game
+    fn add_points(&mut self, seat: usize, points: u8) -> bool {
+        match self {
+            Scores::Two(board) => {
+                board.add_points(TWO_SEATS[seat], points).is_ok() && board.winner().is_some()
+            }
+            Scores::Three(board) => {
+                board.add_points(THREE_SEATS[seat], points).is_ok() && board.winner().is_some()
+            }
+            Scores::Four(board) => {
+                board.add_points(FOUR_SEATS[seat], points).is_ok() && board.winner().is_some()
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum TablePhase {
+    Discard,
+    Pegging,
+    Finished,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum SeatEvent {
+    DealStarted {
+        dealer: usize,
+    },
+    Discarded {
+        seat: usize,
+    },
+    StarterCut {
+        starter: Card,
+        heels: u8,
+    },
+    Played {
+        seat: usize,
+        card: Card,
+        count: u8,
+        points: u8,
+    },
+    Go {
+        seat: usize,
+    },
+    SequenceEnded {
+        seat: usize,
+        points: u8,
+    },
+    Shown {
+        seat: usize,
+        cards: [Card; 4],
+        is_crib: bool,
+        points: u8,
+    },
+    GameWon {
+        winner: usize,
+    },
+}
+
+// The authoritative state of a table of two to four players. Two players are dealt six cards
+// and throw two; three players are dealt five and throw one, with the crib topped up from the
+// deck; four players are dealt five and throw one
+#[derive(Clone, Debug)]
+pub(crate) struct Table {
+    seats: usize,
+    rng: StdRng,
+    deck: Deck,
+    scores: Scores,
+    dealer: usize,
+    phase: TablePhase,
+    hands: Vec<Vec<Card>>,
+    discarded: Vec<bool>,
+    crib: Vec<Card>,
+    starter: Option<Card>,
+    pegging_hands: Vec<Vec<Card>>,
+    pegger: Pegger,
+    turn: usize,
+    said_go: Vec<bool>,
+    last_player: usize,
+    winner: Option<usize>,
+    deal_events: Vec<SeatEvent>,
+    previous_deal_events: Vec<SeatEvent>,
+}
+
+impl Table {
+    pub(crate) fn new(seats: usize, seed: u64) -> Result<Table, CribbageCoreError> {
+        let scores = match seats {
+            2 => Scores::Two(standard_two_player_board()),
+            3 => Scores::Three(standard_three_player_board()),
+            4 => Scores::Four(standard_four_player_board()),
+            _ => return Err(CribbageCoreError::InvalidPlayerCount),
+        };
+
+        let mut table = Table {
+            seats,
+            rng: StdRng::seed_from_u64(seed),
+            deck: Deck::new(),
+            scores,
+            dealer: 0,
+            phase: TablePhase::Discard,
+            hands: vec![Vec::new(); seats],
+            discarded: vec![false; seats],
+            crib: Vec::new(),
+            starter: None,
+            pegging_hands: vec![Vec::new(); seats],
+            pegger: Pegger::new(),
+            turn: 0,
+            said_go: vec![false; seats],
+            last_player: 0,
+            winner: None,
+            deal_events: Vec::new(),
+            previous_deal_events: Vec::new(),
+        };
+
+        table.start_deal()?;
+        Ok(table)
+    }
+
+    pub(crate) fn seats(&self) -> usize {
+        self.seats
+    }
+
+    pub(crate) fn phase(&self) -> TablePhase {
+        self.phase
+    }
+
+    pub(crate) fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    pub(crate) fn score(&self, seat: usize) -> u8 {
+        self.scores.score(seat)
+    }
+
+    pub(crate) fn target(&self) -> u8 {
+        self.scores.target()
+    }
+
+    pub(crate) fn winner(&self) -> Option<usize> {
+        self.winner
+    }
+
+    pub(crate) fn starter(&self) -> Option<Card> {
+        self.starter
+    }
+
+    pub(crate) fn count(&self) -> u8 {
+        self.pegger.count()
+    }
+
+    pub(crate) fn sequence(&self) -> &[Card] {
+        self.pegger.played_cards()
+    }
+
+    pub(crate) fn crib_size(&self) -> usize {
+        self.crib.len()
+    }
+
+    pub(crate) fn has_discarded(&self, seat: usize) -> bool {
+        self.discarded[seat]
+    }
+
+    // The cards a seat still holds: its deal before discarding, then what is left to peg
+    pub(crate) fn hand(&self, seat: usize) -> &[Card] {
+        match self.phase {
+            TablePhase::Discard => &self.hands[seat],
+            _ => &self.pegging_hands[seat],
+        }
+    }
+
+    pub(crate) fn turn(&self) -> Option<usize> {
+        match self.phase {
+            TablePhase::Pegging => Some(self.turn),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn discard_size(&self) -> usize {
+        if self.seats == 2 {
+            2
+        } else {
+            1
+        }
+    }
+
+    pub(crate) fn legal_plays(&self, seat: usize) -> Vec<Card> {
+        if self.turn() != Some(seat) {
+            return Vec::new();
+        }
+
+        self.pegging_hands[seat]
+            .iter()
+            .copied()
+            .filter(|&card| self.pegger.can_play(card))
+            .collect()
+    }
+
+    pub(crate) fn deal_events(&self) -> &[SeatEvent] {
+        &self.deal_events
+    }
+
+    // The events of the deal before this one, through to its shows
+    pub(crate) fn previous_deal_events(&self) -> &[SeatEvent] {
+        &self.previous_deal_events
+    }
+
+    pub(crate) fn discard(&mut self, seat: usize, cards: &[Card]) -> Result<(), CribbageCoreError> {
+        if self.phase != TablePhase::Discard || self.discarded[seat] {
+            return Err(CribbageCoreError::WrongPhase);
+        }
+
+        if cards.len() != self.discard_size() {
+            return Err(CribbageCoreError::MismatchedLength);
+        }
+
+        let hand = &self.hands[seat];
+        let distinct = cards
+            .iter()
+            .enumerate()
+            .all(|(index, card)| !cards[..index].contains(card));
+        if !distinct || !cards.iter().all(|card| hand.contains(card)) {
+            return Err(CribbageCoreError::InvalidCard);
+        }
+
+        self.hands[seat].retain(|card| !cards.contains(card));
+        self.crib.extend_from_slice(cards);
+        self.discarded[seat] = true;
+        self.deal_events.push(SeatEvent::Discarded { seat });
+
+        if self.discarded.iter().all(|&discarded| discarded) {
+            self.cut_starter()?;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn play(&mut self, seat: usize, card: Card) -> Result<(), CribbageCoreError> {
+        if self.phase != TablePhase::Pegging {
+            return Err(CribbageCoreError::WrongPhase);
+        }
+
+        if self.turn != seat {
+            return Err(CribbageCoreError::OutOfTurn);
+        }
+
+        let index = self.pegging_hands[seat]
+            .iter()
+            .position(|&held| held == card)
+            .ok_or(CribbageCoreError::InvalidCard)?;
+        let points = self.pegger.play_card(card)?;
+        self.pegging_hands[seat].remove(index);
+        self.last_player = seat;
+        self.deal_events.push(SeatEvent::Played {
+            seat,
+            card,
+            count: self.pegger.count(),
+            points,
+        });
+
+        if self.award(seat, points) {
+            return Ok(());
+        }
+
+        self.advance()
+    }
+
+    fn start_deal(&mut self) -> Result<(), CribbageCoreError> {
+        self.deck = Deck::new();
+        self.deck.shuffle_with_rng(&mut self.rng);
+        self.previous_deal_events = mem::take(&mut self.deal_events);
+        self.deal_events.push(SeatEvent::DealStarted {
+            dealer: self.dealer,
+        });
+
+        let dealt = if self.seats == 2 { 6 } else { 5 };
+        for offset in 1..=self.seats {
+            let seat = (self.dealer + offset) % self.seats;
+            self.hands[seat] = self.deck.draw_n(dealt)?;
+        }
+
+        self.discarded = vec![false; self.seats];
+        self.crib.clear();
+        self.starter = None;
+        self.pegging_hands = vec![Vec::new(); self.seats];
+        self.pegger.reset();
+        self.said_go = vec![false; self.seats];
+        self.phase = TablePhase::Discard;
+        Ok(())
+    }
+
+    fn cut_starter(&mut self) -> Result<(), CribbageCoreError> {
+        while self.crib.len() < 4 {
+            self.crib.push(self.deck.draw()?);
+        }
+
+        let starter = self.deck.draw()?;
+        let heels = if starter.rank() == Rank::Jack { 2 } else { 0 };
+        self.starter = Some(starter);
+        self.deal_events
+            .push(SeatEvent::StarterCut { starter, heels });
+        self.pegging_hands = self.hands.clone();
+        self.turn = (self.dealer + 1) % self.seats;
+        self.last_player = self.dealer;
+        self.phase = TablePhase::Pegging;
+        self.award(self.dealer, heels);
+        Ok(())
+    }
+
+    fn can_play(&self, seat: usize) -> bool {
+        self.pegging_hands[seat]
+            .iter()
+            .any(|&card| self.pegger.can_play(card))
+    }
+
+    // Passes the turn to the next seat able to continue the count, including the player who just
+    // played; seats passed over say go. When nobody can continue, the last player to lay a card
+    // scores the go (or last card), on top of the point the pegger already gave for an exact 31
+    fn advance(&mut self) -> Result<(), CribbageCoreError> {
+        for offset in 1..=self.seats {
+            let seat = (self.last_player + offset) % self.seats;
+            if self.can_play(seat) {
+                self.turn = seat;
+                return Ok(());
+            }
+
+            if !self.pegging_hands[seat].is_empty() && !self.said_go[seat] {
+                self.said_go[seat] = true;
+                self.deal_events.push(SeatEvent::Go { seat });
+            }
+        }
+
+        let seat = self.last_player;
+        self.deal_events
+            .push(SeatEvent::SequenceEnded { seat, points: 1 });
+        if self.award(seat, 1) {
+            return Ok(());
+        }
+
+        self.pegger.reset();
+        self.said_go = vec![false; self.seats];
+        for offset in 1..=self.seats {
+            let next = (seat + offset) % self.seats;
+            if !self.pegging_hands[next].is_empty() {
+                self.turn = next;
+                return Ok(());
+            }
+        }
+
+        self.show()
+    }
+
+    fn show(&mut self) -> Result<(), CribbageCoreError> {
+        let starter = self.starter.ok_or(CribbageCoreError::WrongPhase)?;
+        let mut shows = Vec::with_capacity(self.seats + 1);
+        for offset in 1..=self.seats {
+            let seat = (self.dealer + offset) % self.seats;
+            shows.push((seat, self.hands[seat].clone(), false));
+        }
+
+        shows.push((self.dealer, self.crib.clone(), true));
+        for (seat, cards, is_crib) in shows {
+            let cards = [cards[0], cards[1], cards[2], cards[3]];
+            let points = score_hand(cards, starter, is_crib);
+            self.deal_events.push(SeatEvent::Shown {
+                seat,
+                cards,
+                is_crib,
+                points,
+            });
+
+            if self.award(seat, points) {
+                return Ok(());
+            }
+        }
+
+        self.dealer = (self.dealer + 1) % self.seats;
+        self.start_deal()
+    }
+
+    fn award(&mut self, seat: usize, points: u8) -> bool {
+        if points > 0 && self.scores.add_points(seat, points) {
+            self.winner = Some(seat);
+            self.phase = TablePhase::Finished;
+            self.deal_events.push(SeatEvent::GameWon { winner: seat });
+            return true;
+        }
+
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::card::Card;
+    use crate::pegging::Pegger;
+    use crate::server::table::{SeatEvent, Table, TablePhase};
+    use crate::CribbageCoreError;
+    use std::str::FromStr;
+
+    fn cards(s: &str) -> Vec<Card> {
+        s.split(' ').map(|c| Card::from_str(c).unwrap()).collect()
+    }
+
+    fn play_out(table: &mut Table) {
+        while table.phase() != TablePhase::Finished {
+            match table.phase() {
+                TablePhase::Discard => {
+                    for seat in 0..table.seats() {
+                        let cards = table.hand(seat)[..table.discard_size()].to_vec();
+                        table.discard(seat, &cards).unwrap();
+                    }
+                }
+                TablePhase::Pegging => {
+                    let seat = table.turn().unwrap();
+                    let card = table.legal_plays(seat)[0];
+                    table.play(seat, card).unwrap();
+                }
+                TablePhase::Finished => {}
+            }
+        }
+    }
+
+    #[test]
+    fn test_tables_play_to_completion() {
+        for seats in 2..=4 {
+            let mut table = Table::new(seats, 5).unwrap();
+            let dealt = if seats == 2 { 6 } else { 5 };
+            assert!((0..seats).all(|seat| table.hand(seat).len() == dealt));
+
+            play_out(&mut table);
+            let winner = table.winner().unwrap();
+            assert_eq!(table.score(winner), 121);
+            let shows = table
+                .previous_deal_events()
+                .iter()
+                .filter(|event| matches!(event, SeatEvent::Shown { .. }))
+                .count();
+            assert_eq!(shows, seats + 1);
+            assert!((0..seats).all(|seat| seat == winner || table.score(seat) < 121));
+            assert_eq!(
+                table.deal_events().last(),
+                Some(&SeatEvent::GameWon { winner })
+            );
+        }
+
+        assert_eq!(
+            Table::new(5, 0).err(),
+            Some(CribbageCoreError::InvalidPlayerCount)
+        );
+    }
+
+    #[test]
+    fn test_three_player_deal() {
+        let mut table = Table::new(3, 2).unwrap();
+        let card = table.hand(1)[0];
+        assert_eq!(
+            table.discard(1, &[]),
+            Err(CribbageCoreError::MismatchedLength)
+        );
+        assert_eq!(
+            table.discard(1, &[table.hand(2)[0]]),
+            Err(CribbageCoreError::InvalidCard)
+        );
+        table.discard(1, &[card]).unwrap();
+        assert_eq!(
+            table.discard(1, &[card]),
+            Err(CribbageCoreError::WrongPhase)
+        );
+        table.discard(0, &[table.hand(0)[0]]).unwrap();
+        table.discard(2, &[table.hand(2)[0]]).unwrap();
+
+        assert_eq!(table.phase(), TablePhase::Pegging);
+        assert_eq!(table.crib_size(), 4);
+        assert_eq!(table.turn(), Some(1));
+        assert!(table.starter().is_some());
+        assert!((0..3).all(|seat| table.hand(seat).len() == 4));
+        let card = table.hand(2)[0];
+        assert_eq!(table.play(2, card), Err(CribbageCoreError::OutOfTurn));
+    }
+    #[test]
+    fn test_thirty_one_scores_the_go() {
+        let mut table = Table::new(2, 0).unwrap();
+        for seat in 0..2 {
+            let cards = table.hand(seat)[..2].to_vec();
+            table.discard(seat, &cards).unwrap();
+        }
+
+        table.pegger = Pegger::new();
+        for card in cards("KH JC AD") {
+            table.pegger.play_card(card).unwrap();
+        }
+        table.pegging_hands = vec![cards("5D"), cards("TD 2C")];
+        assert_eq!(table.turn(), Some(1));
+
+        let score = table.score(1);
+        table.play(1, cards("TD")[0]).unwrap();
+        assert_eq!(table.score(1), score + 2);
+        assert!(table.deal_events().contains(&SeatEvent::Played {
+            seat: 1,
+            card: cards("TD")[0],
+            count: 31,
+            points: 1,
+        }));
+        assert_eq!(
+            table.deal_events().last(),
+            Some(&SeatEvent::SequenceEnded { seat: 1, points: 1 })
+        );
+        assert_eq!(table.count(), 0);
+        assert_eq!(table.turn(), Some(0));
+    }
+}
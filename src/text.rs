@@ -0,0 +1,142 @@
+// The plain-text forms of seats, cards and table events shared by the engine protocol, saved
+// games and game notation: seats are 1 and 2 and cards use their two-character notation
+
+use std::str::FromStr;
+
+use crate::board::TwoPlayers;
+use crate::card::Card;
+use crate::game::TableEvent;
+
+pub(crate) fn seat(player: TwoPlayers) -> &'static str {
+    match player {
+        TwoPlayers::PlayerOne => "1",
+        TwoPlayers::PlayerTwo => "2",
+    }
+}
+
+pub(crate) fn parse_seat(s: &str) -> Option<TwoPlayers> {
+    match s {
+        "1" => Some(TwoPlayers::PlayerOne),
+        "2" => Some(TwoPlayers::PlayerTwo),
+        _ => None,
+    }
+}
+
+pub(crate) fn format_cards(cards: &[Card]) -> String {
+    cards
+        .iter()
+        .map(Card::to_string)
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+pub(crate) fn parse_cards(words: &[&str]) -> Option<Vec<Card>> {
+    words.iter().map(|word| Card::from_str(word).ok()).collect()
+}
+
+pub(crate) fn format_event(event: &TableEvent) -> String {
+    match *event {
+        TableEvent::DealStarted { dealer } => format!("deal {}", seat(dealer)),
+        TableEvent::Discarded { player } => format!("discard {}", seat(player)),
+        TableEvent::StarterCut { starter, heels } => format!("starter {} {}", starter, heels),
+        TableEvent::Played {
+            player,
+            card,
+            count,
+            points,
+        } => format!("play {} {} {} {}", seat(player), card, count, points),
+        TableEvent::Go { player, count } => format!("go {} {}", seat(player), count),
+        TableEvent::SequenceEnded { player, points } => {
+            format!("last {} {}", seat(player), points)
+        }
+        TableEvent::Shown {
+            player,
+            cards,
+            is_crib,
+            points,
+        } => format!(
+            "show {} {} {} {}",
+            seat(player),
+            if is_crib { "crib" } else { "hand" },
+            format_cards(&cards),
+            points
+        ),
+        TableEvent::GameWon { winner } => format!("win {}", seat(winner)),
+    }
+}
+
+pub(crate) fn parse_event(words: &[&str]) -> Option<TableEvent> {
+    let event = match *words {
+        ["deal", dealer] => TableEvent::DealStarted {
+            dealer: parse_seat(dealer)?,
+        },
+        ["discard", player] => TableEvent::Discarded {
+            player: parse_seat(player)?,
+        },
+        ["starter", starter, heels] => TableEvent::StarterCut {
+            starter: Card::from_str(starter).ok()?,
+            heels: heels.parse().ok()?,
+        },
+        ["play", player, card, count, points] => TableEvent::Played {
+            player: parse_seat(player)?,
+            card: Card::from_str(card).ok()?,
+            count: count.parse().ok()?,
+            points: points.parse().ok()?,
+        },
+        ["go", player, count] => TableEvent::Go {
+            player: parse_seat(player)?,
+            count: count.parse().ok()?,
+        },
+        ["last", player, points] => TableEvent::SequenceEnded {
+            player: parse_seat(player)?,
+            points: points.parse().ok()?,
+        },
+        ["show", player, kind, a, b, c, d, points] => TableEvent::Shown {
+            player: parse_seat(player)?,
+            cards: [
+                Card::from_str(a).ok()?,
+                Card::from_str(b).ok()?,
+                Card::from_str(c).ok()?,
+                Card::from_str(d).ok()?,
+            ],
+            is_crib: match kind {
+                "crib" => true,
+                "hand" => false,
+                _ => return None,
+            },
+            points: points.parse().ok()?,
+        },
+        ["win", winner] => TableEvent::GameWon {
+            winner: parse_seat(winner)?,
+        },
+        _ => return None,
+    };
+
+    Some(event)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::TwoPlayers;
+    use crate::bots::RandomBot;
+    use crate::game::{play_two_player_game, TwoPlayerGame};
+    use crate::text::{format_event, parse_event};
+
+    #[test]
+    fn test_event_round_trip() {
+        let mut game = TwoPlayerGame::with_seed(TwoPlayers::PlayerTwo, 3);
+        play_two_player_game(&mut game, &mut RandomBot::new(1), &mut RandomBot::new(2)).unwrap();
+        for event in game.events() {
+            let line = format_event(event);
+            let words: Vec<&str> = line.split_whitespace().collect();
+            assert_eq!(parse_event(&words), Some(*event), "{}", line);
+        }
+
+        assert_eq!(parse_event(&["play", "3", "5H", "5", "0"]), None);
+        assert_eq!(
+            parse_event(&["show", "1", "box", "5H", "5C", "5S", "JD", "29"]),
+            None
+        );
+        assert_eq!(parse_event(&["win"]), None);
+    }
+}
@@ -0,0 +1,621 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt::{self, Display, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::board::{Board, TwoPlayerScore, TwoPlayers};
+use crate::card::{Card, Rank};
+use crate::deck::Deck;
+use crate::discard::analyze_discards;
+use crate::game::GamePhase;
+use crate::hand::score_hand;
+use crate::pegging::PeggingRound;
+use crate::pegging_analysis::rollout_card;
+use crate::position::{RaceEstimator, WinEstimator};
+
+const HEADER: &str = "cribbage-win-probability";
+const VERSION: u32 = 1;
+const CRIB_SAMPLES: usize = 20;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DealDistribution {
+    deals: u32,
+    pegging: HashMap<(u8, u8), u32>,
+    pone_hand: HashMap<u8, u32>,
+    dealer_show: HashMap<u8, u32>,
+}
+
+impl DealDistribution {
+    pub fn new() -> DealDistribution {
+        DealDistribution::default()
+    }
+
+    // Plays out deals between two players who throw by expected points and peg greedily
+    pub fn simulate(deals: u32, seed: u64) -> DealDistribution {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut deck = Deck::new();
+        let mut distribution = DealDistribution::new();
+        for _ in 0..deals {
+            deck.shuffle_with_rng(&mut rng);
+            let (pone_kept, pone_throw) = simulated_discard(&mut deck, false, &mut rng);
+            let (dealer_kept, dealer_throw) = simulated_discard(&mut deck, true, &mut rng);
+            let starter = match deck.draw() {
+                Ok(starter) => starter,
+                Err(_) => continue,
+            };
+
+            let pone = TwoPlayers::PlayerOne;
+            let dealer = TwoPlayers::PlayerTwo;
+            let mut points: TwoPlayerScore<u8> = TwoPlayerScore::default();
+            if starter.rank() == Rank::Jack {
+                points[dealer] += 2;
+            }
+
+            let hands = TwoPlayerScore::new([pone_kept.to_vec(), dealer_kept.to_vec()]);
+            let mut round = PeggingRound::new(hands, pone);
+            while let Some(card) = rollout_card(&round, &mut rng) {
+                match round.play(card) {
+                    Ok(outcome) => points[outcome.player] += outcome.total_points(),
+                    Err(_) => break,
+                }
+            }
+
+            let crib = [
+                pone_throw[0],
+                pone_throw[1],
+                dealer_throw[0],
+                dealer_throw[1],
+            ];
+            distribution.record(
+                points[pone],
+                points[dealer],
+                score_hand(pone_kept, starter, false),
+                score_hand(dealer_kept, starter, false),
+                score_hand(crib, starter, true),
+            );
+        }
+
+        distribution
+    }
+
+    pub fn record(
+        &mut self,
+        pone_pegging: u8,
+        dealer_pegging: u8,
+        pone_hand: u8,
+        dealer_hand: u8,
+        crib: u8,
+    ) {
+        self.deals += 1;
+        *self
+            .pegging
+            .entry((pone_pegging, dealer_pegging))
+            .or_insert(0) += 1;
+        *self.pone_hand.entry(pone_hand).or_insert(0) += 1;
+        *self.dealer_show.entry(dealer_hand + crib).or_insert(0) += 1;
+    }
+
+    pub fn deals(&self) -> u32 {
+        self.deals
+    }
+
+    pub fn mean_points(&self) -> (f64, f64) {
+        if self.deals == 0 {
+            return (0.0, 0.0);
+        }
+
+        let deals = f64::from(self.deals);
+        let mut pone = 0.0;
+        let mut dealer = 0.0;
+        for (&(pone_pegging, dealer_pegging), &count) in &self.pegging {
+            pone += f64::from(pone_pegging) * f64::from(count);
+            dealer += f64::from(dealer_pegging) * f64::from(count);
+        }
+
+        for (&points, &count) in &self.pone_hand {
+            pone += f64::from(points) * f64::from(count);
+        }
+
+        for (&points, &count) in &self.dealer_show {
+            dealer += f64::from(points) * f64::from(count);
+        }
+
+        (pone / deals, dealer / deals)
+    }
+
+    fn probabilities<K: Copy + Ord>(&self, counts: &HashMap<K, u32>) -> Vec<(K, f64)> {
+        let deals = f64::from(self.deals.max(1));
+        let mut probabilities: Vec<(K, f64)> = counts
+            .iter()
+            .map(|(&key, &count)| (key, f64::from(count) / deals))
+            .collect();
+        probabilities.sort_by_key(|&(key, _)| key);
+        probabilities
+    }
+}
+
+fn simulated_discard<R: Rng + ?Sized>(
+    deck: &mut Deck,
+    is_dealer: bool,
+    rng: &mut R,
+) -> ([Card; 4], [Card; 2]) {
+    let cards = deck.draw_n(6).unwrap_or_default();
+    match analyze_discards(&cards, is_dealer, CRIB_SAMPLES, rng.gen()) {
+        Ok(options) => (
+            options[0].keep,
+            [options[0].discard[0], options[0].discard[1]],
+        ),
+        Err(_) => (
+            [cards[0], cards[1], cards[2], cards[3]],
+            [cards[4], cards[5]],
+        ),
+    }
+}
+
+#[derive(Debug)]
+pub enum WinProbabilityTableError {
+    Io(io::Error),
+    Parse { line: usize },
+    TargetMismatch { table: u8, board: u8 },
+}
+
+impl Display for WinProbabilityTableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            WinProbabilityTableError::Io(error) => write!(f, "{}", error),
+            WinProbabilityTableError::Parse { line } => {
+                write!(f, "line {}: invalid win probability entry", line)
+            }
+            WinProbabilityTableError::TargetMismatch { table, board } => write!(
+                f,
+                "the table is for games to {} but the board plays to {}",
+                table, board
+            ),
+        }
+    }
+}
+
+impl error::Error for WinProbabilityTableError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            WinProbabilityTableError::Io(error) => Some(error),
+            WinProbabilityTableError::Parse { .. }
+            | WinProbabilityTableError::TargetMismatch { .. } => None,
+        }
+    }
+}
+
+impl From<io::Error> for WinProbabilityTableError {
+    fn from(error: io::Error) -> WinProbabilityTableError {
+        WinProbabilityTableError::Io(error)
+    }
+}
+
+// Chances that the pone wins, indexed by the points the pone and the dealer still need, both at
+// the start of a deal and once the pegging is over
+#[derive(Clone, Debug, PartialEq)]
+pub struct WinProbabilityTable {
+    target: u8,
+    start: Vec<f64>,
+    show: Vec<f64>,
+}
+
+impl WinProbabilityTable {
+    pub fn compute(distribution: &DealDistribution, target: u8) -> WinProbabilityTable {
+        let n = usize::from(target);
+        let index = |pone: usize, dealer: usize| pone * (n + 1) + dealer;
+        let pegging = distribution.probabilities(&distribution.pegging);
+        let pone_hand = distribution.probabilities(&distribution.pone_hand);
+        let dealer_show = distribution.probabilities(&distribution.dealer_show);
+
+        let mut start = vec![0.0; (n + 1) * (n + 1)];
+        let mut show = vec![0.0; (n + 1) * (n + 1)];
+        let mut dealer_to_show = vec![0.0; (n + 1) * (n + 1)];
+
+        // Each stage only ever lowers what a player needs, so filling the table in order of the
+        // total still needed leaves just the pointless deal, where the roles swap with nothing
+        // scored, looping back to the same level; a few passes settle it
+        for level in 2..=2 * n {
+            let pairs: Vec<(usize, usize)> = (1..=n)
+                .filter(|&pone| level > pone && level - pone <= n)
+                .map(|pone| (pone, level - pone))
+                .collect();
+
+            for _ in 0..200 {
+                for &(pone, dealer) in &pairs {
+                    let mut total = 0.0;
+                    for &(points, probability) in &dealer_show {
+                        let points = usize::from(points);
+                        if points < dealer {
+                            // The next deal swaps the roles
+                            total += probability * (1.0 - start[index(dealer - points, pone)]);
+                        }
+                    }
+
+                    dealer_to_show[index(pone, dealer)] = total;
+                }
+
+                for &(pone, dealer) in &pairs {
+                    let mut total = 0.0;
+                    for &(points, probability) in &pone_hand {
+                        let points = usize::from(points);
+                        total += probability
+                            * if points >= pone {
+                                1.0
+                            } else {
+                                dealer_to_show[index(pone - points, dealer)]
+                            };
+                    }
+
+                    show[index(pone, dealer)] = total;
+                }
+
+                let mut change: f64 = 0.0;
+                for &(pone, dealer) in &pairs {
+                    let mut total = 0.0;
+                    for &((pone_points, dealer_points), probability) in &pegging {
+                        let pone_points = usize::from(pone_points);
+                        let dealer_points = usize::from(dealer_points);
+                        total += probability
+                            * match (pone_points >= pone, dealer_points >= dealer) {
+                                (true, true) => 0.5,
+                                (true, false) => 1.0,
+                                (false, true) => 0.0,
+                                (false, false) => {
+                                    show[index(pone - pone_points, dealer - dealer_points)]
+                                }
+                            };
+                    }
+
+                    change = change.max((total - start[index(pone, dealer)]).abs());
+                    start[index(pone, dealer)] = total;
+                }
+
+                if change < 1e-12 {
+                    break;
+                }
+            }
+        }
+
+        WinProbabilityTable {
+            target,
+            start,
+            show,
+        }
+    }
+
+    pub fn simulated(deals: u32, seed: u64, target: u8) -> WinProbabilityTable {
+        WinProbabilityTable::compute(&DealDistribution::simulate(deals, seed), target)
+    }
+
+    pub fn target(&self) -> u8 {
+        self.target
+    }
+
+    pub fn win_probability_in_phase(
+        &self,
+        own_score: u8,
+        opponent_score: u8,
+        is_dealer: bool,
+        phase: GamePhase,
+    ) -> f64 {
+        let own_needed = self.target.saturating_sub(own_score);
+        let opponent_needed = self.target.saturating_sub(opponent_score);
+        if own_needed == 0 {
+            return 1.0;
+        }
+
+        if opponent_needed == 0 {
+            return 0.0;
+        }
+
+        let table = match phase {
+            GamePhase::Discard | GamePhase::Pegging => &self.start,
+            GamePhase::Show => &self.show,
+            GamePhase::Finished => {
+                return if own_score >= opponent_score {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        };
+
+        if is_dealer {
+            1.0 - self.lookup(table, opponent_needed, own_needed)
+        } else {
+            self.lookup(table, own_needed, opponent_needed)
+        }
+    }
+
+    pub fn board_probabilities(
+        &self,
+        board: &Board<TwoPlayers, TwoPlayerScore<u8>>,
+        dealer: TwoPlayers,
+        phase: Option<GamePhase>,
+    ) -> Result<TwoPlayerScore<f64>, WinProbabilityTableError> {
+        if board.target() != self.target {
+            return Err(WinProbabilityTableError::TargetMismatch {
+                table: self.target,
+                board: board.target(),
+            });
+        }
+
+        let pone = dealer.other();
+        let pone_wins = self.win_probability_in_phase(
+            board.score(pone),
+            board.score(dealer),
+            false,
+            phase.unwrap_or(GamePhase::Discard),
+        );
+
+        let mut probabilities = TwoPlayerScore::new([0.0, 0.0]);
+        probabilities[pone] = pone_wins;
+        probabilities[dealer] = 1.0 - pone_wins;
+        Ok(probabilities)
+    }
+
+    fn lookup(&self, table: &[f64], pone_needed: u8, dealer_needed: u8) -> f64 {
+        let n = usize::from(self.target);
+        let pone = usize::from(pone_needed).min(n);
+        let dealer = usize::from(dealer_needed).min(n);
+        table[pone * (n + 1) + dealer]
+    }
+
+    pub fn from_reader<R: BufRead>(
+        reader: R,
+    ) -> Result<WinProbabilityTable, WinProbabilityTableError> {
+        let mut lines = reader.lines();
+        let header = lines
+            .next()
+            .ok_or(WinProbabilityTableError::Parse { line: 1 })??;
+        let fields: Vec<&str> = header.split_whitespace().collect();
+        let target = match fields.as_slice() {
+            [name, version, target] if *name == HEADER && version.parse() == Ok(VERSION) => target
+                .parse::<u8>()
+                .map_err(|_| WinProbabilityTableError::Parse { line: 1 })?,
+            _ => return Err(WinProbabilityTableError::Parse { line: 1 }),
+        };
+
+        let n = usize::from(target);
+        let mut table = WinProbabilityTable {
+            target,
+            start: vec![0.0; (n + 1) * (n + 1)],
+            show: vec![0.0; (n + 1) * (n + 1)],
+        };
+
+        // Every cell must appear exactly once, so a repeated row can't stand in for a missing one
+        let mut filled = vec![false; (n + 1) * (n + 1)];
+        let mut last_line = 1;
+        for (number, line) in lines.enumerate() {
+            let line = line?;
+            let number = number + 2;
+            last_line = number;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            let error = || WinProbabilityTableError::Parse { line: number };
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 4 {
+                return Err(error());
+            }
+
+            let pone: usize = fields[0].parse().map_err(|_| error())?;
+            let dealer: usize = fields[1].parse().map_err(|_| error())?;
+            let start: f64 = fields[2].parse().map_err(|_| error())?;
+            let show: f64 = fields[3].parse().map_err(|_| error())?;
+            if pone == 0 || dealer == 0 || pone > n || dealer > n {
+                return Err(error());
+            }
+
+            let index = pone * (n + 1) + dealer;
+            if filled[index] {
+                return Err(error());
+            }
+
+            filled[index] = true;
+            table.start[index] = start;
+            table.show[index] = show;
+        }
+
+        let complete = (1..=n).all(|pone| (1..=n).all(|dealer| filled[pone * (n + 1) + dealer]));
+        if !complete {
+            return Err(WinProbabilityTableError::Parse {
+                line: last_line + 1,
+            });
+        }
+
+        Ok(table)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<WinProbabilityTable, WinProbabilityTableError> {
+        WinProbabilityTable::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    pub fn load_or_compute<P, F>(
+        path: P,
+        compute: F,
+    ) -> Result<WinProbabilityTable, WinProbabilityTableError>
+    where
+        P: AsRef<Path>,
+        F: FnOnce() -> WinProbabilityTable,
+    {
+        if path.as_ref().exists() {
+            return WinProbabilityTable::load(path);
+        }
+
+        let table = compute();
+        table.save(path)?;
+        Ok(table)
+    }
+
+    pub fn write_to<W: Write>(&self, writer: W) -> io::Result<()> {
+        let mut writer = BufWriter::new(writer);
+        writeln!(writer, "{} {} {}", HEADER, VERSION, self.target)?;
+        let n = usize::from(self.target);
+        for pone in 1..=n {
+            for dealer in 1..=n {
+                let index = pone * (n + 1) + dealer;
+                writeln!(
+                    writer,
+                    "{} {} {} {}",
+                    pone, dealer, self.start[index], self.show[index]
+                )?;
+            }
+        }
+
+        writer.flush()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_to(File::create(path)?)
+    }
+}
+
+// The table only knows games to its own target, so games to any other target fall back to the
+// race approximation
+impl WinEstimator for WinProbabilityTable {
+    fn win_probability(
+        &self,
+        own_score: u8,
+        opponent_score: u8,
+        is_dealer: bool,
+        target: u8,
+    ) -> f64 {
+        if target != self.target {
+            return RaceEstimator.win_probability(own_score, opponent_score, is_dealer, target);
+        }
+
+        self.win_probability_in_phase(own_score, opponent_score, is_dealer, GamePhase::Discard)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::board::{custom_board, TwoPlayerScore, TwoPlayers};
+    use crate::game::GamePhase;
+    use crate::position::{RaceEstimator, WinEstimator};
+    use crate::win_probability::{DealDistribution, WinProbabilityTable, WinProbabilityTableError};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_deterministic_deals() {
+        // The dealer always shows five, so whoever deals first to a target of five wins
+        let mut distribution = DealDistribution::new();
+        distribution.record(0, 0, 0, 5, 0);
+        let table = WinProbabilityTable::compute(&distribution, 5);
+        assert_eq!(table.win_probability(0, 0, true, 5), 1.0);
+        assert_eq!(table.win_probability(0, 0, false, 5), 0.0);
+        assert_eq!(table.win_probability(4, 0, false, 5), 0.0);
+
+        // Half the deals score nothing and the roles swap, so the first dealer wins two in three
+        distribution.record(0, 0, 0, 0, 0);
+        let table = WinProbabilityTable::compute(&distribution, 5);
+        assert!((table.win_probability(0, 0, true, 5) - 2.0 / 3.0).abs() < 1e-9);
+        assert!((table.win_probability(0, 0, false, 5) - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_counting_order() {
+        // Both need three; the pone counts a three-point hand before the dealer can show
+        let mut distribution = DealDistribution::new();
+        distribution.record(1, 1, 3, 10, 10);
+        let table = WinProbabilityTable::compute(&distribution, 5);
+        assert_eq!(table.win_probability(0, 0, false, 5), 0.0);
+        assert_eq!(
+            table.win_probability_in_phase(2, 2, false, GamePhase::Show),
+            1.0
+        );
+        assert_eq!(
+            table.win_probability_in_phase(1, 0, false, GamePhase::Discard),
+            1.0
+        );
+        assert_eq!(
+            table.win_probability_in_phase(5, 0, true, GamePhase::Show),
+            1.0
+        );
+
+        let board = custom_board(TwoPlayerScore::new([2, 2]), 5);
+        let probabilities = table
+            .board_probabilities(&board, TwoPlayers::PlayerTwo, Some(GamePhase::Show))
+            .unwrap();
+        assert_eq!(probabilities[TwoPlayers::PlayerOne], 1.0);
+        assert_eq!(probabilities[TwoPlayers::PlayerTwo], 0.0);
+
+        let board = custom_board(TwoPlayerScore::new([2, 2]), 7);
+        match table.board_probabilities(&board, TwoPlayers::PlayerTwo, None) {
+            Err(WinProbabilityTableError::TargetMismatch { table: 5, board: 7 }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(
+            table.win_probability(0, 0, false, 121),
+            RaceEstimator.win_probability(0, 0, false, 121)
+        );
+    }
+
+    #[test]
+    fn test_simulated_table() {
+        let distribution = DealDistribution::simulate(300, 1);
+        assert_eq!(distribution.deals(), 300);
+        let (pone, dealer) = distribution.mean_points();
+        assert!(dealer > pone);
+        assert!(pone > 6.0 && dealer < 25.0);
+
+        let table = WinProbabilityTable::compute(&distribution, 121);
+        let first_dealer = table.win_probability(0, 0, true, 121);
+        assert!(first_dealer > 0.5 && first_dealer < 0.65);
+        assert!(table.win_probability(120, 0, false, 121) > 0.99);
+        assert!(
+            table.win_probability(100, 90, false, 121) > table.win_probability(90, 90, false, 121)
+        );
+
+        let mut buffer = Vec::new();
+        table.write_to(&mut buffer).unwrap();
+        let loaded = WinProbabilityTable::from_reader(Cursor::new(buffer)).unwrap();
+        assert_eq!(loaded, table);
+    }
+
+    #[test]
+    fn test_load_errors() {
+        match WinProbabilityTable::from_reader(Cursor::new("not a table\n")) {
+            Err(WinProbabilityTableError::Parse { line: 1 }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match WinProbabilityTable::from_reader(Cursor::new(
+            "cribbage-win-probability 1 2\n1 1 0.5 0.5\n1 x 0.5 0.5\n",
+        )) {
+            Err(WinProbabilityTableError::Parse { line: 3 }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        match WinProbabilityTable::from_reader(Cursor::new(
+            "cribbage-win-probability 1 2\n1 1 0.5 0.5\n",
+        )) {
+            Err(WinProbabilityTableError::Parse { .. }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // A repeated cell is rejected where it repeats, even when the row count adds up
+        match WinProbabilityTable::from_reader(Cursor::new(
+            "cribbage-win-probability 1 2\n1 1 0.5 0.5\n1 2 0.5 0.5\n1 1 0.5 0.5\n2 2 0.5 0.5\n",
+        )) {
+            Err(WinProbabilityTableError::Parse { line: 4 }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // A missing cell is reported past the last line
+        match WinProbabilityTable::from_reader(Cursor::new(
+            "cribbage-win-probability 1 2\n1 1 0.5 0.5\n1 2 0.5 0.5\n2 2 0.5 0.5\n",
+        )) {
+            Err(WinProbabilityTableError::Parse { line: 5 }) => {}
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+}